@@ -1,16 +1,21 @@
 use std::path::PathBuf;
-use std::time::Duration;
+use std::thread;
 
 use adw::prelude::*;
-use gtk::gdk_pixbuf::Pixbuf;
 
 use crate::capture::{
-    CaptureTarget, is_window_protocol_unsupported_error, list_windows, take_screenshot,
+    CaptureError, CaptureTarget, ImageFormat, WindowInfo, WorkspaceInfo, last_region_geometry,
+    list_capturable_windows, list_workspaces, screenshot_output_path,
+    take_padded_window_screenshot, take_screenshot, take_screenshot_with_geometry,
     take_window_screenshot, take_window_screenshot_via_niri,
 };
+use crate::config::config;
+use crate::history::{CaptureKind, apply_retention, log_capture};
+use crate::imaging::{Encoding, load_image, save_image};
 use crate::ui::{
     CaptureMode, InteractiveDialogResult, build_interactive_dialog, build_save_dialog,
-    show_window_picker,
+    install_app_actions, install_app_css, load_frozen_frame, show_countdown_overlay,
+    show_frozen_countdown, show_window_picker,
 };
 
 pub fn run() {
@@ -18,15 +23,38 @@ pub fn run() {
         .application_id("io.ncaptura.app")
         .build();
 
-    app.connect_activate(build_ui);
+    app.connect_startup(|app| {
+        install_app_actions(app);
+        install_app_css();
+        thread::spawn(|| apply_retention(None));
+    });
+    app.connect_activate(activate);
     app.run();
 }
 
+fn activate(app: &adw::Application) {
+    match app
+        .active_window()
+        .or_else(|| app.windows().into_iter().next())
+    {
+        Some(window) => window.present(),
+        None => build_ui(app),
+    }
+}
+
 fn build_ui(app: &adw::Application) {
     let app_clone = app.clone();
     let _window = build_interactive_dialog(app, move |result| {
         let guard = app_clone.hold();
-        perform_capture(&app_clone, &result, guard);
+        if !config().hide_window_before_capture {
+            perform_capture(&app_clone, &result, guard);
+            return;
+        }
+
+        let app = app_clone.clone();
+        gtk::glib::timeout_add_local_once(config().hide_window_settle(), move || {
+            perform_capture(&app, &result, guard);
+        });
     });
 }
 
@@ -35,17 +63,15 @@ fn perform_capture(
     result: &InteractiveDialogResult,
     guard: gtk::gio::ApplicationHoldGuard,
 ) {
-    let _ = result.show_pointer;
-
     match result.mode {
         CaptureMode::Screen => {
-            schedule_target_capture(app, CaptureTarget::Fullscreen, result.delay_seconds, guard);
+            schedule_target_capture(app, result.screen_target.clone(), result.clone(), guard);
         }
         CaptureMode::Selection => {
-            schedule_target_capture(app, CaptureTarget::Region, result.delay_seconds, guard);
+            schedule_target_capture(app, CaptureTarget::Region, result.clone(), guard);
         }
         CaptureMode::Window => {
-            show_window_picker_for_capture(app, result.delay_seconds, guard);
+            show_window_picker_for_capture(app, result.show_pointer, result.delay_seconds, guard);
         }
     }
 }
@@ -53,98 +79,218 @@ fn perform_capture(
 fn schedule_target_capture(
     app: &adw::Application,
     target: CaptureTarget,
-    delay_seconds: u32,
+    options: InteractiveDialogResult,
     guard: gtk::gio::ApplicationHoldGuard,
 ) {
-    if delay_seconds > 0 {
-        let app = app.clone();
-        gtk::glib::timeout_add_local_once(Duration::from_secs(delay_seconds as u64), move || {
-            take_and_show(&app, target, guard);
+    if options.freeze && matches!(target, CaptureTarget::Region | CaptureTarget::Fullscreen) {
+        freeze_and_capture(app, target, options, guard);
+    } else if options.delay_seconds > 0 {
+        let capture_app = app.clone();
+        show_countdown_overlay(app, options.delay_seconds, move || {
+            take_and_show(&capture_app, target, options, guard);
         });
     } else {
-        take_and_show(app, target, guard);
+        take_and_show(app, target, options, guard);
     }
 }
 
-fn show_window_picker_for_capture(
+fn freeze_and_capture(
     app: &adw::Application,
-    delay_seconds: u32,
+    target: CaptureTarget,
+    options: InteractiveDialogResult,
     guard: gtk::gio::ApplicationHoldGuard,
 ) {
-    let mut windows = match list_windows() {
-        Ok(items) => items,
+    let frame = match load_frozen_frame(options.show_pointer) {
+        Ok(frame) => frame,
         Err(err) => {
-            eprintln!("读取窗口列表失败: {err}");
+            eprintln!("冻结屏幕失败: {err:#}");
             return;
         }
     };
 
-    windows.retain(|w| w.app_id != "io.ncaptura.app");
-    if windows.is_empty() {
-        eprintln!("没有可供选择的窗口");
-        return;
-    }
-
-    let picker_app = app.clone();
+    let select_region = matches!(target, CaptureTarget::Region);
+    let target_label = target.slug();
     let capture_app = app.clone();
-    show_window_picker(&picker_app, windows, guard, move |window_id, guard| {
-        if delay_seconds > 0 {
-            let app = capture_app.clone();
-            gtk::glib::timeout_add_local_once(
-                Duration::from_secs(delay_seconds as u64),
-                move || {
-                    take_window_and_show(&app, window_id, guard);
-                },
-            );
-        } else {
-            take_window_and_show(&capture_app, window_id, guard);
+    show_frozen_countdown(
+        app,
+        &frame,
+        options.delay_seconds,
+        select_region,
+        move |captured| {
+            let _guard = guard;
+            let Some(captured) = captured else {
+                return;
+            };
+
+            let path = match screenshot_output_path(target) {
+                Ok(path) => path,
+                Err(err) => {
+                    eprintln!("截图失败: {err}");
+                    return;
+                }
+            };
+            if let Err(err) = save_image(&captured, &path, Encoding::Png) {
+                log_capture(
+                    CaptureKind::Screenshot,
+                    target_label,
+                    Err(format!("{err:#}")),
+                );
+                eprintln!("截图失败: {err:#}");
+                return;
+            }
+            log_capture(CaptureKind::Screenshot, target_label, Ok(&path));
+
+            show_save_dialog_for_path(&capture_app, path);
+        },
+    );
+}
+
+fn show_window_picker_for_capture(
+    app: &adw::Application,
+    show_pointer: bool,
+    delay_seconds: u32,
+    guard: gtk::gio::ApplicationHoldGuard,
+) {
+    let app = app.clone();
+    gtk::glib::spawn_future_local(async move {
+        let listing = gtk::gio::spawn_blocking(|| {
+            list_capturable_windows()
+                .map(|windows| (windows, list_workspaces().unwrap_or_default()))
+        });
+        let (windows, workspaces) = match listing.await {
+            Ok(Ok(items)) => items,
+            Ok(Err(err)) => {
+                eprintln!("读取窗口列表失败: {err}");
+                return;
+            }
+            Err(_) => {
+                eprintln!("读取窗口列表失败: 工作线程异常退出");
+                return;
+            }
+        };
+
+        if windows.is_empty() {
+            eprintln!("没有可供选择的窗口");
+            return;
         }
+
+        open_window_picker(
+            &app,
+            windows,
+            workspaces,
+            show_pointer,
+            delay_seconds,
+            guard,
+        );
     });
 }
 
+fn open_window_picker(
+    app: &adw::Application,
+    windows: Vec<WindowInfo>,
+    workspaces: Vec<WorkspaceInfo>,
+    show_pointer: bool,
+    delay_seconds: u32,
+    guard: gtk::gio::ApplicationHoldGuard,
+) {
+    let picker_app = app.clone();
+    let capture_app = app.clone();
+    show_window_picker(
+        &picker_app,
+        windows,
+        &workspaces,
+        false,
+        guard,
+        move |window_id, include_shadow, guard| {
+            let padding = include_shadow.then_some(config().window_padding);
+            if delay_seconds > 0 {
+                let app = capture_app.clone();
+                show_countdown_overlay(&capture_app, delay_seconds, move || {
+                    take_window_and_show(&app, window_id, show_pointer, padding, guard);
+                });
+            } else {
+                take_window_and_show(&capture_app, window_id, show_pointer, padding, guard);
+            }
+        },
+    );
+}
+
 fn take_and_show(
     app: &adw::Application,
     target: CaptureTarget,
-    _guard: gtk::gio::ApplicationHoldGuard,
+    options: InteractiveDialogResult,
+    guard: gtk::gio::ApplicationHoldGuard,
 ) {
-    let path = match take_screenshot(target) {
-        Ok(path) => path,
-        Err(err) => {
-            eprintln!("截图失败: {err}");
-            return;
+    let target_label = target.slug().to_string();
+    capture_in_background(app, guard, target_label, "截图失败", move || {
+        let repeated_geometry = match target {
+            CaptureTarget::Region if options.repeat_region => last_region_geometry(),
+            _ => None,
+        };
+        match repeated_geometry {
+            Some(geometry) => take_screenshot_with_geometry(
+                &geometry,
+                options.show_pointer,
+                false,
+                ImageFormat::Png,
+            ),
+            None => take_screenshot(target, options.show_pointer),
         }
-    };
-
-    show_save_dialog_for_path(app, path);
+    });
 }
 
 fn take_window_and_show(
     app: &adw::Application,
     window_id: u64,
-    _guard: gtk::gio::ApplicationHoldGuard,
+    show_pointer: bool,
+    padding: Option<u32>,
+    guard: gtk::gio::ApplicationHoldGuard,
 ) {
-    let path = match take_window_screenshot(window_id, false) {
-        Ok(path) => path,
-        Err(err) => {
-            if is_window_protocol_unsupported_error(&err) {
-                if let Err(niri_err) = take_window_screenshot_via_niri(window_id) {
-                    eprintln!("窗口截图失败: {niri_err}");
-                }
-                return;
+    let target_label = format!("window {window_id}");
+    capture_in_background(app, guard, target_label, "窗口截图失败", move || {
+        let result = match padding {
+            Some(padding) => take_padded_window_screenshot(window_id, padding, show_pointer, false),
+            None => take_window_screenshot(window_id, show_pointer, false),
+        };
+        match result {
+            Err(CaptureError::ProtocolUnsupported(_)) => {
+                take_window_screenshot_via_niri(window_id, false)
             }
-            eprintln!("窗口截图失败: {err}");
-            return;
+            result => result,
         }
-    };
+    });
+}
 
-    show_save_dialog_for_path(app, path);
+fn capture_in_background(
+    app: &adw::Application,
+    guard: gtk::gio::ApplicationHoldGuard,
+    target_label: String,
+    error_message: &'static str,
+    capture: impl FnOnce() -> Result<PathBuf, CaptureError> + Send + 'static,
+) {
+    let app = app.clone();
+    gtk::glib::spawn_future_local(async move {
+        let _guard = guard;
+        match gtk::gio::spawn_blocking(capture).await {
+            Ok(Ok(path)) => {
+                log_capture(CaptureKind::Screenshot, &target_label, Ok(&path));
+                show_save_dialog_for_path(&app, path);
+            }
+            Ok(Err(CaptureError::Cancelled)) => {}
+            Ok(Err(err)) => {
+                log_capture(CaptureKind::Screenshot, &target_label, Err(err.to_string()));
+                eprintln!("{error_message}: {err}");
+            }
+            Err(_) => eprintln!("{error_message}: 工作线程异常退出"),
+        }
+    });
 }
 
 fn show_save_dialog_for_path(app: &adw::Application, path: PathBuf) {
-    let pixbuf = match Pixbuf::from_file(&path) {
+    let pixbuf = match load_image(&path) {
         Ok(pixbuf) => pixbuf,
         Err(err) => {
-            eprintln!("无法加载截图: {err}");
+            eprintln!("无法加载截图: {err:#}");
             return;
         }
     };
@@ -1,21 +1,115 @@
+use std::env;
 use std::fs::File;
-use std::io;
+use std::io::{self, Read, Write};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
+use gtk::gdk;
+use gtk::gdk::prelude::*;
+use gtk::gdk_pixbuf::Pixbuf;
 
-pub(crate) fn run_command(mut command: Command, context_message: &str) -> Result<()> {
-    let output = command
-        .output()
-        .with_context(|| format!("{context_message}: 无法启动命令"))?;
+use crate::capture::error::{Context, Result, bail};
+use crate::capture::runner::{command_output, command_output_with_stdin};
+use crate::capture::state::{read_last_region, write_last_region};
+use crate::capture::windows::visible_window_geometries;
+use crate::capture::{CaptureError, OutputGeometry};
+use crate::config::config;
+
+const SCREEN_CAPTURE_PROTOCOL_UNSUPPORTED: &str =
+    "compositor doesn't support the screen capture protocol";
+const TRANSIENT_CAPTURE_ERRORS: &[&str] = &[
+    "failed to copy output",
+    "failed to create buffer",
+    "failed to create wl_shm pool",
+    "(protocol error) dispatching to wayland display",
+];
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+pub const HOLD_CLIPBOARD_FLAG: &str = "--hold-clipboard";
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_quiet(enabled: bool) {
+    QUIET.store(enabled, Ordering::Relaxed);
+}
+
+fn notice(message: &str) {
+    if !QUIET.load(Ordering::Relaxed) {
+        eprintln!("{message}");
+    }
+}
+
+fn verbose(message: &str) {
+    if VERBOSE.load(Ordering::Relaxed) {
+        eprintln!("{message}");
+    }
+}
+
+pub(crate) fn run_command(command: Command, context_message: &str) -> Result<()> {
+    run_command_output(command, context_message)?;
+    Ok(())
+}
+
+pub(crate) fn run_command_output(mut command: Command, context_message: &str) -> Result<Vec<u8>> {
+    run_command_output_ref(&mut command, context_message)
+}
+
+pub(crate) fn run_command_with_retries(
+    mut command: Command,
+    context_message: &str,
+    retries: u32,
+) -> Result<Vec<u8>> {
+    let mut attempt = 0;
+    loop {
+        match run_command_output_ref(&mut command, context_message) {
+            Err(err) if attempt < retries && is_transient_capture_error(&err) => {
+                attempt += 1;
+                notice(&format!("{err}，第 {attempt}/{retries} 次重试"));
+                thread::sleep(RETRY_BACKOFF * attempt);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient_capture_error(err: &CaptureError) -> bool {
+    let CaptureError::Failed(message) = err else {
+        return false;
+    };
+    let message = message.to_lowercase();
+    TRANSIENT_CAPTURE_ERRORS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+fn run_command_output_ref(command: &mut Command, context_message: &str) -> Result<Vec<u8>> {
+    let output = match command_output(command) {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let program = command.get_program().to_string_lossy().into_owned();
+            return Err(CaptureError::ToolNotFound(program));
+        }
+        Err(err) => return Err(err).with_context(|| format!("{context_message}: 无法启动命令")),
+    };
 
     if output.status.success() {
-        return Ok(());
+        return Ok(output.stdout);
     }
 
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stderr = stderr.trim();
+    if stderr.contains(SCREEN_CAPTURE_PROTOCOL_UNSUPPORTED) {
+        return Err(CaptureError::ProtocolUnsupported(format!(
+            "{context_message}: {stderr}"
+        )));
+    }
     if stderr.is_empty() {
         bail!("{context_message}: 退出码 {}", output.status);
     }
@@ -23,26 +117,145 @@ pub(crate) fn run_command(mut command: Command, context_message: &str) -> Result
     bail!("{context_message}: {stderr}");
 }
 
-pub(crate) fn pick_region_geometry() -> Result<String> {
-    let output = Command::new("slurp")
-        .output()
-        .context("无法启动 slurp，请确认已安装")?;
-
-    if !output.status.success() {
-        bail!("区域选择已取消或 slurp 执行失败");
+fn run_slurp(single_point: bool, candidates: &[OutputGeometry]) -> Result<String> {
+    let config = config();
+    let mut command = Command::new("slurp");
+    if single_point {
+        command.arg("-p");
+    }
+    for (flag, value) in [
+        ("-b", &config.slurp_background_color),
+        ("-c", &config.slurp_border_color),
+        ("-s", &config.slurp_selection_color),
+    ] {
+        if let Some(value) = value {
+            command.args([flag, value]);
+        }
+    }
+    if let Some(weight) = config.slurp_border_weight {
+        command.args(["-w", &weight.to_string()]);
     }
 
+    let output = if candidates.is_empty() {
+        command_output(&mut command).map_err(|err| tool_spawn_error("slurp", err))?
+    } else {
+        slurp_with_candidates(command, candidates)?
+    };
+
     let geometry = String::from_utf8(output.stdout).context("slurp 输出不是有效文本")?;
     let geometry = geometry.trim().to_string();
 
     if geometry.is_empty() {
-        bail!("未获取到区域坐标");
+        return Err(CaptureError::Cancelled);
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("slurp 执行失败: {}", stderr.trim());
     }
 
     Ok(geometry)
 }
 
-pub(crate) fn default_system_mix_audio_device() -> Option<String> {
+fn slurp_with_candidates(mut command: Command, candidates: &[OutputGeometry]) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| tool_spawn_error("slurp", err))?;
+
+    let mut child_stdin = child.stdin.take().context("无法写入 slurp 输入流")?;
+    let boxes: String = candidates
+        .iter()
+        .map(|geometry| format!("{geometry}\n"))
+        .collect();
+    child_stdin
+        .write_all(boxes.as_bytes())
+        .context("写入窗口候选区域失败")?;
+    drop(child_stdin);
+
+    child.wait_with_output().context("等待 slurp 结束失败")
+}
+
+pub(crate) fn pick_region_geometry() -> Result<String> {
+    if config().snap_region_to_windows {
+        return pick_region_geometry_with_snapping();
+    }
+    remember_region(run_slurp(false, &[])?)
+}
+
+pub(crate) fn pick_region_geometry_with_snapping() -> Result<String> {
+    let candidates = visible_window_geometries().unwrap_or_else(|err| {
+        eprintln!("无法获取窗口位置，区域选择不吸附窗口: {err}");
+        Vec::new()
+    });
+    remember_region(run_slurp(false, &candidates)?)
+}
+
+fn remember_region(geometry: String) -> Result<String> {
+    if let Err(err) = write_last_region(&geometry) {
+        eprintln!("无法保存本次选择的区域: {err}");
+    }
+
+    Ok(geometry)
+}
+
+pub fn pick_point() -> Result<(i64, i64)> {
+    let selection = run_slurp(true, &[])?;
+    let position = selection.split_whitespace().next().unwrap_or_default();
+    position
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+        .with_context(|| format!("无法解析 slurp 返回的坐标: {selection}"))
+}
+
+pub fn last_region_geometry() -> Option<String> {
+    read_last_region().and_then(|geometry| parse_geometry(&geometry).ok())
+}
+
+pub fn parse_geometry(input: &str) -> Result<String> {
+    let invalid = || {
+        CaptureError::Failed(format!(
+            "区域格式无效: \"{input}\"，应为 \"X,Y WxH\"，例如 \"100,100 640x480\""
+        ))
+    };
+
+    let mut parts = input.split_whitespace();
+    let (Some(position), Some(size), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+
+    let (x, y) = position.split_once(',').ok_or_else(invalid)?;
+    let x: i32 = x.trim().parse().map_err(|_| invalid())?;
+    let y: i32 = y.trim().parse().map_err(|_| invalid())?;
+
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+
+    if width == 0 || height == 0 {
+        bail!("区域宽高必须大于 0: \"{input}\"");
+    }
+
+    Ok(format!("{x},{y} {width}x{height}"))
+}
+
+pub(crate) fn geometry_rect(geometry: &str) -> Result<OutputGeometry> {
+    let normalized = parse_geometry(geometry)?;
+    let (position, size) = normalized.split_once(' ').context("区域格式无效")?;
+    let (x, y) = position.split_once(',').context("区域格式无效")?;
+    let (width, height) = size.split_once('x').context("区域格式无效")?;
+    let number = |value: &str| value.parse::<i64>().context("区域格式无效");
+    Ok(OutputGeometry {
+        x: number(x)?,
+        y: number(y)?,
+        width: number(width)?,
+        height: number(height)?,
+    })
+}
+
+pub fn default_system_mix_audio_device() -> Option<String> {
     let output = Command::new("pactl")
         .arg("get-default-sink")
         .output()
@@ -63,24 +276,280 @@ pub(crate) fn default_system_mix_audio_device() -> Option<String> {
 }
 
 pub(crate) fn copy_image_to_clipboard(path: &Path) -> Result<()> {
-    let mut child = Command::new("wl-copy")
-        .arg("--type")
-        .arg("image/png")
+    if gtk::is_initialized_main_thread() {
+        let pixbuf = Pixbuf::from_file(path)
+            .with_context(|| format!("无法读取截图文件: {}", path.display()))?;
+        set_clipboard_image(&pixbuf).context("截图已保存，但复制到剪贴板失败")?;
+        verbose("剪贴板: 已通过 GTK 复制截图");
+        return Ok(());
+    }
+
+    let mime_type = image_mime_type(path)?;
+    let mut image_file =
+        File::open(path).with_context(|| format!("无法读取截图文件: {}", path.display()))?;
+    match pipe_to_wl_copy(&["--type", mime_type], &mut image_file) {
+        Err(CaptureError::ToolNotFound(_)) if env::var_os("WAYLAND_DISPLAY").is_some() => {
+            hold_clipboard_in_background(path).context("截图已保存，但复制到剪贴板失败")?;
+            verbose("剪贴板: 未找到 wl-copy，已交由后台 GTK 进程持有截图");
+            Ok(())
+        }
+        result => {
+            result.context("截图已保存，但复制到剪贴板失败")?;
+            verbose("剪贴板: 已通过 wl-copy 复制截图");
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn image_mime_type(path: &Path) -> Result<&'static str> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if let Some(mime_type) = extension.as_deref().and_then(mime_type_for_extension) {
+        return Ok(mime_type);
+    }
+
+    let mut header = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .with_context(|| format!("无法读取截图文件: {}", path.display()))?;
+    match header {
+        [0x89, b'P', b'N', b'G'] => Ok("image/png"),
+        [0xFF, 0xD8, 0xFF, _] => Ok("image/jpeg"),
+        [b'P', b'6', ..] => Ok("image/x-portable-pixmap"),
+        _ => bail!("无法识别截图文件格式: {}", path.display()),
+    }
+}
+
+fn mime_type_for_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "ppm" => Some("image/x-portable-pixmap"),
+        _ => None,
+    }
+}
+
+pub fn set_clipboard_image(pixbuf: &Pixbuf) -> Result<()> {
+    let display = gdk::Display::default().context("没有可用的显示")?;
+    display
+        .clipboard()
+        .set_texture(&gdk::Texture::for_pixbuf(pixbuf));
+    Ok(())
+}
+
+fn hold_clipboard_in_background(path: &Path) -> Result<()> {
+    let executable = env::current_exe().context("无法定位 ncaptura 可执行文件")?;
+    let mut child = Command::new(executable)
+        .arg(HOLD_CLIPBOARD_FLAG)
         .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
         .spawn()
-        .context("无法启动 wl-copy，请确认已安装")?;
+        .context("无法启动剪贴板后台进程")?;
 
-    let mut child_stdin = child.stdin.take().context("无法写入 wl-copy 输入流")?;
+    let mut child_stdin = child.stdin.take().context("无法写入剪贴板后台进程")?;
     let mut image_file =
         File::open(path).with_context(|| format!("无法读取截图文件: {}", path.display()))?;
-
     io::copy(&mut image_file, &mut child_stdin).context("写入剪贴板数据失败")?;
-    drop(child_stdin);
+    Ok(())
+}
+
+pub fn copy_text_to_clipboard(text: &str) -> Result<()> {
+    pipe_to_wl_copy(&[], &mut text.as_bytes()).context("复制文本到剪贴板失败")
+}
+
+fn pipe_to_wl_copy(args: &[&str], input: &mut impl Read) -> Result<()> {
+    let mut command = Command::new("wl-copy");
+    command.args(args);
+    let output = command_output_with_stdin(&mut command, input)
+        .map_err(|err| tool_spawn_error("wl-copy", err))?;
 
-    let status = child.wait().context("等待 wl-copy 结束失败")?;
-    if !status.success() {
-        bail!("截图已保存，但复制到剪贴板失败");
+    if !output.status.success() {
+        bail!("wl-copy 退出码 {}", output.status);
     }
 
     Ok(())
 }
+
+pub(crate) fn tool_spawn_error(program: &str, err: io::Error) -> CaptureError {
+    match err.kind() {
+        io::ErrorKind::NotFound => CaptureError::ToolNotFound(program.to_string()),
+        _ => CaptureError::Io {
+            context: format!("无法启动 {program}"),
+            source: err,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::capture::runner::{MockCommandRunner, with_command_runner};
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ncaptura-mime-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn mime_type_for_extension_maps_known_extensions() {
+        assert_eq!(mime_type_for_extension("png"), Some("image/png"));
+        assert_eq!(mime_type_for_extension("jpg"), Some("image/jpeg"));
+        assert_eq!(mime_type_for_extension("jpeg"), Some("image/jpeg"));
+        assert_eq!(
+            mime_type_for_extension("ppm"),
+            Some("image/x-portable-pixmap")
+        );
+        assert_eq!(mime_type_for_extension("webp"), None);
+        assert_eq!(mime_type_for_extension("PNG"), None);
+    }
+
+    #[test]
+    fn image_mime_type_ignores_extension_case() {
+        assert_eq!(
+            image_mime_type(Path::new("/nonexistent/SHOT.PNG")).unwrap(),
+            "image/png"
+        );
+        assert_eq!(
+            image_mime_type(Path::new("/nonexistent/shot.JpEg")).unwrap(),
+            "image/jpeg"
+        );
+    }
+
+    #[test]
+    fn image_mime_type_sniffs_unknown_extensions() {
+        let png = temp_file("shot.img", b"\x89PNG\r\n\x1a\n");
+        let jpeg = temp_file("shot", b"\xff\xd8\xff\xe0");
+        let ppm = temp_file("shot.bin", b"P6\n1 1\n255\n");
+
+        assert_eq!(image_mime_type(&png).unwrap(), "image/png");
+        assert_eq!(image_mime_type(&jpeg).unwrap(), "image/jpeg");
+        assert_eq!(image_mime_type(&ppm).unwrap(), "image/x-portable-pixmap");
+    }
+
+    #[test]
+    fn image_mime_type_rejects_unrecognized_files() {
+        let text = temp_file("notes.txt", b"hello world");
+
+        assert!(image_mime_type(&text).is_err());
+        assert!(image_mime_type(Path::new("/nonexistent/shot.webp")).is_err());
+    }
+
+    #[test]
+    fn copy_text_to_clipboard_pipes_text_to_wl_copy() {
+        let runner = Rc::new(MockCommandRunner::default());
+        runner.respond("wl-copy", 0, "", "");
+        with_command_runner(runner.clone(), || {
+            copy_text_to_clipboard("https://example.com/a.png")
+        })
+        .unwrap();
+
+        assert_eq!(runner.calls(), vec![vec!["wl-copy"]]);
+        assert_eq!(runner.inputs(), vec![b"https://example.com/a.png".to_vec()]);
+    }
+
+    #[test]
+    fn copy_text_to_clipboard_reports_wl_copy_failures() {
+        let runner = Rc::new(MockCommandRunner::default());
+        runner.respond("wl-copy", 1, "", "");
+        let err = with_command_runner(runner, || copy_text_to_clipboard("text")).unwrap_err();
+        assert!(err.to_string().contains("复制文本到剪贴板失败"));
+
+        let err = with_command_runner(Rc::new(MockCommandRunner::default()), || {
+            copy_text_to_clipboard("text")
+        })
+        .unwrap_err();
+        assert!(matches!(err, CaptureError::ToolNotFound(_)));
+    }
+
+    #[test]
+    fn transient_capture_errors_match_known_grim_failures() {
+        for message in [
+            "截图失败: failed to copy output HDMI-A-1",
+            "截图失败: Failed to create buffer",
+            "截图失败: Error 71 (Protocol error) dispatching to Wayland display.",
+        ] {
+            assert!(
+                is_transient_capture_error(&CaptureError::Failed(message.to_string())),
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn transient_capture_errors_ignore_unrelated_failures() {
+        for message in [
+            "截图失败: invalid geometry for surface",
+            "截图失败: cannot write buffer to file: No space left on device",
+            "截图失败: screencopy output not found",
+        ] {
+            assert!(
+                !is_transient_capture_error(&CaptureError::Failed(message.to_string())),
+                "{message}"
+            );
+        }
+        assert!(!is_transient_capture_error(&CaptureError::CompositorError(
+            "failed to copy output".to_string()
+        )));
+    }
+
+    #[test]
+    fn parse_geometry_normalizes_whitespace() {
+        assert_eq!(
+            parse_geometry("  100,200 \t 640x480\n").unwrap(),
+            "100,200 640x480"
+        );
+    }
+
+    #[test]
+    fn parse_geometry_accepts_negative_origins() {
+        assert_eq!(
+            parse_geometry("-1920,-200 800x600").unwrap(),
+            "-1920,-200 800x600"
+        );
+    }
+
+    #[test]
+    fn parse_geometry_rejects_malformed_input() {
+        for input in [
+            "",
+            "100,100",
+            "100 640x480",
+            "100,100 640",
+            "100,100 640x480 extra",
+            "a,b 640x480",
+            "100,100 -640x480",
+        ] {
+            assert!(parse_geometry(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn parse_geometry_rejects_empty_size() {
+        assert!(parse_geometry("0,0 0x480").is_err());
+        assert!(parse_geometry("0,0 640x0").is_err());
+    }
+
+    #[test]
+    fn geometry_rect_parses_into_output_geometry() {
+        assert_eq!(
+            geometry_rect("-10,20 30x40").unwrap(),
+            OutputGeometry {
+                x: -10,
+                y: 20,
+                width: 30,
+                height: 40
+            }
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::deps::missing_tool_message;
+
+pub type Result<T, E = CaptureError> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum CaptureError {
+    ToolNotFound(String),
+    Cancelled,
+    ProtocolUnsupported(String),
+    CompositorError(String),
+    Io { context: String, source: io::Error },
+    Failed(String),
+}
+
+impl CaptureError {
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, CaptureError::Cancelled)
+    }
+
+    fn with_prefix(self, context: impl fmt::Display) -> Self {
+        match self {
+            CaptureError::Failed(message) => CaptureError::Failed(format!("{context}: {message}")),
+            CaptureError::CompositorError(message) => {
+                CaptureError::CompositorError(format!("{context}: {message}"))
+            }
+            other => other,
+        }
+    }
+
+    fn wrap(source: Box<dyn Error + Send + Sync>, context: String) -> Self {
+        let source = match source.downcast::<CaptureError>() {
+            Ok(error) => return error.with_prefix(context),
+            Err(source) => source,
+        };
+        match source.downcast::<io::Error>() {
+            Ok(source) => CaptureError::Io {
+                context,
+                source: *source,
+            },
+            Err(source) => CaptureError::Failed(format!("{context}: {source}")),
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::ToolNotFound(tool) => write!(f, "{}", missing_tool_message(tool)),
+            CaptureError::Cancelled => write!(f, "选择已取消"),
+            CaptureError::ProtocolUnsupported(message)
+            | CaptureError::CompositorError(message)
+            | CaptureError::Failed(message) => write!(f, "{message}"),
+            CaptureError::Io { context, source } => write!(f, "{context}: {source}"),
+        }
+    }
+}
+
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CaptureError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CaptureError {
+    fn from(source: io::Error) -> Self {
+        CaptureError::Io {
+            context: "I/O 错误".to_string(),
+            source,
+        }
+    }
+}
+
+pub(crate) trait Context<T> {
+    fn context(self, context: impl fmt::Display) -> Result<T>;
+
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T, E> Context<T> for std::result::Result<T, E>
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn context(self, context: impl fmt::Display) -> Result<T> {
+        self.map_err(|err| CaptureError::wrap(Box::new(err), context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|err| CaptureError::wrap(Box::new(err), context().to_string()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: impl fmt::Display) -> Result<T> {
+        self.ok_or_else(|| CaptureError::Failed(context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display>(self, context: impl FnOnce() -> C) -> Result<T> {
+        self.ok_or_else(|| CaptureError::Failed(context().to_string()))
+    }
+}
+
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err($crate::capture::CaptureError::Failed(format!($($arg)*)))
+    };
+}
+
+pub(crate) use bail;
+
+pub fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<CaptureError>()
+        .is_some_and(CaptureError::is_cancelled)
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::capture::command_utils::{run_command, run_command_output};
+use crate::capture::error::{Context, Result, bail};
+
+const BYTES_PER_KIB: f64 = 1024.0;
+const LAST_FRAME_SEEK: &str = "-1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaInfo {
+    pub duration: Option<Duration>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub fps: Option<f64>,
+    pub size: u64,
+}
+
+impl fmt::Display for MediaInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(duration) = self.duration {
+            let seconds = duration.as_secs();
+            parts.push(format!(
+                "{:02}:{:02}:{:02}",
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60
+            ));
+        }
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("{width}x{height}"));
+        }
+        if let Some(fps) = self.fps {
+            parts.push(format!("{fps:.2} fps"));
+        }
+        parts.push(format_size(self.size));
+        write!(f, "{}", parts.join(" · "))
+    }
+}
+
+pub fn probe_media(path: &Path) -> Result<MediaInfo> {
+    let mut command = Command::new("ffprobe");
+    command
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args([
+            "-show_entries",
+            "stream=width,height,avg_frame_rate:format=duration,size",
+        ])
+        .args(["-of", "json"])
+        .arg(path);
+    let stdout = run_command_output(command, "读取录屏信息失败")?;
+    let probe: Value = serde_json::from_slice(&stdout).context("ffprobe 输出不是有效 JSON")?;
+
+    let stream = probe.pointer("/streams/0");
+    let format = probe.get("format");
+    let number = |value: Option<&Value>| value.and_then(Value::as_str)?.parse::<f64>().ok();
+
+    let size = match number(format.and_then(|format| format.get("size"))) {
+        Some(size) => size as u64,
+        None => fs::metadata(path)
+            .with_context(|| format!("无法读取录屏文件: {}", path.display()))?
+            .len(),
+    };
+
+    Ok(MediaInfo {
+        duration: number(format.and_then(|format| format.get("duration")))
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .map(Duration::from_secs_f64),
+        width: stream.and_then(|stream| stream.get("width")?.as_u64()),
+        height: stream.and_then(|stream| stream.get("height")?.as_u64()),
+        fps: stream
+            .and_then(|stream| stream.get("avg_frame_rate")?.as_str())
+            .and_then(parse_frame_rate),
+        size,
+    })
+}
+
+pub fn extract_frame(video_path: &Path, timestamp: Option<Duration>) -> Result<PathBuf> {
+    let stem = video_path.file_stem().unwrap_or_default().to_string_lossy();
+    let frame_path = video_path.with_file_name(format!("{stem}-thumbnail.png"));
+
+    let _ = fs::remove_file(&frame_path);
+
+    let mut command = Command::new("ffmpeg");
+    command.args(["-hide_banner", "-loglevel", "error", "-y"]);
+    match timestamp {
+        Some(timestamp) => command.args(["-ss", &format!("{:.3}", timestamp.as_secs_f64())]),
+        None => command.args(["-sseof", LAST_FRAME_SEEK]),
+    };
+    command.arg("-i").arg(video_path);
+    match timestamp {
+        Some(_) => command.args(["-frames:v", "1"]),
+        None => command.args(["-update", "1"]),
+    };
+    command.arg(&frame_path);
+    run_command(command, "提取视频帧失败")?;
+
+    if !frame_path.is_file() {
+        bail!("提取视频帧失败: 指定时间超出视频时长");
+    }
+    Ok(frame_path)
+}
+
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (numerator, denominator) = rate.split_once('/')?;
+    let numerator: f64 = numerator.parse().ok()?;
+    let denominator: f64 = denominator.parse().ok()?;
+    (denominator > 0.0 && numerator > 0.0).then(|| numerator / denominator)
+}
+
+fn format_size(bytes: u64) -> String {
+    if (bytes as f64) < BYTES_PER_KIB {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / BYTES_PER_KIB;
+    for unit in ["KiB", "MiB"] {
+        if size < BYTES_PER_KIB {
+            return format!("{size:.1} {unit}");
+        }
+        size /= BYTES_PER_KIB;
+    }
+    format!("{size:.1} GiB")
+}
@@ -1,35 +1,162 @@
 mod command_utils;
+mod error;
+mod media;
 mod output;
 mod recording;
+mod runner;
 mod screenshot;
 mod state;
+#[cfg(feature = "webcam")]
+mod webcam;
 mod windows;
 
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::process::Child;
+use std::time::{Duration, SystemTime};
 
+pub use command_utils::{
+    HOLD_CLIPBOARD_FLAG, copy_text_to_clipboard, default_system_mix_audio_device,
+    last_region_geometry, parse_geometry, pick_point, set_clipboard_image, set_quiet, set_verbose,
+};
+pub(crate) use command_utils::{copy_image_to_clipboard, image_mime_type, pick_region_geometry};
+use error::bail;
+pub use error::{CaptureError, is_cancelled};
+pub use media::{MediaInfo, extract_frame, probe_media};
+pub(crate) use output::build_output_path;
+pub use output::{prune_captures, recent_captures, with_temporary_output};
 pub use recording::{
-    current_cli_recording_state, start_recording, start_recording_detached, stop_recording,
-    stop_recording_detached, toggle_recording_pause,
+    current_cli_recording_state, pause_cli_recording, process_is_running, recover_cli_recording,
+    resume_cli_recording, start_recording, start_recording_detached, start_recording_with_webcam,
+    stop_recording, stop_recording_detached, toggle_recording_pause,
 };
+pub use runner::{CommandRunner, SystemCommandRunner};
 pub use screenshot::{
-    is_window_protocol_unsupported_error, take_screenshot, take_window_screenshot,
+    screenshot_output_path, take_frozen_frame, take_geometry_screenshot_to_bytes,
+    take_geometry_screenshot_to_stdout, take_padded_window_screenshot, take_screenshot,
+    take_screenshot_in_format, take_screenshot_to_bytes, take_screenshot_to_stdout,
+    take_screenshot_with_clipboard, take_screenshot_with_geometry, take_window_screenshot,
     take_window_screenshot_via_niri,
 };
-pub use windows::{focused_output_name, list_windows};
+pub(crate) use state::cli_state_dir;
+pub use windows::{
+    Compositor, all_outputs_geometry, cursor_position, focused_output_name,
+    list_capturable_windows, list_outputs, list_windows, list_workspaces, window_geometry,
+};
+
+use crate::config::config;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CaptureTarget {
     Region,
     Fullscreen,
+    AllOutputs,
+    Output(String),
+    Workspace(u64),
 }
 
 impl CaptureTarget {
-    pub(crate) fn slug(self) -> &'static str {
+    pub(crate) fn slug(&self) -> &'static str {
         match self {
             CaptureTarget::Region => "region",
             CaptureTarget::Fullscreen => "fullscreen",
+            CaptureTarget::AllOutputs => "all-outputs",
+            CaptureTarget::Output(_) => "output",
+            CaptureTarget::Workspace(_) => "workspace",
+        }
+    }
+}
+
+const WF_RECORDER_DEFAULT_CODEC: &str = "libx264";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Container {
+    #[default]
+    Mkv,
+    Mp4,
+    Webm,
+}
+
+impl Container {
+    pub const ALL: [Container; 3] = [Container::Mkv, Container::Mp4, Container::Webm];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mkv" => Some(Container::Mkv),
+            "mp4" => Some(Container::Mp4),
+            "webm" => Some(Container::Webm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Container::Mkv => "mkv",
+            Container::Mp4 => "mp4",
+            Container::Webm => "webm",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Container::Mkv => "MKV",
+            Container::Mp4 => "MP4",
+            Container::Webm => "WebM",
+        }
+    }
+
+    pub(crate) fn default_video_codec(self) -> Option<&'static str> {
+        match self {
+            Container::Webm => Some("libvpx-vp9"),
+            Container::Mkv | Container::Mp4 => None,
+        }
+    }
+
+    pub(crate) fn default_audio_codec(self) -> Option<&'static str> {
+        match self {
+            Container::Webm => Some("libopus"),
+            Container::Mkv | Container::Mp4 => None,
+        }
+    }
+
+    pub fn check_video_codec(self, codec: &str) -> Result<(), CaptureError> {
+        let codec = codec.to_ascii_lowercase();
+        let is_vpx = codec.contains("vp8") || codec.contains("vp9") || codec.contains("vpx");
+        let is_av1 = codec.contains("av1");
+        let compatible = match self {
+            Container::Mkv => true,
+            Container::Mp4 => !is_vpx,
+            Container::Webm => is_vpx || is_av1,
+        };
+
+        if !compatible {
+            bail!("编码器 {codec} 无法封装进 {} 容器", self.label());
         }
+        Ok(())
     }
 }
 
@@ -42,14 +169,349 @@ pub struct WindowInfo {
     pub is_focused: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct WorkspaceInfo {
+    pub id: u64,
+    pub idx: u64,
+    pub name: Option<String>,
+    pub output: Option<String>,
+    pub is_active: bool,
+    pub is_focused: bool,
+}
+
+impl WorkspaceInfo {
+    pub fn label(&self) -> String {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => format!("Workspace {}", self.idx),
+        };
+        match &self.output {
+            Some(output) => format!("{name} ({output})"),
+            None => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    pub geometry: OutputGeometry,
+    pub scale: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputGeometry {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+impl OutputGeometry {
+    pub fn padded(self, padding: u32) -> Self {
+        let padding = i64::from(padding);
+        Self {
+            x: self.x - padding,
+            y: self.y - padding,
+            width: self.width + padding * 2,
+            height: self.height + padding * 2,
+        }
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
+    pub fn relative_to(self, origin: Self) -> Self {
+        Self {
+            x: self.x - origin.x,
+            y: self.y - origin.y,
+            ..self
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
+
+impl fmt::Display for OutputGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{} {}x{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 3] = [
+        QualityPreset::Low,
+        QualityPreset::Medium,
+        QualityPreset::High,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "low" => Some(QualityPreset::Low),
+            "medium" => Some(QualityPreset::Medium),
+            "high" => Some(QualityPreset::High),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            QualityPreset::Low => "low",
+            QualityPreset::Medium => "medium",
+            QualityPreset::High => "high",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QualityPreset::Low => "Low",
+            QualityPreset::Medium => "Medium",
+            QualityPreset::High => "High",
+        }
+    }
+
+    pub fn quality_params(self, codec: &str) -> Result<Vec<String>, CaptureError> {
+        let codec = codec.to_ascii_lowercase();
+        let pick = |low: u32, medium: u32, high: u32| match self {
+            QualityPreset::Low => low,
+            QualityPreset::Medium => medium,
+            QualityPreset::High => high,
+        };
+
+        let params = if codec.contains("nvenc") {
+            vec![format!("cq={}", pick(32, 26, 20))]
+        } else if codec.contains("vaapi") {
+            vec![format!("qp={}", pick(30, 24, 18))]
+        } else if codec.contains("vp8") || codec.contains("vp9") || codec.contains("vpx") {
+            vec![format!("crf={}", pick(40, 33, 24)), "b=0".to_string()]
+        } else if codec.contains("av1") {
+            vec![format!("crf={}", pick(45, 35, 25)), "b=0".to_string()]
+        } else if codec.contains("264") || codec.contains("265") || codec.contains("hevc") {
+            vec![format!("crf={}", pick(30, 23, 18))]
+        } else {
+            bail!("编码器 {codec} 不支持质量预设，请改用编码器默认质量");
+        };
+
+        Ok(params
+            .into_iter()
+            .flat_map(|param| ["-p".to_string(), param])
+            .collect())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordingOptions {
+    pub audio: bool,
+    pub show_cursor: bool,
+    pub max_duration: Option<Duration>,
+    pub container: Container,
+    pub video_codec: Option<String>,
+    pub quality: Option<QualityPreset>,
+    pub region_geometry: Option<String>,
+    pub normalize_audio: bool,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            audio: false,
+            show_cursor: true,
+            max_duration: None,
+            container: Container::default(),
+            video_codec: None,
+            quality: None,
+            region_geometry: None,
+            normalize_audio: config().normalize_audio,
+        }
+    }
+}
+
+impl RecordingOptions {
+    pub(crate) fn effective_video_codec(&self) -> &str {
+        self.video_codec
+            .as_deref()
+            .or(self.container.default_video_codec())
+            .unwrap_or(WF_RECORDER_DEFAULT_CODEC)
+    }
+
+    pub fn quality_params(&self) -> Result<Vec<String>, CaptureError> {
+        match self.quality {
+            Some(quality) => quality.quality_params(self.effective_video_codec()),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WebcamPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl WebcamPosition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left" => Some(WebcamPosition::TopLeft),
+            "top-right" => Some(WebcamPosition::TopRight),
+            "bottom-left" => Some(WebcamPosition::BottomLeft),
+            "bottom-right" => Some(WebcamPosition::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebcamConfig {
+    pub device: PathBuf,
+    pub position: WebcamPosition,
+    pub width: u32,
+}
+
 pub struct RecordingSession {
-    pub(crate) child: Child,
+    pub(crate) child: Option<Child>,
     pub(crate) output_path: PathBuf,
+    pub(crate) audio_device: Option<String>,
     pub(crate) paused: bool,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) recorder_args: Vec<String>,
+    pub(crate) segments: Vec<PathBuf>,
+    pub(crate) normalize_audio: bool,
+    #[cfg(feature = "webcam")]
+    pub(crate) webcam: Option<webcam::WebcamRecording>,
+}
+
+impl RecordingSession {
+    pub fn output_path(&self) -> &Path {
+        &self.output_path
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn segments(&self) -> &[PathBuf] {
+        &self.segments
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecentCapture {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+impl RecentCapture {
+    pub fn is_recording(&self) -> bool {
+        self.path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|name| name == "recordings")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum RecoveryOutcome {
+    NothingToRecover,
+    Recovered(PathBuf),
+    Unrecoverable(PathBuf),
 }
 
 #[derive(Clone, Debug)]
 pub struct CliRecordingState {
     pub pid: u32,
     pub output_path: PathBuf,
+    pub target: Option<String>,
+    pub audio_device: Option<String>,
+    pub max_duration: Option<Duration>,
+    pub started_at: SystemTime,
+    pub normalize_audio: bool,
+    pub paused: bool,
+    pub paused_at: Option<SystemTime>,
+    pub paused_duration: Duration,
+    pub segment_pause: bool,
+    pub recorder_args: Vec<String>,
+    pub segments: Vec<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i64, y: i64, width: i64, height: i64) -> OutputGeometry {
+        OutputGeometry {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn contains_accepts_regions_touching_edges() {
+        let output = rect(0, 0, 1920, 1080);
+
+        assert!(output.contains(output));
+        assert!(output.contains(rect(0, 0, 1, 1)));
+        assert!(output.contains(rect(1919, 1079, 1, 1)));
+    }
+
+    #[test]
+    fn contains_rejects_regions_crossing_edges() {
+        let output = rect(0, 0, 1920, 1080);
+
+        assert!(!output.contains(rect(-1, 0, 10, 10)));
+        assert!(!output.contains(rect(0, -1, 10, 10)));
+        assert!(!output.contains(rect(1911, 0, 10, 10)));
+        assert!(!output.contains(rect(0, 1071, 10, 10)));
+    }
+
+    #[test]
+    fn contains_handles_negative_origins() {
+        let output = rect(-2560, -400, 2560, 1440);
+
+        assert!(output.contains(rect(-2560, -400, 2560, 1440)));
+        assert!(output.contains(rect(-100, -100, 100, 100)));
+        assert!(!output.contains(rect(-100, -100, 101, 100)));
+    }
+
+    #[test]
+    fn relative_to_translates_into_output_space() {
+        let output = rect(-2560, -400, 2560, 1440);
+
+        assert_eq!(
+            rect(-2500, -300, 200, 100).relative_to(output),
+            rect(60, 100, 200, 100)
+        );
+        assert_eq!(
+            rect(1920, 0, 300, 200).relative_to(rect(1920, 0, 1280, 1024)),
+            rect(0, 0, 300, 200)
+        );
+    }
 }
@@ -1,19 +1,201 @@
+use std::cell::Cell;
+use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime};
 
-use anyhow::{Context, Result, bail};
 use chrono::Local;
 
+use crate::capture::RecentCapture;
+use crate::capture::error::{Context, Result, bail};
+use crate::capture::state::read_cli_recording_state;
+use crate::config::config;
+
+const CAPTURE_KIND_DIRS: &[&str] = &["screenshots", "recordings"];
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+struct CaptureFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+thread_local! {
+    static TEMPORARY_OUTPUT: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn with_temporary_output<T>(capture: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TEMPORARY_OUTPUT.with(|temporary| temporary.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TEMPORARY_OUTPUT.with(|temporary| temporary.replace(true)));
+    capture()
+}
+
 pub(crate) fn build_output_path(kind_dir: &str, prefix: &str, extension: &str) -> Result<PathBuf> {
-    let base_dir = base_output_dir()?;
-    let output_dir = base_dir.join(kind_dir);
-    fs::create_dir_all(&output_dir)
-        .with_context(|| format!("无法创建输出目录: {}", output_dir.display()))?;
+    if TEMPORARY_OUTPUT.with(Cell::get) {
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+        let temp_dir = dirs::runtime_dir().unwrap_or_else(env::temp_dir);
+        return Ok(temp_dir.join(format!(
+            "ncaptura-{}-{prefix}-{timestamp}.{extension}",
+            process::id()
+        )));
+    }
+
+    let base_dir = ensure_dir(&base_output_dir()?)?;
+    let output_dir = ensure_dir(&base_dir.join(kind_dir))?;
 
     let timestamp = Local::now().format("%Y%m%d-%H%M%S");
     Ok(output_dir.join(format!("{prefix}-{timestamp}.{extension}")))
 }
 
+pub fn recent_captures(limit: usize) -> Result<Vec<RecentCapture>> {
+    let mut captures: Vec<RecentCapture> = capture_files()?
+        .into_iter()
+        .filter(|file| file.size > 0)
+        .map(|file| RecentCapture {
+            path: file.path,
+            modified: file.modified,
+        })
+        .collect();
+
+    captures.sort_by_key(|capture| std::cmp::Reverse(capture.modified));
+    captures.truncate(limit);
+    Ok(captures)
+}
+
+pub fn prune_captures(keep: Option<&Path>, dry_run: bool) -> Result<Vec<PathBuf>> {
+    let config = config();
+    if config.max_age_days.is_none() && config.max_total_size_mb.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut protected_paths: Vec<PathBuf> = keep.map(Path::to_path_buf).into_iter().collect();
+    if let Ok(state) = read_cli_recording_state() {
+        protected_paths.push(state.output_path);
+        protected_paths.extend(state.segments);
+    }
+    let protected_paths: Vec<PathBuf> = protected_paths
+        .iter()
+        .map(|path| canonical_path(path))
+        .collect();
+    let protected = |path: &Path| protected_paths.iter().any(|protected| protected == path);
+
+    let mut files = capture_files()?;
+    files.sort_by_key(|file| file.modified);
+    let mut total_size: u64 = files.iter().map(|file| file.size).sum();
+
+    let age_cutoff = config.max_age_days.and_then(|days| {
+        SystemTime::now().checked_sub(Duration::from_secs(days * SECONDS_PER_DAY))
+    });
+    let size_limit = config.max_total_size_mb.map(|mb| mb * BYTES_PER_MB);
+
+    let mut removed = Vec::new();
+    for file in files {
+        if protected(&file.path) {
+            continue;
+        }
+        let too_old = age_cutoff.is_some_and(|cutoff| file.modified < cutoff);
+        let over_size = size_limit.is_some_and(|limit| total_size > limit);
+        if !too_old && !over_size {
+            continue;
+        }
+
+        if !dry_run {
+            fs::remove_file(&file.path)
+                .with_context(|| format!("无法删除旧文件: {}", file.path.display()))?;
+        }
+        total_size -= file.size;
+        removed.push(file.path);
+    }
+
+    Ok(removed)
+}
+
+fn capture_files() -> Result<Vec<CaptureFile>> {
+    let base_dir = base_output_dir()?;
+    let mut files = Vec::new();
+    for kind_dir in CAPTURE_KIND_DIRS {
+        let Ok(dir) = base_dir.join(kind_dir).canonicalize() else {
+            continue;
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            files.push(CaptureFile {
+                path: dir.join(entry.file_name()),
+                modified,
+                size: metadata.len(),
+            });
+        }
+    }
+    Ok(files)
+}
+
+fn ensure_dir(dir: &Path) -> Result<PathBuf> {
+    let is_symlink = fs::symlink_metadata(dir)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if is_symlink {
+        let link_target =
+            fs::read_link(dir).with_context(|| format!("无法读取符号链接: {}", dir.display()))?;
+        let link_target = match dir.parent() {
+            Some(parent) if link_target.is_relative() => parent.join(link_target),
+            _ => link_target,
+        };
+        fs::create_dir_all(&link_target).with_context(|| {
+            format!(
+                "无法创建符号链接指向的输出目录: {} -> {}",
+                dir.display(),
+                link_target.display()
+            )
+        })?;
+    } else {
+        fs::create_dir_all(dir).with_context(|| format!("无法创建输出目录: {}", dir.display()))?;
+    }
+
+    let resolved = dir
+        .canonicalize()
+        .with_context(|| format!("无法解析输出目录: {}", dir.display()))?;
+    if !resolved.is_dir() {
+        bail!("输出路径不是目录: {}", resolved.display());
+    }
+
+    Ok(resolved)
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
 fn base_output_dir() -> Result<PathBuf> {
     if let Some(pictures_dir) = dirs::picture_dir() {
         return Ok(pictures_dir.join("NCaptura"));
@@ -25,3 +207,81 @@ fn base_output_dir() -> Result<PathBuf> {
 
     bail!("无法定位用户目录")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::os::unix::fs::symlink;
+    use std::process;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ncaptura-output-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn temporary_output_stays_out_of_the_capture_library() {
+        let path = with_temporary_output(|| {
+            build_output_path("screenshots", "screenshot-region", "png").unwrap()
+        });
+        let temp_dir = dirs::runtime_dir().unwrap_or_else(env::temp_dir);
+
+        assert_eq!(path.parent(), Some(temp_dir.as_path()));
+        assert!(!TEMPORARY_OUTPUT.with(Cell::get));
+    }
+
+    #[test]
+    fn ensure_dir_creates_missing_directories() {
+        let root = temp_dir("plain");
+        let dir = root.join("a").join("b");
+
+        assert_eq!(ensure_dir(&dir).unwrap(), dir);
+        assert!(dir.is_dir());
+    }
+
+    #[test]
+    fn ensure_dir_resolves_symlinked_directory() {
+        let root = temp_dir("symlink");
+        let target = root.join("target");
+        fs::create_dir(&target).unwrap();
+        let link = root.join("link");
+        symlink(&target, &link).unwrap();
+
+        assert_eq!(ensure_dir(&link).unwrap(), target);
+    }
+
+    #[test]
+    fn ensure_dir_creates_target_of_dangling_symlink() {
+        let root = temp_dir("dangling");
+        let target = root.join("missing").join("captures");
+        let link = root.join("link");
+        symlink(&target, &link).unwrap();
+
+        assert_eq!(ensure_dir(&link).unwrap(), target);
+        assert!(target.is_dir());
+    }
+
+    #[test]
+    fn ensure_dir_resolves_relative_dangling_symlink() {
+        let root = temp_dir("relative");
+        let link = root.join("link");
+        symlink("relative-target", &link).unwrap();
+
+        assert_eq!(ensure_dir(&link).unwrap(), root.join("relative-target"));
+    }
+
+    #[test]
+    fn ensure_dir_rejects_symlink_to_file() {
+        let root = temp_dir("file");
+        let file = root.join("file");
+        fs::write(&file, b"").unwrap();
+        let link = root.join("link");
+        symlink(&file, &link).unwrap();
+
+        assert!(ensure_dir(&link).is_err());
+    }
+}
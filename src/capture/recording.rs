@@ -1,74 +1,284 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::{Context, Result, bail};
 use nix::errno::Errno;
 use nix::sys::signal::{Signal, kill};
 use nix::unistd::Pid;
 
-use crate::capture::command_utils::{default_system_mix_audio_device, pick_region_geometry};
+use crate::capture::command_utils::{
+    default_system_mix_audio_device, geometry_rect, pick_region_geometry, run_command,
+    tool_spawn_error,
+};
+use crate::capture::error::{Context, Result, bail};
 use crate::capture::output::build_output_path;
+use crate::capture::runner::command_output;
 use crate::capture::state::{
     clear_cli_recording_state, read_cli_recording_state, write_cli_recording_state,
 };
-use crate::capture::{CaptureTarget, CliRecordingState, RecordingSession, focused_output_name};
+#[cfg(feature = "webcam")]
+use crate::capture::webcam::start_webcam;
+use crate::capture::windows::output_containing;
+use crate::capture::{
+    CaptureTarget, CliRecordingState, Container, RecordingOptions, RecordingSession,
+    RecoveryOutcome, WebcamConfig, focused_output_name, list_outputs,
+};
+use crate::config::config;
+use crate::deps::tool_version;
+
+const RECORDER_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+const RECORDER_EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const WF_RECORDER_GLOBAL_GEOMETRY_VERSION: (u32, u32) = (0, 4);
+
+pub fn start_recording(
+    target: CaptureTarget,
+    options: &RecordingOptions,
+) -> Result<RecordingSession> {
+    start_recording_with_webcam(target, options, None)
+}
 
-pub fn start_recording(target: CaptureTarget, with_audio: bool) -> Result<RecordingSession> {
-    let output_path =
-        build_output_path("recordings", &format!("recording-{}", target.slug()), "mkv")?;
+pub fn start_recording_with_webcam(
+    target: CaptureTarget,
+    options: &RecordingOptions,
+    webcam: Option<WebcamConfig>,
+) -> Result<RecordingSession> {
+    #[cfg(not(feature = "webcam"))]
+    if webcam.is_some() {
+        bail!("当前构建未启用 webcam 特性，无法叠加摄像头画面");
+    }
+
+    let output_path = build_output_path(
+        "recordings",
+        &format!("recording-{}", target.slug()),
+        options.container.extension(),
+    )?;
+    let (recorder_args, audio_device) = wf_recorder_args(target, options)?;
+
+    #[cfg(feature = "webcam")]
+    let webcam = webcam
+        .map(|config| start_webcam(config, &output_path))
+        .transpose()?;
+
+    let first_segment = segment_path(&output_path, 1);
+    let child = match spawn_segment(&recorder_args, &first_segment) {
+        Ok(child) => child,
+        Err(err) => {
+            #[cfg(feature = "webcam")]
+            if let Some(webcam) = webcam {
+                webcam.discard();
+            }
+            return Err(err);
+        }
+    };
 
-    let mut command = Command::new("wf-recorder");
+    Ok(RecordingSession {
+        child: Some(child),
+        output_path,
+        audio_device,
+        paused: false,
+        max_duration: options.max_duration,
+        recorder_args,
+        segments: vec![first_segment],
+        normalize_audio: options.audio && options.normalize_audio,
+        #[cfg(feature = "webcam")]
+        webcam,
+    })
+}
 
+fn wf_recorder_args(
+    target: CaptureTarget,
+    options: &RecordingOptions,
+) -> Result<(Vec<String>, Option<String>)> {
+    let mut args = Vec::new();
     match target {
         CaptureTarget::Region => {
-            let geometry = pick_region_geometry()?;
-            command.args(["-g", &geometry]);
+            let geometry = match &options.region_geometry {
+                Some(geometry) => geometry.clone(),
+                None => pick_region_geometry()?,
+            };
+            args.extend(region_recorder_args(&geometry));
         }
         CaptureTarget::Fullscreen => {
             if let Ok(output_name) = focused_output_name() {
-                command.args(["-o", &output_name]);
+                args.extend(["-o".to_string(), output_name]);
+            }
+        }
+        CaptureTarget::Output(output_name) => {
+            args.extend(["-o".to_string(), output_name]);
+        }
+        CaptureTarget::AllOutputs => {
+            let outputs = list_outputs()?;
+            match outputs.as_slice() {
+                [output] => args.extend(["-o".to_string(), output.name.clone()]),
+                [] => bail!("未检测到可录制的输出"),
+                _ => bail!(
+                    "wf-recorder 只能录制单个输出，无法同时录制全部 {} 个输出，请选择单个输出或区域",
+                    outputs.len()
+                ),
             }
         }
+        CaptureTarget::Workspace(_) => bail!("录屏暂不支持工作区目标"),
     }
 
-    if with_audio {
-        if let Some(audio_device) = default_system_mix_audio_device() {
-            command.arg(format!("--audio={audio_device}"));
-        } else {
-            command.arg("--audio");
+    let audio_device = if options.audio {
+        default_system_mix_audio_device()
+    } else {
+        None
+    };
+    if options.audio {
+        match &audio_device {
+            Some(audio_device) => args.push(format!("--audio={audio_device}")),
+            None => args.push("--audio".to_string()),
+        }
+        if let Some(codec) = options.container.default_audio_codec() {
+            args.extend(["-C".to_string(), codec.to_string()]);
         }
     }
 
-    command.arg("-f").arg(&output_path);
+    if !options.show_cursor {
+        match hide_cursor_flag() {
+            Some(flag) => args.push(flag.to_string()),
+            None => eprintln!("当前 wf-recorder 不支持隐藏鼠标指针，录屏中仍会包含指针"),
+        }
+    }
 
-    let child = command
-        .spawn()
-        .context("无法启动 wf-recorder，请确认已安装并在 PATH 中")?;
+    let video_codec = options
+        .video_codec
+        .as_deref()
+        .or(options.container.default_video_codec());
+    if let Some(codec) = video_codec {
+        options.container.check_video_codec(codec)?;
+        args.extend(["-c".to_string(), codec.to_string()]);
+    }
+    args.extend(options.quality_params()?);
 
-    Ok(RecordingSession {
-        child,
-        output_path,
-        paused: false,
+    Ok((args, audio_device))
+}
+
+fn region_recorder_args(geometry: &str) -> Vec<String> {
+    let global = vec!["-g".to_string(), geometry.to_string()];
+    if !recorder_needs_output_relative_geometry() {
+        return global;
+    }
+
+    let Ok(region) = geometry_rect(geometry) else {
+        return global;
+    };
+    let outputs = match list_outputs() {
+        Ok(outputs) => outputs,
+        Err(err) => {
+            eprintln!("无法读取输出列表，按全局坐标传递录屏区域: {err}");
+            return global;
+        }
+    };
+    match output_containing(&outputs, region) {
+        Some(output) => vec![
+            "-o".to_string(),
+            output.name.clone(),
+            "-g".to_string(),
+            region.relative_to(output.geometry).to_string(),
+        ],
+        None => {
+            eprintln!("录屏区域跨越多个输出，wf-recorder 只能录制其中一个输出");
+            global
+        }
+    }
+}
+
+fn recorder_needs_output_relative_geometry() -> bool {
+    static RELATIVE: OnceLock<bool> = OnceLock::new();
+    *RELATIVE.get_or_init(|| {
+        let Some(version) = tool_version("wf-recorder") else {
+            return false;
+        };
+        let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+        match (parts.next().flatten(), parts.next().flatten()) {
+            (Some(major), Some(minor)) => (major, minor) < WF_RECORDER_GLOBAL_GEOMETRY_VERSION,
+            _ => false,
+        }
     })
 }
 
-pub fn toggle_recording_pause(session: &mut RecordingSession) -> Result<bool> {
-    let pid = Pid::from_raw(session.child.id() as i32);
+fn hide_cursor_flag() -> Option<&'static str> {
+    static FLAG: OnceLock<Option<String>> = OnceLock::new();
+    FLAG.get_or_init(|| {
+        let mut command = Command::new("wf-recorder");
+        command.arg("--help");
+        let output = command_output(&mut command).ok()?;
+        let help = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        help.split(|ch: char| ch.is_whitespace() || ch == ',' || ch == '=')
+            .find(|token| token.starts_with("--no-") && token.contains("cursor"))
+            .map(str::to_string)
+    })
+    .as_deref()
+}
 
-    if session.paused {
-        if let Err(err) = kill(pid, Signal::SIGCONT)
+fn spawn_segment(recorder_args: &[String], segment: &Path) -> Result<Child> {
+    Command::new("wf-recorder")
+        .args(recorder_args)
+        .arg("-f")
+        .arg(segment)
+        .spawn()
+        .map_err(|err| tool_spawn_error("wf-recorder", err))
+}
+
+pub(crate) fn segment_path(output_path: &Path, index: usize) -> PathBuf {
+    let stem = output_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let extension = output_path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    output_path.with_file_name(format!("{stem}-part{index}.{extension}"))
+}
+
+fn finish_segment(mut child: Child) -> Result<()> {
+    if child.try_wait().context("读取录屏进程状态失败")?.is_none() {
+        let pid = Pid::from_raw(child.id() as i32);
+        if let Err(err) = kill(pid, Signal::SIGINT)
             && err != Errno::ESRCH
         {
-            bail!("恢复录屏失败: {err}");
+            bail!("发送停止信号失败: {err}");
         }
+    }
+
+    let status = child.wait().context("等待录屏进程结束失败")?;
+    if !status.success() {
+        bail!("录屏进程异常退出: {status}");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_recording_pause(session: &mut RecordingSession) -> Result<bool> {
+    if session.paused {
+        let segment = segment_path(&session.output_path, session.segments.len() + 1);
+        let child = spawn_segment(&session.recorder_args, &segment).context("恢复录屏失败")?;
+        session.segments.push(segment);
+        session.child = Some(child);
         session.paused = false;
+        #[cfg(feature = "webcam")]
+        if let Some(webcam) = session.webcam.as_mut() {
+            webcam.resume().context("恢复摄像头录制失败")?;
+        }
         return Ok(false);
     }
 
-    if let Err(err) = kill(pid, Signal::SIGSTOP)
-        && err != Errno::ESRCH
-    {
-        bail!("暂停录屏失败: {err}");
+    if let Some(child) = session.child.take() {
+        finish_segment(child).context("暂停录屏失败")?;
+    }
+    #[cfg(feature = "webcam")]
+    if let Some(webcam) = session.webcam.as_mut() {
+        webcam.pause().context("暂停摄像头录制失败")?;
     }
 
     session.paused = true;
@@ -76,102 +286,435 @@ pub fn toggle_recording_pause(session: &mut RecordingSession) -> Result<bool> {
 }
 
 pub fn stop_recording(mut session: RecordingSession) -> Result<PathBuf> {
-    if session.paused {
-        let pid = Pid::from_raw(session.child.id() as i32);
-        if let Err(err) = kill(pid, Signal::SIGCONT)
-            && err != Errno::ESRCH
-        {
-            bail!("恢复录屏失败: {err}");
-        }
-        session.paused = false;
+    if let Some(child) = session.child.take() {
+        finish_segment(child)?;
     }
+    #[cfg(feature = "webcam")]
+    let webcam = session.webcam.take();
 
-    if session
-        .child
-        .try_wait()
-        .context("读取录屏进程状态失败")?
-        .is_none()
-    {
-        let pid = Pid::from_raw(session.child.id() as i32);
-        if let Err(err) = kill(pid, Signal::SIGINT)
-            && err != Errno::ESRCH
-        {
-            bail!("发送停止信号失败: {err}");
-        }
+    let segments: Vec<PathBuf> = session
+        .segments
+        .into_iter()
+        .filter(|segment| segment.is_file())
+        .collect();
+    match segments.as_slice() {
+        [] => bail!("录屏未生成任何文件"),
+        [segment] => fs::rename(segment, &session.output_path)
+            .with_context(|| format!("无法移动录屏文件: {}", session.output_path.display()))?,
+        _ => concat_segments(&segments, &session.output_path)?,
     }
 
-    let status = session.child.wait().context("等待录屏进程结束失败")?;
-    if !status.success() {
-        bail!("录屏进程异常退出: {status}");
+    #[cfg(feature = "webcam")]
+    if let Some(webcam) = webcam
+        && let Err(err) = webcam.overlay_onto(&session.output_path)
+    {
+        eprintln!("摄像头画面叠加失败，已保留原始录屏: {err}");
     }
 
+    if session.normalize_audio {
+        normalize_recording_audio(&session.output_path);
+    }
     Ok(session.output_path)
 }
 
+pub(crate) fn concat_segments(segments: &[PathBuf], output_path: &Path) -> Result<()> {
+    let list_path = output_path.with_extension("segments.txt");
+    let list: String = segments
+        .iter()
+        .map(|segment| {
+            let escaped = segment.to_string_lossy().replace('\'', "'\\''");
+            format!("file '{escaped}'\n")
+        })
+        .collect();
+    fs::write(&list_path, list)
+        .with_context(|| format!("无法写入分段列表: {}", list_path.display()))?;
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(["-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-c", "copy"])
+        .arg(output_path);
+    let result = run_command(command, "合并录屏分段失败");
+    let _ = fs::remove_file(&list_path);
+    result?;
+
+    for segment in segments {
+        let _ = fs::remove_file(segment);
+    }
+    Ok(())
+}
+
 pub fn start_recording_detached(
     target: CaptureTarget,
-    with_audio: bool,
+    options: &RecordingOptions,
 ) -> Result<CliRecordingState> {
-    if read_cli_recording_state().is_ok() {
-        bail!("已有通过 CLI 启动的录屏在进行中，请先停止");
-    }
-
-    let output_path =
-        build_output_path("recordings", &format!("recording-{}", target.slug()), "mkv")?;
-    let mut command = Command::new("wf-recorder");
-
-    match target {
-        CaptureTarget::Region => {
-            let geometry = pick_region_geometry()?;
-            command.args(["-g", &geometry]);
+    if let Ok(state) = read_cli_recording_state() {
+        if state.is_live() {
+            bail!("已有通过 CLI 启动的录屏在进行中，请先停止");
         }
-        CaptureTarget::Fullscreen => {
-            if let Ok(output_name) = focused_output_name() {
-                command.args(["-o", &output_name]);
+        match recover_state(state) {
+            RecoveryOutcome::Recovered(path) => {
+                eprintln!("上次录屏异常中断，已恢复录制内容: {}", path.display());
+            }
+            RecoveryOutcome::Unrecoverable(path) => {
+                eprintln!("上次录屏异常中断，无法恢复文件: {}", path.display());
             }
+            RecoveryOutcome::NothingToRecover => {}
         }
     }
 
-    if with_audio {
-        if let Some(audio_device) = default_system_mix_audio_device() {
-            command.arg(format!("--audio={audio_device}"));
-        } else {
-            command.arg("--audio");
+    let target_slug = target.slug();
+    let output_path = build_output_path(
+        "recordings",
+        &format!("recording-{target_slug}"),
+        options.container.extension(),
+    )?;
+    let (recorder_args, audio_device) = wf_recorder_args(target, options)?;
+    let child = spawn_segment(&recorder_args, &output_path)?;
+
+    let state = CliRecordingState {
+        pid: child.id(),
+        output_path,
+        target: Some(target_slug.to_string()),
+        audio_device,
+        max_duration: options.max_duration,
+        started_at: SystemTime::now(),
+        normalize_audio: options.audio && options.normalize_audio,
+        paused: false,
+        paused_at: None,
+        paused_duration: Duration::ZERO,
+        segment_pause: options.audio && config().segment_pause_with_audio,
+        recorder_args,
+        segments: Vec::new(),
+    };
+    write_cli_recording_state(&state)?;
+    Ok(state)
+}
+
+pub fn pause_cli_recording() -> Result<()> {
+    let mut state = read_cli_recording_state()?;
+    if state.paused {
+        return Ok(());
+    }
+
+    if state.segment_pause {
+        interrupt_recorder(state.pid)?;
+        if !wait_for_recorder_exit(state.pid) {
+            bail!("等待录屏进程结束超时");
+        }
+        if state.segments.is_empty() {
+            let first_segment = segment_path(&state.output_path, 1);
+            fs::rename(&state.output_path, &first_segment)
+                .with_context(|| format!("无法移动录屏分段: {}", first_segment.display()))?;
+            state.segments.push(first_segment);
         }
+    } else {
+        kill(Pid::from_raw(state.pid as i32), Signal::SIGSTOP).context("暂停录屏失败")?;
     }
 
-    command.arg("-f").arg(&output_path);
+    state.paused = true;
+    state.paused_at = Some(SystemTime::now());
+    write_cli_recording_state(&state)
+}
 
-    let child = command
-        .spawn()
-        .context("无法启动 wf-recorder，请确认已安装并在 PATH 中")?;
+pub fn resume_cli_recording() -> Result<()> {
+    let mut state = read_cli_recording_state()?;
+    if !state.paused {
+        return Ok(());
+    }
 
-    let pid = child.id();
-    write_cli_recording_state(pid, &output_path)?;
-    Ok(CliRecordingState { pid, output_path })
+    if state.segment_pause {
+        let segment = segment_path(&state.output_path, state.segments.len() + 1);
+        let mut child = spawn_segment(&state.recorder_args, &segment).context("恢复录屏失败")?;
+        state.pid = child.id();
+        state.segments.push(segment);
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+    } else {
+        kill(Pid::from_raw(state.pid as i32), Signal::SIGCONT).context("恢复录屏失败")?;
+    }
+
+    state.paused = false;
+    state.paused_duration += state.current_pause();
+    state.paused_at = None;
+    write_cli_recording_state(&state)
 }
 
 pub fn stop_recording_detached() -> Result<PathBuf> {
-    let (pid, output_path) = read_cli_recording_state()?;
-    let process_id = Pid::from_raw(pid as i32);
+    let state = read_cli_recording_state()?;
+    if !(state.paused && state.segment_pause) {
+        if let Err(err) = kill(Pid::from_raw(state.pid as i32), Signal::SIGCONT)
+            && err != Errno::ESRCH
+        {
+            bail!("发送恢复信号失败: {err}");
+        }
+        interrupt_recorder(state.pid)?;
+    }
 
-    if let Err(err) = kill(process_id, Signal::SIGCONT)
-        && err != Errno::ESRCH
-    {
-        bail!("发送恢复信号失败: {err}");
+    if !wait_for_recorder_exit(state.pid) {
+        bail!(
+            "等待录屏进程 {} 结束超时，录屏状态已保留，可稍后重试 record stop 或使用 record recover",
+            state.pid
+        );
+    }
+
+    if !state.segments.is_empty() {
+        let segments: Vec<PathBuf> = state
+            .segments
+            .into_iter()
+            .filter(|segment| segment.is_file())
+            .collect();
+        match segments.as_slice() {
+            [] => bail!("录屏未生成任何文件"),
+            [segment] => fs::rename(segment, &state.output_path)
+                .with_context(|| format!("无法移动录屏文件: {}", state.output_path.display()))?,
+            _ => concat_segments(&segments, &state.output_path)?,
+        }
     }
+    clear_cli_recording_state();
+
+    if state.normalize_audio {
+        normalize_recording_audio(&state.output_path);
+    }
+    Ok(state.output_path)
+}
 
-    if let Err(err) = kill(process_id, Signal::SIGINT)
+fn interrupt_recorder(pid: u32) -> Result<()> {
+    if let Err(err) = kill(Pid::from_raw(pid as i32), Signal::SIGINT)
         && err != Errno::ESRCH
     {
         bail!("发送停止信号失败: {err}");
     }
+    Ok(())
+}
+
+fn wait_for_recorder_exit(pid: u32) -> bool {
+    let deadline = Instant::now() + RECORDER_EXIT_TIMEOUT;
+    while recorder_is_running(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(RECORDER_EXIT_POLL_INTERVAL);
+    }
+    true
+}
+
+fn normalize_recording_audio(path: &Path) {
+    if let Err(err) = loudnorm_recording(path) {
+        eprintln!("音量标准化失败，保留原始录屏: {err:#}");
+    }
+}
 
+fn loudnorm_recording(path: &Path) -> Result<()> {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let normalized = path.with_file_name(format!("{stem}-normalized.{extension}"));
+    let audio_codec = Container::from_name(&extension).and_then(Container::default_audio_codec);
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(path)
+        .args(["-c:v", "copy", "-af", "loudnorm"]);
+    if let Some(codec) = audio_codec {
+        command.args(["-c:a", codec]);
+    }
+    command.arg(&normalized);
+    if let Err(err) = run_command(command, "音量标准化失败") {
+        let _ = fs::remove_file(&normalized);
+        return Err(err);
+    }
+
+    fs::rename(&normalized, path).with_context(|| format!("无法替换录屏文件: {}", path.display()))
+}
+
+pub fn recover_cli_recording() -> Result<RecoveryOutcome> {
+    let Ok(state) = read_cli_recording_state() else {
+        return Ok(RecoveryOutcome::NothingToRecover);
+    };
+    if state.is_live() {
+        bail!("录屏进程 {} 仍在运行，无需恢复", state.pid);
+    }
+
+    Ok(recover_state(state))
+}
+
+fn recover_state(state: CliRecordingState) -> RecoveryOutcome {
     clear_cli_recording_state();
-    Ok(output_path)
+
+    let path = state.output_path;
+    let segments: Vec<PathBuf> = state
+        .segments
+        .into_iter()
+        .filter(|segment| has_content(segment))
+        .collect();
+    let result = match segments.as_slice() {
+        [] if has_content(&path) => remux_recording(&path),
+        [] => return RecoveryOutcome::Unrecoverable(path),
+        _ => recover_segments(&segments, &path),
+    };
+
+    match result {
+        Ok(()) => RecoveryOutcome::Recovered(path),
+        Err(err) => {
+            eprintln!("{err:#}");
+            RecoveryOutcome::Unrecoverable(path)
+        }
+    }
+}
+
+fn recover_segments(segments: &[PathBuf], output_path: &Path) -> Result<()> {
+    for segment in segments {
+        remux_recording(segment)?;
+    }
+    match segments {
+        [segment] => fs::rename(segment, output_path)
+            .with_context(|| format!("无法移动录屏文件: {}", output_path.display())),
+        _ => concat_segments(segments, output_path),
+    }
+}
+
+fn has_content(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|metadata| metadata.len() > 0)
+        .unwrap_or(false)
+}
+
+fn remux_recording(path: &Path) -> Result<()> {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let remuxed = path.with_file_name(format!("{stem}-recovered.{extension}"));
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(path)
+        .args(["-c", "copy"])
+        .arg(&remuxed);
+    if let Err(err) = run_command(command, "修复录屏文件失败") {
+        let _ = fs::remove_file(&remuxed);
+        return Err(err);
+    }
+
+    fs::rename(&remuxed, path).with_context(|| format!("无法替换录屏文件: {}", path.display()))
+}
+
+fn recorder_is_running(pid: u32) -> bool {
+    if !process_is_running(pid) {
+        return false;
+    }
+
+    fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|name| name.trim() == "wf-recorder")
+        .unwrap_or(true)
+}
+
+impl CliRecordingState {
+    pub fn is_live(&self) -> bool {
+        (self.paused && self.segment_pause) || recorder_is_running(self.pid)
+    }
+
+    pub fn current_pause(&self) -> Duration {
+        self.paused_at
+            .and_then(|paused_at| SystemTime::now().duration_since(paused_at).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn recorded_duration(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.started_at)
+            .unwrap_or_default()
+            .saturating_sub(self.paused_duration + self.current_pause())
+    }
 }
 
 pub fn current_cli_recording_state() -> Result<CliRecordingState> {
-    let (pid, output_path) = read_cli_recording_state()?;
-    Ok(CliRecordingState { pid, output_path })
+    read_cli_recording_state()
+}
+
+pub fn process_is_running(pid: u32) -> bool {
+    let process_id = Pid::from_raw(pid as i32);
+    match kill(process_id, None) {
+        Ok(_) => true,
+        Err(err) => err != Errno::ESRCH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+
+    const SEGMENT_FRAMES: usize = 10;
+    const FRAME_INTERVAL: f64 = 0.1;
+
+    fn ffmpeg_available() -> bool {
+        ["ffmpeg", "ffprobe"].iter().all(|tool| {
+            Command::new(tool)
+                .arg("-version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        })
+    }
+
+    fn write_segment(path: &Path, color: &str) {
+        let status = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-y", "-f", "lavfi"])
+            .args(["-i", &format!("color=c={color}:s=64x64:r=10:d=1")])
+            .args(["-c:v", "ffv1"])
+            .arg(path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn frame_timestamps(path: &Path) -> Vec<f64> {
+        let output = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0"])
+            .args(["-show_entries", "frame=pts_time", "-of", "csv=p=0"])
+            .arg(path)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().trim_end_matches(',').parse().ok())
+            .collect()
+    }
+
+    #[test]
+    fn paused_segments_concatenate_with_continuous_timestamps() {
+        if !ffmpeg_available() {
+            eprintln!("未安装 ffmpeg/ffprobe，跳过分段合并测试");
+            return;
+        }
+
+        let dir = env::temp_dir().join(format!("ncaptura-segments-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let output_path = dir.join("recording.mkv");
+        let segments = vec![segment_path(&output_path, 1), segment_path(&output_path, 2)];
+        write_segment(&segments[0], "red");
+        write_segment(&segments[1], "blue");
+
+        concat_segments(&segments, &output_path).unwrap();
+        let timestamps = frame_timestamps(&output_path);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(timestamps.len(), SEGMENT_FRAMES * 2);
+        assert!(timestamps[0].abs() < f64::EPSILON);
+        for pair in timestamps.windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(
+                (gap - FRAME_INTERVAL).abs() < 0.01,
+                "时间戳不连续: {} -> {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert!(segments.iter().all(|segment| !segment.exists()));
+    }
 }
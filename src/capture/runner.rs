@@ -0,0 +1,225 @@
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use std::collections::HashMap;
+use std::io::{self, Read};
+#[cfg(test)]
+use std::os::unix::process::ExitStatusExt;
+#[cfg(test)]
+use std::process::ExitStatus;
+use std::process::{Command, Output, Stdio};
+#[cfg(test)]
+use std::rc::Rc;
+#[cfg(test)]
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub trait CommandRunner: Send + Sync {
+    fn output(&self, command: &mut Command) -> io::Result<Output>;
+
+    fn output_with_timeout(&self, command: &mut Command, _timeout: Duration) -> io::Result<Output> {
+        self.output(command)
+    }
+
+    fn output_with_stdin(&self, command: &mut Command, input: &mut dyn Read) -> io::Result<Output>;
+}
+
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        command.output()
+    }
+
+    fn output_with_timeout(&self, command: &mut Command, timeout: Duration) -> io::Result<Output> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().map(read_in_background);
+        let stderr = child.stderr.take().map(read_in_background);
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("超过 {} 毫秒未响应", timeout.as_millis()),
+                ));
+            }
+            thread::sleep(TIMEOUT_POLL_INTERVAL);
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout.map(join_reader).unwrap_or_default(),
+            stderr: stderr.map(join_reader).unwrap_or_default(),
+        })
+    }
+
+    fn output_with_stdin(&self, command: &mut Command, input: &mut dyn Read) -> io::Result<Output> {
+        let mut child = command.stdin(Stdio::piped()).spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            io::copy(input, &mut stdin).map_err(|err| {
+                let _ = child.kill();
+                let _ = child.wait();
+                io::Error::new(err.kind(), format!("写入标准输入失败: {err}"))
+            })?;
+        }
+        let status = child.wait()?;
+
+        Ok(Output {
+            status,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+fn join_reader(handle: JoinHandle<Vec<u8>>) -> Vec<u8> {
+    handle.join().unwrap_or_default()
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockCommandRunner {
+    responses: Mutex<HashMap<String, Output>>,
+    calls: Mutex<Vec<Vec<String>>>,
+    inputs: Mutex<Vec<Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl MockCommandRunner {
+    pub(crate) fn respond(&self, program: &str, exit_code: i32, stdout: &str, stderr: &str) {
+        let output = Output {
+            status: ExitStatus::from_raw(exit_code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        self.lock_responses().insert(program.to_string(), output);
+    }
+
+    pub(crate) fn calls(&self) -> Vec<Vec<String>> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn inputs(&self) -> Vec<Vec<u8>> {
+        self.inputs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn lock_responses(&self) -> MutexGuard<'_, HashMap<String, Output>> {
+        self.responses
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for MockCommandRunner {
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        let program = command.get_program().to_string_lossy().into_owned();
+        let mut call = vec![program.clone()];
+        call.extend(
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned()),
+        );
+        self.calls
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(call);
+
+        self.lock_responses()
+            .get(&program)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, program))
+    }
+
+    fn output_with_stdin(&self, command: &mut Command, input: &mut dyn Read) -> io::Result<Output> {
+        let mut buffer = Vec::new();
+        input.read_to_end(&mut buffer)?;
+        self.inputs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(buffer);
+        self.output(command)
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_RUNNER: RefCell<Option<Rc<dyn CommandRunner>>> = const { RefCell::new(None) };
+}
+
+#[cfg(test)]
+pub(crate) fn with_command_runner<T>(runner: Rc<dyn CommandRunner>, test: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Rc<dyn CommandRunner>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            TEST_RUNNER.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(TEST_RUNNER.with(|current| current.borrow_mut().replace(runner)));
+    test()
+}
+
+pub(crate) fn command_output(command: &mut Command) -> io::Result<Output> {
+    #[cfg(test)]
+    if let Some(runner) = test_runner() {
+        return runner.output(command);
+    }
+    SystemCommandRunner.output(command)
+}
+
+pub(crate) fn command_output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<Output> {
+    #[cfg(test)]
+    if let Some(runner) = test_runner() {
+        return runner.output_with_timeout(command, timeout);
+    }
+    SystemCommandRunner.output_with_timeout(command, timeout)
+}
+
+pub(crate) fn command_output_with_stdin(
+    command: &mut Command,
+    input: &mut dyn Read,
+) -> io::Result<Output> {
+    #[cfg(test)]
+    if let Some(runner) = test_runner() {
+        return runner.output_with_stdin(command, input);
+    }
+    SystemCommandRunner.output_with_stdin(command, input)
+}
+
+#[cfg(test)]
+fn test_runner() -> Option<Rc<dyn CommandRunner>> {
+    TEST_RUNNER.with(|current| current.borrow().clone())
+}
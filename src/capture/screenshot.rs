@@ -1,27 +1,158 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::Result;
-
-use crate::capture::command_utils::{copy_image_to_clipboard, pick_region_geometry, run_command};
+use crate::capture::command_utils::{
+    copy_image_to_clipboard, parse_geometry, pick_region_geometry, run_command, run_command_output,
+    run_command_with_retries, tool_spawn_error,
+};
+use crate::capture::error::{Context, Result, bail};
 use crate::capture::output::build_output_path;
-use crate::capture::{CaptureTarget, focused_output_name};
+use crate::capture::runner::command_output;
+use crate::capture::windows::{focus_niri_workspace, focused_niri_workspace, niri_workspace};
+use crate::capture::{
+    CaptureError, CaptureTarget, ImageFormat, WorkspaceInfo, focused_output_name, window_geometry,
+};
+use crate::config::config;
+use crate::keybinds::niri_config_path;
+
+const NIRI_SAVE_TIMEOUT: Duration = Duration::from_secs(3);
+const NIRI_SAVE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const WORKSPACE_SWITCH_SETTLE: Duration = Duration::from_millis(300);
+const NIRI_UNKNOWN_ARGUMENT: &str = "unexpected argument";
 
-pub fn take_screenshot(target: CaptureTarget) -> Result<PathBuf> {
-    take_screenshot_with_clipboard(target, false)
+pub fn take_screenshot(target: CaptureTarget, show_pointer: bool) -> Result<PathBuf> {
+    take_screenshot_with_clipboard(target, show_pointer, false)
 }
 
 pub fn take_screenshot_with_clipboard(
     target: CaptureTarget,
+    show_pointer: bool,
+    copy_to_clipboard: bool,
+) -> Result<PathBuf> {
+    take_screenshot_in_format(target, show_pointer, copy_to_clipboard, ImageFormat::Png)
+}
+
+pub fn take_screenshot_in_format(
+    target: CaptureTarget,
+    show_pointer: bool,
     copy_to_clipboard: bool,
+    format: ImageFormat,
 ) -> Result<PathBuf> {
     let output_path = build_output_path(
         "screenshots",
         &format!("screenshot-{}", target.slug()),
-        "png",
+        format.extension(),
     )?;
+    with_target_command(target, show_pointer, |mut command| {
+        command.args(["-t", format.extension()]);
+        save_grim_capture(command, output_path, copy_to_clipboard)
+    })
+}
+
+pub fn screenshot_output_path(target: CaptureTarget) -> Result<PathBuf> {
+    build_output_path(
+        "screenshots",
+        &format!("screenshot-{}", target.slug()),
+        "png",
+    )
+}
+
+pub fn take_frozen_frame(show_pointer: bool) -> Result<PathBuf> {
+    let frame_path = env::temp_dir().join(format!("ncaptura-frame-{}.png", process::id()));
+    let mut command = grim_command(CaptureTarget::Fullscreen, show_pointer)?;
+    command.arg(&frame_path);
+    run_command(command, "冻结屏幕失败")?;
+    Ok(frame_path)
+}
+
+pub fn take_screenshot_with_geometry(
+    geometry: &str,
+    show_pointer: bool,
+    copy_to_clipboard: bool,
+    format: ImageFormat,
+) -> Result<PathBuf> {
+    let geometry = parse_geometry(geometry)?;
+    let output_path = build_output_path("screenshots", "screenshot-geometry", format.extension())?;
+
+    let mut command = grim_base_command(show_pointer);
+    command.args(["-g", &geometry, "-t", format.extension()]);
+    save_grim_capture(command, output_path, copy_to_clipboard)
+}
+
+pub fn take_screenshot_to_bytes(
+    target: CaptureTarget,
+    show_pointer: bool,
+    format: ImageFormat,
+) -> Result<Vec<u8>> {
+    with_target_command(target, show_pointer, |command| {
+        grim_capture_bytes(command, format)
+    })
+}
+
+pub fn take_geometry_screenshot_to_bytes(
+    geometry: &str,
+    show_pointer: bool,
+    format: ImageFormat,
+) -> Result<Vec<u8>> {
+    let geometry = parse_geometry(geometry)?;
+    let mut command = grim_base_command(show_pointer);
+    command.args(["-g", &geometry]);
+    grim_capture_bytes(command, format)
+}
+
+pub fn take_screenshot_to_stdout(
+    target: CaptureTarget,
+    show_pointer: bool,
+    format: ImageFormat,
+) -> Result<()> {
+    with_target_command(target, show_pointer, |command| {
+        grim_capture_to_stdout(command, format)
+    })
+}
+
+pub fn take_geometry_screenshot_to_stdout(
+    geometry: &str,
+    show_pointer: bool,
+    format: ImageFormat,
+) -> Result<()> {
+    let geometry = parse_geometry(geometry)?;
+    let mut command = grim_base_command(show_pointer);
+    command.args(["-g", &geometry]);
+    grim_capture_to_stdout(command, format)
+}
 
+fn grim_capture_to_stdout(mut command: Command, format: ImageFormat) -> Result<()> {
+    command
+        .args(["-t", format.extension(), "-"])
+        .stdout(Stdio::inherit());
+    run_command(command, "截图失败")
+}
+
+fn grim_capture_bytes(mut command: Command, format: ImageFormat) -> Result<Vec<u8>> {
+    command.args(["-t", format.extension(), "-"]);
+    let bytes = run_command_output(command, "截图失败")?;
+
+    if bytes.is_empty() {
+        bail!("截图失败: grim 未输出图像数据");
+    }
+
+    Ok(bytes)
+}
+
+fn grim_base_command(show_pointer: bool) -> Command {
     let mut command = Command::new("grim");
+    if show_pointer {
+        command.arg("-c");
+    }
+    command
+}
+
+fn grim_command(target: CaptureTarget, show_pointer: bool) -> Result<Command> {
+    let mut command = grim_base_command(show_pointer);
     match target {
         CaptureTarget::Region => {
             let geometry = pick_region_geometry()?;
@@ -32,10 +163,79 @@ pub fn take_screenshot_with_clipboard(
                 command.args(["-o", &output_name]);
             }
         }
+        CaptureTarget::Output(output_name) => {
+            command.args(["-o", &output_name]);
+        }
+        CaptureTarget::AllOutputs => {}
+        CaptureTarget::Workspace(_) => bail!("工作区截图需要先切换到目标工作区"),
+    }
+
+    Ok(command)
+}
+
+fn with_target_command<T>(
+    target: CaptureTarget,
+    show_pointer: bool,
+    capture: impl FnOnce(Command) -> Result<T>,
+) -> Result<T> {
+    let CaptureTarget::Workspace(workspace_id) = target else {
+        return capture(grim_command(target, show_pointer)?);
+    };
+
+    let workspace = niri_workspace(workspace_id)?;
+    let output = workspace
+        .output
+        .clone()
+        .with_context(|| format!("工作区 {workspace_id} 当前不在任何输出上"))?;
+    let switch = WorkspaceSwitch::enter(&workspace)?;
+    let mut command = grim_base_command(show_pointer);
+    command.args(["-o", &output]);
+    let result = capture(command);
+    drop(switch);
+    result
+}
+
+struct WorkspaceSwitch {
+    previous: Option<WorkspaceInfo>,
+}
+
+impl WorkspaceSwitch {
+    fn enter(workspace: &WorkspaceInfo) -> Result<Self> {
+        let previous = focused_niri_workspace()?;
+        if previous.id == workspace.id {
+            return Ok(Self { previous: None });
+        }
+
+        let switch = Self {
+            previous: Some(previous),
+        };
+        focus_niri_workspace(workspace)?;
+        thread::sleep(WORKSPACE_SWITCH_SETTLE);
+        Ok(switch)
+    }
+}
+
+impl Drop for WorkspaceSwitch {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take()
+            && let Err(err) = focus_niri_workspace(&previous)
+        {
+            eprintln!("恢复之前的工作区失败: {err}");
+        }
+    }
+}
+
+fn save_grim_capture(
+    mut command: Command,
+    output_path: PathBuf,
+    copy_to_clipboard: bool,
+) -> Result<PathBuf> {
+    if copy_to_clipboard && output_path.extension().is_some_and(|ext| ext != "png") {
+        bail!("仅 PNG 格式的截图可以复制到剪贴板");
     }
 
     command.arg(&output_path);
-    run_command(command, "截图失败")?;
+    run_command_with_retries(command, "截图失败", config().grim_retries)?;
 
     if copy_to_clipboard {
         copy_image_to_clipboard(&output_path)?;
@@ -44,26 +244,62 @@ pub fn take_screenshot_with_clipboard(
     Ok(output_path)
 }
 
-pub fn take_window_screenshot(window_id: u64, copy_to_clipboard: bool) -> Result<PathBuf> {
+pub fn take_window_screenshot(
+    window_id: u64,
+    show_pointer: bool,
+    copy_to_clipboard: bool,
+) -> Result<PathBuf> {
     let output_path = build_output_path(
         "screenshots",
         &format!("screenshot-window-{window_id}"),
         "png",
     )?;
 
-    let mut command = Command::new("grim");
+    let mut command = grim_base_command(show_pointer);
     command.args(["-T", &window_id.to_string()]);
-    command.arg(&output_path);
-    run_command(command, "截图失败")?;
+    save_grim_capture(command, output_path, copy_to_clipboard)
+}
 
-    if copy_to_clipboard {
-        copy_image_to_clipboard(&output_path)?;
-    }
+pub fn take_padded_window_screenshot(
+    window_id: u64,
+    padding: u32,
+    show_pointer: bool,
+    copy_to_clipboard: bool,
+) -> Result<PathBuf> {
+    let geometry = window_geometry(window_id)?.padded(padding);
+    let output_path = build_output_path(
+        "screenshots",
+        &format!("screenshot-window-{window_id}"),
+        "png",
+    )?;
 
-    Ok(output_path)
+    let mut command = grim_base_command(show_pointer);
+    command.args(["-g", &geometry.to_string()]);
+    save_grim_capture(command, output_path, copy_to_clipboard)
 }
 
-pub fn take_window_screenshot_via_niri(window_id: u64) -> Result<()> {
+pub fn take_window_screenshot_via_niri(window_id: u64, copy_to_clipboard: bool) -> Result<PathBuf> {
+    let output_path = build_output_path(
+        "screenshots",
+        &format!("screenshot-window-{window_id}"),
+        "png",
+    )?;
+
+    match niri_screenshot_window(window_id, &output_path) {
+        Ok(()) => {
+            wait_for_niri_file(|| output_path.is_file().then(|| output_path.clone()))?;
+            if copy_to_clipboard {
+                copy_image_to_clipboard(&output_path)?;
+            }
+            return Ok(output_path);
+        }
+        Err(CaptureError::ProtocolUnsupported(_)) => {}
+        Err(err) => return Err(err),
+    }
+
+    let screenshot_dir = niri_screenshot_dir()?;
+    let started_at = SystemTime::now();
+
     let mut focus = Command::new("niri");
     focus.args([
         "msg",
@@ -74,14 +310,158 @@ pub fn take_window_screenshot_via_niri(window_id: u64) -> Result<()> {
     ]);
     run_command(focus, "聚焦目标窗口失败")?;
 
+    thread::sleep(config().niri_focus_settle());
+
     let mut screenshot = Command::new("niri");
     screenshot.args(["msg", "action", "screenshot-window"]);
     run_command(screenshot, "niri 窗口截图失败")?;
 
-    Ok(())
+    let path = wait_for_niri_file(|| newest_file_since(&screenshot_dir, started_at))?;
+    if copy_to_clipboard {
+        copy_image_to_clipboard(&path)?;
+    }
+    Ok(path)
 }
 
-pub fn is_window_protocol_unsupported_error(err: &anyhow::Error) -> bool {
-    err.to_string()
-        .contains("compositor doesn't support the screen capture protocol")
+fn niri_screenshot_window(window_id: u64, output_path: &Path) -> Result<()> {
+    let mut command = Command::new("niri");
+    command
+        .args([
+            "msg",
+            "action",
+            "screenshot-window",
+            "--id",
+            &window_id.to_string(),
+            "--path",
+        ])
+        .arg(output_path);
+    let output = command_output(&mut command).map_err(|err| tool_spawn_error("niri", err))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if stderr.contains(NIRI_UNKNOWN_ARGUMENT) {
+        return Err(CaptureError::ProtocolUnsupported(format!(
+            "当前 niri 不支持 screenshot-window --id/--path: {stderr}"
+        )));
+    }
+    if stderr.is_empty() {
+        bail!("niri 窗口截图失败: 退出码 {}", output.status);
+    }
+    bail!("niri 窗口截图失败: {stderr}")
+}
+
+fn wait_for_niri_file(mut find: impl FnMut() -> Option<PathBuf>) -> Result<PathBuf> {
+    let deadline = Instant::now() + NIRI_SAVE_TIMEOUT;
+    loop {
+        if let Some(path) = find() {
+            return Ok(path);
+        }
+        if Instant::now() >= deadline {
+            bail!("niri 未在预期时间内保存截图文件");
+        }
+        thread::sleep(NIRI_SAVE_POLL_INTERVAL);
+    }
+}
+
+fn niri_screenshot_dir() -> Result<PathBuf> {
+    let configured = niri_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| {
+            data.lines().find_map(|line| {
+                let value = line.trim().strip_prefix("screenshot-path")?.trim();
+                Some(value.to_string())
+            })
+        });
+
+    let template = match configured.as_deref() {
+        Some("null") => bail!("niri 配置了 screenshot-path null，不会将截图保存到磁盘"),
+        Some(value) => value.trim_matches('"').to_string(),
+        None => {
+            let pictures_dir = dirs::picture_dir().context("无法确定 niri 截图目录")?;
+            return Ok(pictures_dir.join("Screenshots"));
+        }
+    };
+
+    let path = match template.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().context("无法确定主目录")?.join(rest),
+        None => PathBuf::from(&template),
+    };
+    path.parent()
+        .map(Path::to_path_buf)
+        .with_context(|| format!("无法解析 niri 截图路径: {template}"))
+}
+
+fn newest_file_since(dir: &Path, since: SystemTime) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            (metadata.is_file() && modified >= since).then(|| (modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::capture::runner::{MockCommandRunner, with_command_runner};
+
+    fn screenshot_window_with(exit_code: i32, stderr: &str) -> Result<()> {
+        let runner = MockCommandRunner::default();
+        runner.respond("niri", exit_code, "", stderr);
+        with_command_runner(Rc::new(runner), || {
+            niri_screenshot_window(7, Path::new("/tmp/window.png"))
+        })
+    }
+
+    #[test]
+    fn niri_screenshot_window_passes_id_and_path() {
+        let runner = Rc::new(MockCommandRunner::default());
+        runner.respond("niri", 0, "", "");
+        with_command_runner(runner.clone(), || {
+            niri_screenshot_window(7, Path::new("/tmp/window.png"))
+        })
+        .unwrap();
+
+        assert_eq!(
+            runner.calls(),
+            vec![vec![
+                "niri",
+                "msg",
+                "action",
+                "screenshot-window",
+                "--id",
+                "7",
+                "--path",
+                "/tmp/window.png"
+            ]]
+        );
+    }
+
+    #[test]
+    fn niri_screenshot_window_reports_old_niri_as_unsupported() {
+        let err = screenshot_window_with(
+            2,
+            "error: unexpected argument '--path' found\n\nUsage: niri msg action screenshot-window",
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, CaptureError::ProtocolUnsupported(_)));
+    }
+
+    #[test]
+    fn niri_screenshot_window_keeps_other_failures() {
+        let err = screenshot_window_with(1, "Error: no window with id 7").unwrap_err();
+
+        assert!(matches!(err, CaptureError::Failed(_)));
+        assert!(err.to_string().contains("no window with id 7"));
+    }
 }
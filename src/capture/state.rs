@@ -1,45 +1,133 @@
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result, bail};
-use serde_json::Value;
+use serde_json::{Value, json};
+
+use crate::capture::CliRecordingState;
+use crate::capture::error::{Context, Result, bail};
 
 const CLI_RECORDING_STATE_FILE: &str = "recording.json";
+const LAST_REGION_FILE: &str = "last_region.json";
 
-pub(crate) fn write_cli_recording_state(pid: u32, output_path: &Path) -> Result<()> {
+pub(crate) fn write_cli_recording_state(state: &CliRecordingState) -> Result<()> {
     let state_dir = cli_state_dir()?;
     fs::create_dir_all(&state_dir)
         .with_context(|| format!("无法创建状态目录: {}", state_dir.display()))?;
 
     let file_path = state_dir.join(CLI_RECORDING_STATE_FILE);
-    let data = serde_json::json!({
-        "pid": pid,
-        "output_path": output_path,
-    });
-
-    fs::write(&file_path, data.to_string())
+    fs::write(&file_path, state.to_json().to_string())
         .with_context(|| format!("无法写入状态文件: {}", file_path.display()))?;
 
     Ok(())
 }
 
-pub(crate) fn read_cli_recording_state() -> Result<(u32, PathBuf)> {
+pub(crate) fn read_cli_recording_state() -> Result<CliRecordingState> {
     let file_path = cli_state_dir()?.join(CLI_RECORDING_STATE_FILE);
     let data = fs::read_to_string(&file_path)
         .with_context(|| format!("无法读取录屏状态文件: {}", file_path.display()))?;
 
     let value: Value = serde_json::from_str(&data).context("录屏状态文件解析失败")?;
-    let pid = value
-        .get("pid")
-        .and_then(Value::as_u64)
-        .context("录屏状态缺少 pid")? as u32;
+    CliRecordingState::from_json(&value, || {
+        fs::metadata(&file_path)
+            .and_then(|meta| meta.modified())
+            .unwrap_or_else(|_| SystemTime::now())
+    })
+}
 
-    let output_path = value
-        .get("output_path")
-        .and_then(Value::as_str)
-        .context("录屏状态缺少 output_path")?;
+impl CliRecordingState {
+    fn to_json(&self) -> Value {
+        json!({
+            "pid": self.pid,
+            "output_path": self.output_path,
+            "target": self.target,
+            "audio_device": self.audio_device,
+            "max_duration_secs": self.max_duration.map(|duration| duration.as_secs()),
+            "started_at": self
+                .started_at
+                .duration_since(UNIX_EPOCH)
+                .map(|since_epoch| since_epoch.as_secs_f64())
+                .unwrap_or_default(),
+            "normalize_audio": self.normalize_audio,
+            "paused": self.paused,
+            "paused_at": self.paused_at.map(|paused_at| {
+                paused_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|since_epoch| since_epoch.as_secs_f64())
+                    .unwrap_or_default()
+            }),
+            "paused_duration_secs": self.paused_duration.as_secs_f64(),
+            "segment_pause": self.segment_pause,
+            "recorder_args": self.recorder_args,
+            "segments": self.segments,
+        })
+    }
 
-    Ok((pid, PathBuf::from(output_path)))
+    fn from_json(value: &Value, fallback_started_at: impl FnOnce() -> SystemTime) -> Result<Self> {
+        let pid = value
+            .get("pid")
+            .and_then(Value::as_u64)
+            .and_then(|pid| u32::try_from(pid).ok())
+            .context("录屏状态缺少 pid")?;
+        let output_path = value
+            .get("output_path")
+            .and_then(Value::as_str)
+            .context("录屏状态缺少 output_path")?;
+        let optional_string =
+            |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+        let optional_bool = |key: &str| value.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+        Ok(Self {
+            pid,
+            output_path: PathBuf::from(output_path),
+            target: optional_string("target"),
+            audio_device: optional_string("audio_device"),
+            max_duration: value
+                .get("max_duration_secs")
+                .and_then(Value::as_u64)
+                .map(Duration::from_secs),
+            started_at: value
+                .get("started_at")
+                .and_then(Value::as_f64)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .and_then(|since_epoch| UNIX_EPOCH.checked_add(since_epoch))
+                .unwrap_or_else(fallback_started_at),
+            normalize_audio: optional_bool("normalize_audio"),
+            paused: optional_bool("paused"),
+            paused_at: value
+                .get("paused_at")
+                .and_then(Value::as_f64)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .and_then(|since_epoch| UNIX_EPOCH.checked_add(since_epoch)),
+            paused_duration: value
+                .get("paused_duration_secs")
+                .and_then(Value::as_f64)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .unwrap_or_default(),
+            segment_pause: optional_bool("segment_pause"),
+            recorder_args: value
+                .get("recorder_args")
+                .and_then(Value::as_array)
+                .map(|args| {
+                    args.iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            segments: value
+                .get("segments")
+                .and_then(Value::as_array)
+                .map(|segments| {
+                    segments
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
 }
 
 pub(crate) fn clear_cli_recording_state() {
@@ -48,7 +136,32 @@ pub(crate) fn clear_cli_recording_state() {
     }
 }
 
-fn cli_state_dir() -> Result<PathBuf> {
+pub(crate) fn write_last_region(geometry: &str) -> Result<()> {
+    let state_dir = cli_state_dir()?;
+    fs::create_dir_all(&state_dir)
+        .with_context(|| format!("无法创建状态目录: {}", state_dir.display()))?;
+
+    let file_path = state_dir.join(LAST_REGION_FILE);
+    let data = json!({ "geometry": geometry });
+
+    fs::write(&file_path, data.to_string())
+        .with_context(|| format!("无法写入区域文件: {}", file_path.display()))?;
+
+    Ok(())
+}
+
+pub(crate) fn read_last_region() -> Option<String> {
+    let file_path = cli_state_dir().ok()?.join(LAST_REGION_FILE);
+    let data = fs::read_to_string(file_path).ok()?;
+    let value: Value = serde_json::from_str(&data).ok()?;
+
+    value
+        .get("geometry")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+pub(crate) fn cli_state_dir() -> Result<PathBuf> {
     if let Some(state_dir) = dirs::state_dir() {
         return Ok(state_dir.join("ncaptura"));
     }
@@ -59,3 +172,122 @@ fn cli_state_dir() -> Result<PathBuf> {
 
     bail!("无法定位状态目录")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::capture::recording::segment_path;
+
+    fn sample_state() -> CliRecordingState {
+        CliRecordingState {
+            pid: 4242,
+            output_path: PathBuf::from("/tmp/ncaptura/recording.mkv"),
+            target: Some("region".to_string()),
+            audio_device: Some("alsa_output.monitor".to_string()),
+            max_duration: Some(Duration::from_secs(90)),
+            started_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            normalize_audio: true,
+            paused: true,
+            paused_at: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_030)),
+            paused_duration: Duration::from_millis(12_500),
+            segment_pause: true,
+            recorder_args: vec!["-g".to_string(), "0,0 100x100".to_string()],
+            segments: vec![
+                segment_path(Path::new("/tmp/ncaptura/recording.mkv"), 1),
+                segment_path(Path::new("/tmp/ncaptura/recording.mkv"), 2),
+            ],
+        }
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let state = sample_state();
+        let restored = CliRecordingState::from_json(&state.to_json(), || unreachable!()).unwrap();
+
+        assert_eq!(restored.pid, state.pid);
+        assert_eq!(restored.output_path, state.output_path);
+        assert_eq!(restored.target, state.target);
+        assert_eq!(restored.audio_device, state.audio_device);
+        assert_eq!(restored.max_duration, state.max_duration);
+        assert_eq!(restored.started_at, state.started_at);
+        assert!(restored.normalize_audio);
+        assert!(restored.paused);
+        assert_eq!(restored.paused_at, state.paused_at);
+        assert_eq!(restored.paused_duration, state.paused_duration);
+        assert!(restored.segment_pause);
+        assert_eq!(restored.recorder_args, state.recorder_args);
+        assert_eq!(restored.segments, state.segments);
+        assert_eq!(
+            restored.segments[0],
+            PathBuf::from("/tmp/ncaptura/recording-part1.mkv")
+        );
+    }
+
+    #[test]
+    fn old_state_files_default_new_fields() {
+        let value = json!({
+            "pid": 17,
+            "output_path": "/tmp/ncaptura/old.mkv",
+            "target": "fullscreen",
+            "audio_device": null,
+            "max_duration_secs": null,
+        });
+        let fallback = UNIX_EPOCH + Duration::from_secs(42);
+        let restored = CliRecordingState::from_json(&value, || fallback).unwrap();
+
+        assert_eq!(restored.pid, 17);
+        assert_eq!(restored.target.as_deref(), Some("fullscreen"));
+        assert_eq!(restored.audio_device, None);
+        assert_eq!(restored.max_duration, None);
+        assert_eq!(restored.started_at, fallback);
+        assert!(!restored.normalize_audio);
+        assert!(!restored.paused);
+        assert_eq!(restored.paused_at, None);
+        assert_eq!(restored.paused_duration, Duration::ZERO);
+        assert!(!restored.segment_pause);
+        assert!(restored.recorder_args.is_empty());
+        assert!(restored.segments.is_empty());
+    }
+
+    #[test]
+    fn corrupt_time_fields_fall_back_instead_of_panicking() {
+        let fallback = UNIX_EPOCH + Duration::from_secs(42);
+        for bad in [json!(-1.0), json!(1e300), json!("soon")] {
+            let value = json!({
+                "pid": 17,
+                "output_path": "/tmp/ncaptura/old.mkv",
+                "started_at": bad,
+                "paused_at": bad,
+                "paused_duration_secs": bad,
+            });
+            let restored = CliRecordingState::from_json(&value, || fallback).unwrap();
+
+            assert_eq!(restored.started_at, fallback, "{bad}");
+            assert_eq!(restored.paused_at, None, "{bad}");
+            assert_eq!(restored.paused_duration, Duration::ZERO, "{bad}");
+        }
+    }
+
+    #[test]
+    fn recorded_duration_excludes_paused_time() {
+        let now = SystemTime::now();
+        let mut state = sample_state();
+        state.started_at = now - Duration::from_secs(100);
+        state.paused_duration = Duration::from_secs(30);
+        state.paused_at = Some(now - Duration::from_secs(20));
+
+        let recorded = state.recorded_duration().as_secs_f64();
+        assert!((recorded - 50.0).abs() < 1.0, "{recorded}");
+    }
+
+    #[test]
+    fn state_without_pid_or_output_path_is_rejected() {
+        let missing_pid = json!({ "output_path": "/tmp/ncaptura/a.mkv" });
+        let missing_path = json!({ "pid": 1 });
+
+        assert!(CliRecordingState::from_json(&missing_pid, SystemTime::now).is_err());
+        assert!(CliRecordingState::from_json(&missing_path, SystemTime::now).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::capture::command_utils::{run_command, tool_spawn_error};
+use crate::capture::error::{Context, Result, bail};
+use crate::capture::recording::{concat_segments, segment_path};
+use crate::capture::{WebcamConfig, WebcamPosition};
+
+const WEBCAM_MARGIN: u32 = 24;
+const WEBCAM_STARTUP_GRACE: Duration = Duration::from_millis(300);
+
+pub(crate) struct WebcamRecording {
+    config: WebcamConfig,
+    output_path: PathBuf,
+    child: Option<Child>,
+    segments: Vec<PathBuf>,
+}
+
+pub(crate) fn start_webcam(config: WebcamConfig, recording_path: &Path) -> Result<WebcamRecording> {
+    let stem = recording_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let mut webcam = WebcamRecording {
+        config,
+        output_path: recording_path.with_file_name(format!("{stem}-webcam.mkv")),
+        child: None,
+        segments: Vec::new(),
+    };
+    webcam.resume()?;
+    Ok(webcam)
+}
+
+impl WebcamRecording {
+    pub(crate) fn resume(&mut self) -> Result<()> {
+        let segment = segment_path(&self.output_path, self.segments.len() + 1);
+        let mut child = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args(["-f", "v4l2", "-i"])
+            .arg(&self.config.device)
+            .args(["-an", "-c:v", "libx264", "-preset", "ultrafast"])
+            .arg(&segment)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|err| tool_spawn_error("ffmpeg", err))?;
+
+        thread::sleep(WEBCAM_STARTUP_GRACE);
+        if let Some(status) = child.try_wait().context("读取摄像头进程状态失败")? {
+            bail!("无法打开摄像头 {} ({status})", self.config.device.display());
+        }
+
+        self.segments.push(segment);
+        self.child = Some(child);
+        Ok(())
+    }
+
+    pub(crate) fn pause(&mut self) -> Result<()> {
+        let Some(mut child) = self.child.take() else {
+            return Ok(());
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(b"q");
+        }
+        let status = child.wait().context("等待摄像头录制结束失败")?;
+        if !status.success() {
+            bail!("摄像头录制进程异常退出: {status}");
+        }
+        Ok(())
+    }
+
+    pub(crate) fn discard(mut self) {
+        let _ = self.pause();
+        for segment in &self.segments {
+            let _ = fs::remove_file(segment);
+        }
+    }
+
+    pub(crate) fn overlay_onto(mut self, recording_path: &Path) -> Result<()> {
+        self.pause()?;
+        let segments: Vec<PathBuf> = self
+            .segments
+            .iter()
+            .filter(|segment| segment.is_file())
+            .cloned()
+            .collect();
+        let webcam_path = match segments.as_slice() {
+            [] => bail!("摄像头未录制到任何画面"),
+            [segment] => segment.clone(),
+            _ => {
+                concat_segments(&segments, &self.output_path)?;
+                self.output_path.clone()
+            }
+        };
+
+        let extension = recording_path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let composed = recording_path.with_extension(format!("overlay.{extension}"));
+        let filter = format!(
+            "[1:v]scale={}:-2[cam];[0:v][cam]overlay={}:eof_action=pass[video]",
+            self.config.width,
+            overlay_position(self.config.position, WEBCAM_MARGIN)
+        );
+
+        let mut command = Command::new("ffmpeg");
+        command
+            .args(["-hide_banner", "-loglevel", "error", "-y", "-i"])
+            .arg(recording_path)
+            .arg("-i")
+            .arg(&webcam_path)
+            .args(["-filter_complex", &filter])
+            .args(["-map", "[video]", "-map", "0:a?", "-c:a", "copy"])
+            .arg(&composed);
+        if let Err(err) = run_command(command, "合成摄像头画面失败") {
+            let _ = fs::remove_file(&composed);
+            return Err(err);
+        }
+
+        fs::rename(&composed, recording_path)
+            .with_context(|| format!("无法替换录屏文件: {}", recording_path.display()))?;
+        let _ = fs::remove_file(&webcam_path);
+        Ok(())
+    }
+}
+
+fn overlay_position(position: WebcamPosition, margin: u32) -> String {
+    match position {
+        WebcamPosition::TopLeft => format!("{margin}:{margin}"),
+        WebcamPosition::TopRight => format!("W-w-{margin}:{margin}"),
+        WebcamPosition::BottomLeft => format!("{margin}:H-h-{margin}"),
+        WebcamPosition::BottomRight => format!("W-w-{margin}:H-h-{margin}"),
+    }
+}
+
+impl Drop for WebcamRecording {
+    fn drop(&mut self) {
+        let _ = self.pause();
+    }
+}
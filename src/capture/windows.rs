@@ -1,23 +1,354 @@
+use std::env;
+use std::io;
 use std::process::Command;
 
-use anyhow::{Context, Result, bail};
 use serde_json::Value;
 
-use crate::capture::WindowInfo;
+use crate::capture::command_utils::run_command;
+use crate::capture::error::{Context, Result, bail};
+use crate::capture::runner::command_output_with_timeout;
+use crate::capture::{CaptureError, OutputGeometry, OutputInfo, WindowInfo, WorkspaceInfo};
+use crate::config::config;
+
+const OWN_APP_ID: &str = "io.ncaptura.app";
+const NIRI_FOCUSED_OUTPUT_POINTERS: &[&str] = &[
+    "/Ok/FocusedOutput",
+    "/FocusedOutput",
+    "/Ok/focused_output",
+    "/focused_output",
+    "/output",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compositor {
+    Niri,
+    Sway,
+    Hyprland,
+    Unknown,
+}
+
+impl Compositor {
+    pub fn detect() -> Self {
+        if env::var_os("NIRI_SOCKET").is_some() {
+            return Self::Niri;
+        }
+        if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+            return Self::Hyprland;
+        }
+        if env::var_os("SWAYSOCK").is_some() {
+            return Self::Sway;
+        }
+
+        let desktop = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .to_lowercase();
+        if desktop.contains("niri") {
+            Self::Niri
+        } else if desktop.contains("hyprland") {
+            Self::Hyprland
+        } else if desktop.contains("sway") {
+            Self::Sway
+        } else {
+            Self::Unknown
+        }
+    }
+}
 
 pub fn list_windows() -> Result<Vec<WindowInfo>> {
-    let output = Command::new("niri")
-        .args(["msg", "--json", "windows"])
-        .output()
-        .context("无法调用 niri msg windows，请确认正在 niri 会话中")?;
+    let mut windows = match Compositor::detect() {
+        Compositor::Niri | Compositor::Unknown => niri_windows()?,
+        Compositor::Sway => sway_windows()?,
+        Compositor::Hyprland => hyprland_windows()?,
+    };
 
-    if !output.status.success() {
-        bail!("niri msg windows 执行失败");
+    windows.sort_by_key(|w| (!w.is_focused, w.workspace_id, w.title.clone()));
+    Ok(windows)
+}
+
+pub fn list_capturable_windows() -> Result<Vec<WindowInfo>> {
+    let mut windows = list_windows()?;
+    windows.retain(|w| !w.app_id.starts_with(OWN_APP_ID));
+    Ok(windows)
+}
+
+pub fn focused_output_name() -> Result<String> {
+    match Compositor::detect() {
+        Compositor::Niri | Compositor::Unknown => niri_focused_output_name(),
+        Compositor::Sway => focused_entry_name(
+            compositor_json("swaymsg", &["-r", "-t", "get_outputs"])?,
+            "swaymsg get_outputs",
+        ),
+        Compositor::Hyprland => focused_entry_name(
+            compositor_json("hyprctl", &["-j", "monitors"])?,
+            "hyprctl monitors",
+        ),
+    }
+}
+
+pub fn list_outputs() -> Result<Vec<OutputInfo>> {
+    let mut outputs = match Compositor::detect() {
+        Compositor::Niri | Compositor::Unknown => niri_outputs()?,
+        Compositor::Sway => sway_outputs()?,
+        Compositor::Hyprland => hyprland_outputs()?,
+    };
+
+    outputs.sort_by_key(|output| (output.geometry.x, output.geometry.y));
+    Ok(outputs)
+}
+
+pub fn all_outputs_geometry() -> Result<String> {
+    let bounds = list_outputs()?
+        .into_iter()
+        .map(|output| output.geometry)
+        .reduce(OutputGeometry::union)
+        .context("没有找到已启用的输出")?;
+    Ok(bounds.to_string())
+}
+
+pub fn cursor_position() -> Option<(f64, f64)> {
+    if Compositor::detect() != Compositor::Hyprland {
+        return None;
+    }
+    let position = compositor_json("hyprctl", &["-j", "cursorpos"]).ok()?;
+    Some((position.get("x")?.as_f64()?, position.get("y")?.as_f64()?))
+}
+
+pub(crate) fn output_containing(
+    outputs: &[OutputInfo],
+    region: OutputGeometry,
+) -> Option<&OutputInfo> {
+    outputs
+        .iter()
+        .find(|output| output.geometry.contains(region))
+}
+
+pub fn window_geometry(window_id: u64) -> Result<OutputGeometry> {
+    let geometry = match Compositor::detect() {
+        Compositor::Niri | Compositor::Unknown => niri_window_geometry(window_id)?,
+        Compositor::Sway => {
+            let tree = compositor_json("swaymsg", &["-r", "-t", "get_tree"])?;
+            find_sway_node(&tree, window_id)
+                .and_then(|node| node.get("rect"))
+                .and_then(geometry_from_value)
+        }
+        Compositor::Hyprland => hyprland_window_geometry(window_id)?,
+    };
+    geometry.with_context(|| format!("无法获取窗口 {window_id} 的位置，窗口可能不在当前可见区域"))
+}
+
+pub(crate) fn visible_window_geometries() -> Result<Vec<OutputGeometry>> {
+    match Compositor::detect() {
+        Compositor::Niri | Compositor::Unknown => {
+            let windows = compositor_json("niri", &["msg", "--json", "windows"])?;
+            let workspaces = compositor_json("niri", &["msg", "--json", "workspaces"])?;
+            let outputs = niri_outputs()?;
+            Ok(windows
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|window| {
+                    niri_workspace_of(&workspaces, window)
+                        .and_then(|workspace| workspace.get("is_active"))
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false)
+                })
+                .filter_map(|window| niri_window_rect(window, &workspaces, &outputs).ok()?)
+                .collect())
+        }
+        Compositor::Sway | Compositor::Hyprland => Ok(list_windows()?
+            .into_iter()
+            .filter_map(|window| window_geometry(window.id).ok())
+            .collect()),
+    }
+}
+
+fn niri_window_geometry(window_id: u64) -> Result<Option<OutputGeometry>> {
+    let windows = compositor_json("niri", &["msg", "--json", "windows"])?;
+    let Some(window) = windows
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|window| window.get("id").and_then(Value::as_u64) == Some(window_id))
+    else {
+        bail!("未找到窗口 {window_id}");
+    };
+
+    let workspaces = compositor_json("niri", &["msg", "--json", "workspaces"])?;
+    niri_window_rect(window, &workspaces, &niri_outputs()?)
+}
+
+fn niri_workspace_of<'a>(workspaces: &'a Value, window: &Value) -> Option<&'a Value> {
+    let workspace_id = window.get("workspace_id").and_then(Value::as_u64);
+    workspaces
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|workspace| workspace.get("id").and_then(Value::as_u64) == workspace_id)
+}
+
+fn niri_window_rect(
+    window: &Value,
+    workspaces: &Value,
+    outputs: &[OutputInfo],
+) -> Result<Option<OutputGeometry>> {
+    let Some(layout) = window.get("layout") else {
+        bail!("当前 niri 版本未提供窗口布局信息");
+    };
+    let (Some((tile_x, tile_y)), Some((width, height))) = (
+        number_pair(layout.get("tile_pos_in_workspace_view")),
+        number_pair(layout.get("window_size")),
+    ) else {
+        return Ok(None);
+    };
+    let (offset_x, offset_y) = number_pair(layout.get("window_offset_in_tile")).unwrap_or_default();
+
+    let output_name = niri_workspace_of(workspaces, window)
+        .and_then(|workspace| workspace.get("output").and_then(Value::as_str))
+        .context("无法确定窗口所在的输出")?;
+    let output = outputs
+        .iter()
+        .find(|output| output.name == output_name)
+        .with_context(|| format!("未找到输出: {output_name}"))?;
+
+    Ok(Some(OutputGeometry {
+        x: output.geometry.x + (tile_x + offset_x).round() as i64,
+        y: output.geometry.y + (tile_y + offset_y).round() as i64,
+        width: width.round() as i64,
+        height: height.round() as i64,
+    }))
+}
+
+fn find_sway_node(node: &Value, id: u64) -> Option<&Value> {
+    if node.get("id").and_then(Value::as_u64) == Some(id) {
+        return Some(node);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(*key).and_then(Value::as_array))
+        .flatten()
+        .find_map(|child| find_sway_node(child, id))
+}
+
+fn hyprland_window_geometry(window_id: u64) -> Result<Option<OutputGeometry>> {
+    let clients = compositor_json("hyprctl", &["-j", "clients"])?;
+    let address = format!("0x{window_id:x}");
+    let Some(client) = clients
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|client| client.get("address").and_then(Value::as_str) == Some(address.as_str()))
+    else {
+        bail!("未找到窗口 {window_id}");
+    };
+
+    Ok(number_pair(client.get("at"))
+        .zip(number_pair(client.get("size")))
+        .map(|((x, y), (width, height))| OutputGeometry {
+            x: x as i64,
+            y: y as i64,
+            width: width as i64,
+            height: height as i64,
+        }))
+}
+
+fn number_pair(value: Option<&Value>) -> Option<(f64, f64)> {
+    match value?.as_array()?.as_slice() {
+        [first, second] => Some((first.as_f64()?, second.as_f64()?)),
+        _ => None,
     }
+}
 
-    let stdout = String::from_utf8(output.stdout).context("niri windows JSON 输出不是 UTF-8")?;
-    let values: Vec<Value> =
-        serde_json::from_str(stdout.trim()).context("niri windows JSON 解析失败")?;
+fn niri_outputs() -> Result<Vec<OutputInfo>> {
+    let outputs = match compositor_json("niri", &["msg", "--json", "outputs"])? {
+        Value::Object(outputs) => outputs,
+        _ => bail!("niri outputs JSON 解析失败"),
+    };
+
+    Ok(outputs
+        .into_iter()
+        .filter_map(|(name, output)| {
+            let logical = output.get("logical")?;
+            let geometry = geometry_from_value(logical)?;
+            Some(output_info(&output, name, geometry, output_scale(logical)))
+        })
+        .collect())
+}
+
+fn sway_outputs() -> Result<Vec<OutputInfo>> {
+    let outputs = compositor_json("swaymsg", &["-r", "-t", "get_outputs"])?;
+    Ok(outputs
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|output| output.get("active").and_then(Value::as_bool) != Some(false))
+        .filter_map(|output| {
+            let name = output.get("name").and_then(Value::as_str)?.to_string();
+            let geometry = output.get("rect").and_then(geometry_from_value)?;
+            Some(output_info(output, name, geometry, output_scale(output)))
+        })
+        .collect())
+}
+
+fn hyprland_outputs() -> Result<Vec<OutputInfo>> {
+    let monitors = compositor_json("hyprctl", &["-j", "monitors"])?;
+    Ok(monitors
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|monitor| monitor.get("disabled").and_then(Value::as_bool) != Some(true))
+        .filter_map(|monitor| {
+            let name = monitor.get("name").and_then(Value::as_str)?.to_string();
+            let mut geometry = geometry_from_value(monitor)?;
+            let scale = output_scale(monitor);
+            geometry.width = (geometry.width as f64 / scale).round() as i64;
+            geometry.height = (geometry.height as f64 / scale).round() as i64;
+            if monitor
+                .get("transform")
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+                % 2
+                == 1
+            {
+                std::mem::swap(&mut geometry.width, &mut geometry.height);
+            }
+            Some(output_info(monitor, name, geometry, scale))
+        })
+        .collect())
+}
+
+fn output_info(output: &Value, name: String, geometry: OutputGeometry, scale: f64) -> OutputInfo {
+    OutputInfo {
+        name,
+        make: string_field(output, "make", "Unknown"),
+        model: string_field(output, "model", "Unknown"),
+        geometry,
+        scale,
+    }
+}
+
+fn output_scale(value: &Value) -> f64 {
+    value
+        .get("scale")
+        .and_then(Value::as_f64)
+        .filter(|scale| *scale > 0.0)
+        .unwrap_or(1.0)
+}
+
+fn geometry_from_value(value: &Value) -> Option<OutputGeometry> {
+    Some(OutputGeometry {
+        x: value.get("x")?.as_i64()?,
+        y: value.get("y")?.as_i64()?,
+        width: value.get("width")?.as_i64()?,
+        height: value.get("height")?.as_i64()?,
+    })
+}
+
+fn niri_windows() -> Result<Vec<WindowInfo>> {
+    let values = match compositor_json("niri", &["msg", "--json", "windows"])? {
+        Value::Array(values) => values,
+        _ => bail!("niri windows JSON 解析失败"),
+    };
 
     let mut windows = Vec::new();
     for item in values {
@@ -25,61 +356,482 @@ pub fn list_windows() -> Result<Vec<WindowInfo>> {
             continue;
         };
 
-        let title = item
-            .get("title")
-            .and_then(Value::as_str)
-            .unwrap_or("(untitled)")
-            .to_string();
-        let app_id = item
+        windows.push(WindowInfo {
+            id,
+            title: string_field(&item, "title", "(untitled)"),
+            app_id: string_field(&item, "app_id", "unknown"),
+            workspace_id: item
+                .get("workspace_id")
+                .and_then(Value::as_u64)
+                .unwrap_or_default(),
+            is_focused: item
+                .get("is_focused")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        });
+    }
+
+    Ok(windows)
+}
+
+fn sway_windows() -> Result<Vec<WindowInfo>> {
+    let tree = compositor_json("swaymsg", &["-r", "-t", "get_tree"])?;
+    let mut windows = Vec::new();
+    collect_sway_windows(&tree, 0, &mut windows);
+    Ok(windows)
+}
+
+fn collect_sway_windows(node: &Value, workspace_id: u64, windows: &mut Vec<WindowInfo>) {
+    let workspace_id = if node.get("type").and_then(Value::as_str) == Some("workspace") {
+        node.get("num")
+            .and_then(Value::as_u64)
+            .or_else(|| node.get("id").and_then(Value::as_u64))
+            .unwrap_or(workspace_id)
+    } else {
+        workspace_id
+    };
+
+    if node.get("pid").and_then(Value::as_u64).is_some()
+        && let Some(id) = node.get("id").and_then(Value::as_u64)
+    {
+        let app_id = node
             .get("app_id")
             .and_then(Value::as_str)
+            .or_else(|| {
+                node.pointer("/window_properties/class")
+                    .and_then(Value::as_str)
+            })
             .unwrap_or("unknown")
             .to_string();
-        let workspace_id = item
-            .get("workspace_id")
-            .and_then(Value::as_u64)
-            .unwrap_or_default();
-        let is_focused = item
-            .get("is_focused")
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
 
         windows.push(WindowInfo {
             id,
-            title,
+            title: string_field(node, "name", "(untitled)"),
             app_id,
             workspace_id,
-            is_focused,
+            is_focused: node
+                .get("focused")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        });
+    }
+
+    for key in ["nodes", "floating_nodes"] {
+        if let Some(children) = node.get(key).and_then(Value::as_array) {
+            for child in children {
+                collect_sway_windows(child, workspace_id, windows);
+            }
+        }
+    }
+}
+
+fn hyprland_windows() -> Result<Vec<WindowInfo>> {
+    let values = match compositor_json("hyprctl", &["-j", "clients"])? {
+        Value::Array(values) => values,
+        _ => bail!("hyprctl clients JSON 解析失败"),
+    };
+
+    let mut windows = Vec::new();
+    for item in values {
+        let Some(id) = item
+            .get("address")
+            .and_then(Value::as_str)
+            .and_then(|address| u64::from_str_radix(address.trim_start_matches("0x"), 16).ok())
+        else {
+            continue;
+        };
+
+        windows.push(WindowInfo {
+            id,
+            title: string_field(&item, "title", "(untitled)"),
+            app_id: string_field(&item, "class", "unknown"),
+            workspace_id: item
+                .pointer("/workspace/id")
+                .and_then(Value::as_u64)
+                .unwrap_or_default(),
+            is_focused: item.get("focusHistoryID").and_then(Value::as_i64) == Some(0),
         });
     }
 
-    windows.sort_by_key(|w| (!w.is_focused, w.workspace_id, w.title.clone()));
     Ok(windows)
 }
 
-pub fn focused_output_name() -> Result<String> {
-    let output = Command::new("niri")
-        .args(["msg", "--json", "focused-output"])
-        .output()
-        .context("无法调用 niri msg，请确认正在 niri 会话中")?;
+pub fn list_workspaces() -> Result<Vec<WorkspaceInfo>> {
+    if Compositor::detect() != Compositor::Niri {
+        bail!("工作区列表仅支持 niri");
+    }
 
-    if !output.status.success() {
-        bail!("niri msg focused-output 执行失败");
+    let values = match compositor_json("niri", &["msg", "--json", "workspaces"])? {
+        Value::Array(values) => values,
+        _ => bail!("niri workspaces JSON 解析失败"),
+    };
+
+    let mut workspaces = Vec::new();
+    for item in values {
+        let Some(id) = item.get("id").and_then(Value::as_u64) else {
+            continue;
+        };
+
+        workspaces.push(WorkspaceInfo {
+            id,
+            idx: item.get("idx").and_then(Value::as_u64).unwrap_or_default(),
+            name: item.get("name").and_then(Value::as_str).map(str::to_string),
+            output: item
+                .get("output")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            is_active: item
+                .get("is_active")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            is_focused: item
+                .get("is_focused")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        });
     }
 
-    let stdout = String::from_utf8(output.stdout).context("niri JSON 输出不是 UTF-8")?;
-    let data: Value = serde_json::from_str(stdout.trim()).context("niri JSON 解析失败")?;
+    workspaces.sort_by_key(|w| (w.output.clone(), w.idx));
+    Ok(workspaces)
+}
+
+pub(crate) fn niri_workspace(workspace_id: u64) -> Result<WorkspaceInfo> {
+    list_workspaces()?
+        .into_iter()
+        .find(|workspace| workspace.id == workspace_id)
+        .with_context(|| format!("未找到工作区 {workspace_id}"))
+}
 
-    if let Some(name) = data.get("name").and_then(Value::as_str) {
-        return Ok(name.to_string());
+pub(crate) fn focused_niri_workspace() -> Result<WorkspaceInfo> {
+    list_workspaces()?
+        .into_iter()
+        .find(|workspace| workspace.is_focused)
+        .context("未从 niri workspaces 返回中找到聚焦的工作区")
+}
+
+pub(crate) fn focus_niri_workspace(workspace: &WorkspaceInfo) -> Result<()> {
+    if let Some(output) = &workspace.output {
+        let mut focus_monitor = Command::new("niri");
+        focus_monitor.args(["msg", "action", "focus-monitor", output]);
+        run_command(focus_monitor, "切换 niri 输出失败")?;
     }
 
-    if let Some(name) = data
-        .pointer("/Ok/FocusedOutput/name")
+    let mut focus_workspace = Command::new("niri");
+    focus_workspace.args([
+        "msg",
+        "action",
+        "focus-workspace",
+        &workspace.idx.to_string(),
+    ]);
+    run_command(focus_workspace, "切换 niri 工作区失败")
+}
+
+fn niri_focused_output_name() -> Result<String> {
+    let data = compositor_json("niri", &["msg", "--json", "focused-output"])?;
+    focused_output_from_niri_reply(&data)
+}
+
+fn focused_output_from_niri_reply(data: &Value) -> Result<String> {
+    let error = data
+        .get("Err")
+        .or_else(|| data.pointer("/Ok/Err"))
+        .or_else(|| data.get("error"));
+    if let Some(error) = error {
+        let message = error
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(CaptureError::CompositorError(format!(
+            "niri focused-output 返回错误: {message}"
+        )));
+    }
+
+    let focused = NIRI_FOCUSED_OUTPUT_POINTERS
+        .iter()
+        .find_map(|pointer| data.pointer(pointer))
+        .or_else(|| data.get("name").is_some().then_some(data))
+        .context("未从 niri focused-output 返回中找到输出名称")?;
+    if focused.is_null() {
+        bail!("niri 当前没有聚焦的输出");
+    }
+
+    focused
+        .get("name")
         .and_then(Value::as_str)
-    {
-        return Ok(name.to_string());
+        .map(str::to_string)
+        .context("未从 niri focused-output 返回中找到输出名称")
+}
+
+fn focused_entry_name(data: Value, source: &str) -> Result<String> {
+    data.as_array()
+        .into_iter()
+        .flatten()
+        .find(|entry| entry.get("focused").and_then(Value::as_bool) == Some(true))
+        .and_then(|entry| entry.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .with_context(|| format!("未从 {source} 返回中找到聚焦的输出"))
+}
+
+fn compositor_json(program: &str, args: &[&str]) -> Result<Value> {
+    let command_line = format!("{program} {}", args.join(" "));
+    let mut command = Command::new(program);
+    command.args(args);
+    let output = match command_output_with_timeout(&mut command, config().compositor_timeout()) {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+            return Err(CaptureError::CompositorError(format!(
+                "{command_line} 无响应（{err}），合成器 IPC 可能正忙"
+            )));
+        }
+        Err(err) => {
+            return Err(CaptureError::CompositorError(format!(
+                "无法调用 {command_line}，请确认正在对应的合成器会话中: {err}"
+            )));
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        return Err(CaptureError::CompositorError(if stderr.is_empty() {
+            format!("{command_line} 执行失败")
+        } else {
+            format!("{command_line} 执行失败: {stderr}")
+        }));
     }
 
-    bail!("未从 niri focused-output 返回中找到输出名称")
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| format!("{command_line} 的 JSON 输出不是 UTF-8"))?;
+    serde_json::from_str(stdout.trim()).with_context(|| format!("{command_line} 的 JSON 解析失败"))
+}
+
+fn string_field(item: &Value, key: &str, fallback: &str) -> String {
+    item.get(key)
+        .and_then(Value::as_str)
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::capture::runner::{MockCommandRunner, with_command_runner};
+
+    fn with_reply<T>(program: &str, stdout: &str, test: impl FnOnce() -> T) -> T {
+        let runner = MockCommandRunner::default();
+        runner.respond(program, 0, stdout, "");
+        with_command_runner(Rc::new(runner), test)
+    }
+
+    fn output(name: &str, x: i64, y: i64, width: i64, height: i64) -> OutputInfo {
+        OutputInfo {
+            name: name.to_string(),
+            make: "Unknown".to_string(),
+            model: "Unknown".to_string(),
+            geometry: OutputGeometry {
+                x,
+                y,
+                width,
+                height,
+            },
+            scale: 1.0,
+        }
+    }
+
+    fn region(x: i64, y: i64, width: i64, height: i64) -> OutputGeometry {
+        OutputGeometry {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn output_containing_finds_output_for_region() {
+        let outputs = [
+            output("DP-1", -1920, 0, 1920, 1080),
+            output("eDP-1", 0, 0, 1280, 800),
+        ];
+
+        let found = output_containing(&outputs, region(-1920, 0, 100, 100)).unwrap();
+        assert_eq!(found.name, "DP-1");
+        let found = output_containing(&outputs, region(1180, 700, 100, 100)).unwrap();
+        assert_eq!(found.name, "eDP-1");
+    }
+
+    #[test]
+    fn output_containing_rejects_regions_spanning_outputs() {
+        let outputs = [
+            output("DP-1", -1920, 0, 1920, 1080),
+            output("eDP-1", 0, 0, 1280, 800),
+        ];
+
+        assert!(output_containing(&outputs, region(-50, 100, 100, 100)).is_none());
+        assert!(output_containing(&outputs, region(0, 750, 100, 100)).is_none());
+        assert!(output_containing(&[], region(0, 0, 1, 1)).is_none());
+    }
+
+    #[test]
+    fn niri_outputs_use_logical_geometry() {
+        let reply = r#"{
+            "DP-1": {"make": "Dell", "model": "U2720Q",
+                     "logical": {"x": -1920, "y": 0, "width": 1920, "height": 1080, "scale": 2.0}},
+            "HDMI-A-1": {"make": "LG", "model": "27GL", "logical": null}
+        }"#;
+        let outputs = with_reply("niri", reply, niri_outputs).unwrap();
+
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].name, "DP-1");
+        assert_eq!(outputs[0].make, "Dell");
+        assert_eq!(outputs[0].scale, 2.0);
+        assert_eq!(
+            outputs[0].geometry,
+            OutputGeometry {
+                x: -1920,
+                y: 0,
+                width: 1920,
+                height: 1080
+            }
+        );
+    }
+
+    #[test]
+    fn sway_outputs_skip_inactive_outputs() {
+        let reply = r#"[
+            {"name": "eDP-1", "active": true, "scale": 1.5,
+             "rect": {"x": 0, "y": 0, "width": 1280, "height": 800}},
+            {"name": "DP-2", "active": false,
+             "rect": {"x": 0, "y": 0, "width": 0, "height": 0}}
+        ]"#;
+        let outputs = with_reply("swaymsg", reply, sway_outputs).unwrap();
+
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].name, "eDP-1");
+        assert_eq!(outputs[0].make, "Unknown");
+        assert_eq!(outputs[0].scale, 1.5);
+    }
+
+    #[test]
+    fn hyprland_outputs_convert_to_logical_size() {
+        let reply = r#"[
+            {"name": "DP-1", "x": 0, "y": 0, "width": 3840, "height": 2160,
+             "scale": 2.0, "transform": 1},
+            {"name": "DP-2", "x": 0, "y": 0, "width": 1920, "height": 1080, "disabled": true}
+        ]"#;
+        let outputs = with_reply("hyprctl", reply, hyprland_outputs).unwrap();
+
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(
+            outputs[0].geometry,
+            OutputGeometry {
+                x: 0,
+                y: 0,
+                width: 1080,
+                height: 1920
+            }
+        );
+    }
+
+    #[test]
+    fn niri_windows_skip_entries_without_id() {
+        let reply = r#"[
+            {"id": 7, "title": "Editor", "app_id": "code", "workspace_id": 2, "is_focused": true},
+            {"title": "Ghost"},
+            {"id": 9, "title": null}
+        ]"#;
+        let windows = with_reply("niri", reply, niri_windows).unwrap();
+
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].id, 7);
+        assert_eq!(windows[0].app_id, "code");
+        assert_eq!(windows[0].workspace_id, 2);
+        assert!(windows[0].is_focused);
+        assert_eq!(windows[1].title, "(untitled)");
+        assert_eq!(windows[1].app_id, "unknown");
+    }
+
+    #[test]
+    fn niri_focused_output_reads_ok_reply() {
+        let reply = r#"{"Ok": {"FocusedOutput": {"name": "DP-1", "make": "Dell"}}}"#;
+        let name = with_reply("niri", reply, niri_focused_output_name).unwrap();
+
+        assert_eq!(name, "DP-1");
+    }
+
+    #[test]
+    fn niri_focused_output_reads_legacy_replies() {
+        for reply in [
+            r#"{"FocusedOutput": {"name": "eDP-1"}}"#,
+            r#"{"Ok": {"focused_output": {"name": "eDP-1"}}}"#,
+            r#"{"output": {"name": "eDP-1"}}"#,
+        ] {
+            let name = with_reply("niri", reply, niri_focused_output_name).unwrap();
+            assert_eq!(name, "eDP-1", "{reply}");
+        }
+    }
+
+    #[test]
+    fn niri_focused_output_reports_err_reply() {
+        for reply in [
+            r#"{"Err": "compositor busy"}"#,
+            r#"{"Ok": {"Err": "compositor busy"}}"#,
+        ] {
+            let err = with_reply("niri", reply, niri_focused_output_name).unwrap_err();
+            assert!(matches!(err, CaptureError::CompositorError(_)), "{reply}");
+            assert!(err.to_string().contains("compositor busy"), "{reply}");
+        }
+    }
+
+    #[test]
+    fn niri_focused_output_reads_bare_output_object() {
+        let reply = r#"{"name": "DP-1", "make": "Dell", "logical": {"x": 0, "y": 0}}"#;
+        let name = with_reply("niri", reply, niri_focused_output_name).unwrap();
+
+        assert_eq!(name, "DP-1");
+    }
+
+    #[test]
+    fn niri_focused_output_rejects_unfocused_and_unknown_replies() {
+        let err = with_reply(
+            "niri",
+            r#"{"Ok": {"FocusedOutput": null}}"#,
+            niri_focused_output_name,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("没有聚焦的输出"));
+
+        let err = with_reply("niri", r#"{"Ok": "Handled"}"#, niri_focused_output_name).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("未从 niri focused-output 返回中找到输出名称")
+        );
+    }
+
+    #[test]
+    fn compositor_json_reports_stderr_on_failure() {
+        let runner = MockCommandRunner::default();
+        runner.respond("niri", 1, "", "Error: unknown command\n");
+        let err = with_command_runner(Rc::new(runner), niri_focused_output_name).unwrap_err();
+
+        assert!(matches!(err, CaptureError::CompositorError(_)));
+        assert!(
+            err.to_string()
+                .ends_with("执行失败: Error: unknown command")
+        );
+    }
+
+    #[test]
+    fn compositor_json_passes_arguments_to_runner() {
+        let runner = Rc::new(MockCommandRunner::default());
+        runner.respond("niri", 0, "[]", "");
+        with_command_runner(runner.clone(), niri_windows).unwrap();
+
+        assert_eq!(
+            runner.calls(),
+            vec![vec!["niri", "msg", "--json", "windows"]]
+        );
+    }
 }
@@ -1,20 +1,105 @@
 use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use gtk::gdk::prelude::*;
+use serde_json::{Value, json};
 
 use crate::capture::{
-    CaptureTarget, start_recording_detached, stop_recording_detached, take_screenshot,
+    CaptureError, CaptureTarget, Container, HOLD_CLIPBOARD_FLAG, ImageFormat, QualityPreset,
+    RecordingOptions, RecoveryOutcome, build_output_path, copy_image_to_clipboard,
+    current_cli_recording_state, extract_frame, last_region_geometry, list_capturable_windows,
+    parse_geometry, pick_region_geometry, probe_media, prune_captures, recover_cli_recording,
+    set_clipboard_image, set_quiet, set_verbose, start_recording_detached, stop_recording_detached,
+    take_geometry_screenshot_to_bytes, take_geometry_screenshot_to_stdout,
+    take_padded_window_screenshot, take_screenshot_in_format, take_screenshot_to_bytes,
+    take_screenshot_to_stdout, take_screenshot_with_clipboard, take_screenshot_with_geometry,
+    take_window_screenshot, take_window_screenshot_via_niri, with_temporary_output,
+};
+use crate::completions::{Shell, completion_script};
+use crate::config::config;
+use crate::daemon::run_daemon;
+use crate::deps::{TOOLS, VERSIONED_TOOLS, check_dependencies, install_hint_for, tool_version};
+use crate::diff::{compare_images, load_baseline};
+use crate::history::{CaptureKind, log_capture, recent_history};
+use crate::i18n::{Locale, set_locale, t};
+use crate::imaging::{Encoding, load_png_bytes, save_image};
+use crate::ipc::{daemon_running, send_command};
+use crate::keybinds::RECOMMENDED_KEYBINDS;
+use crate::launch::open_with_default_app;
+use crate::notify::notify;
+use crate::scroll_capture::scroll_capture;
+use crate::share::{read_clipboard_text, render_qr_code, serve_file};
+use crate::ui::{
+    HudPosition, RecordingOverlays, run_cli_recording_hud, run_pin_window, run_share_window,
 };
-use crate::ui::run_cli_recording_hud;
+
+const DEFAULT_SCROLL_STEPS: u32 = 5;
+const DEFAULT_HISTORY_ENTRIES: usize = 20;
 
 pub fn handle_cli_if_requested() -> Result<(), i32> {
-    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
     if args.is_empty() {
         return Ok(());
     }
 
+    if args.len() == 1 && args[0] == HOLD_CLIPBOARD_FLAG {
+        return match run_hold_clipboard() {
+            Ok(()) => Err(0),
+            Err(err) => {
+                eprintln!("{err:#}");
+                Err(1)
+            }
+        };
+    }
+
+    let quiet = take_global_flag(&mut args, &["--quiet", "-q"]);
+    set_verbose(take_global_flag(&mut args, &["--verbose"]));
+    let format = if take_global_flag(&mut args, &["--json"]) {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Human
+    };
+    set_quiet(quiet || matches!(format, OutputFormat::Json));
+    let output = CliOutput { format, quiet };
+
+    match take_global_value(&mut args, "--lang") {
+        Ok(None) => {}
+        Ok(Some(lang)) => match Locale::from_name(&lang) {
+            Some(locale) => set_locale(locale),
+            None => {
+                output.error(t!("cli.lang_unsupported", lang = lang));
+                return Err(2);
+            }
+        },
+        Err(message) => {
+            output.error(message);
+            return Err(2);
+        }
+    }
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(command) = daemon_forwarded_command(&args) {
+        return match run_msg(&command, &output) {
+            Ok(()) => Err(0),
+            Err(code) => Err(code),
+        };
+    }
+
     let result = match parse_cli_command(&args) {
-        Ok(command) => run_cli_command(command),
+        Ok(command) => run_cli_command(command, &output),
         Err(message) => {
-            eprintln!("{message}\n\n{}", cli_usage());
+            match format {
+                OutputFormat::Human => eprintln!("{message}\n\n{}", cli_usage()),
+                OutputFormat::Json => output.error(message),
+            }
             Err(2)
         }
     };
@@ -25,126 +110,1590 @@ pub fn handle_cli_if_requested() -> Result<(), i32> {
     }
 }
 
-fn run_cli_command(command: CliCommand) -> Result<(), i32> {
+fn run_hold_clipboard() -> anyhow::Result<()> {
+    let mut bytes = Vec::new();
+    io::stdin()
+        .read_to_end(&mut bytes)
+        .context("读取剪贴板数据失败")?;
+    gtk::init().context("无法初始化 GTK")?;
+    let pixbuf = load_png_bytes(&bytes)?;
+    set_clipboard_image(&pixbuf)?;
+
+    let clipboard = gtk::gdk::Display::default()
+        .context("没有可用的显示")?
+        .clipboard();
+    let main_loop = gtk::glib::MainLoop::new(None, false);
+    {
+        let main_loop = main_loop.clone();
+        clipboard.connect_changed(move |clipboard| {
+            if !clipboard.is_local() {
+                main_loop.quit();
+            }
+        });
+    }
+    main_loop.run();
+    Ok(())
+}
+
+fn daemon_forwarded_command(args: &[String]) -> Option<String> {
+    let words: Vec<&str> = args.iter().map(String::as_str).collect();
+    let forwardable = match words.as_slice() {
+        ["screenshot", "region" | "fullscreen" | "all-outputs"] => true,
+        ["record", "start", "region" | "fullscreen" | "all-outputs"] => true,
+        ["record", "stop"] => current_cli_recording_state().is_err(),
+        _ => false,
+    };
+    (forwardable && daemon_running()).then(|| words.join(" "))
+}
+
+fn take_global_flag(args: &mut Vec<String>, names: &[&str]) -> bool {
+    let original_len = args.len();
+    args.retain(|arg| !names.contains(&arg.as_str()));
+    args.len() != original_len
+}
+
+fn take_global_value(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let prefix = format!("{name}=");
+    if let Some(index) = args.iter().position(|arg| arg.starts_with(&prefix)) {
+        let arg = args.remove(index);
+        return Ok(Some(arg[prefix.len()..].to_string()));
+    }
+
+    let Some(index) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        return Err(t!("cli.lang_missing").to_string());
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Human,
+    Json,
+}
+
+struct CliOutput {
+    format: OutputFormat,
+    quiet: bool,
+}
+
+impl CliOutput {
+    fn success(&self, human: impl FnOnce() -> String, json: Value) {
+        if self.quiet {
+            return;
+        }
+
+        match self.format {
+            OutputFormat::Human => println!("{}", human()),
+            OutputFormat::Json => println!("{json}"),
+        }
+    }
+
+    fn cancelled(&self) {
+        if self.quiet {
+            return;
+        }
+
+        match self.format {
+            OutputFormat::Human => eprintln!("{}", t!("cli.cancelled")),
+            OutputFormat::Json => println!("{}", json!({ "cancelled": true })),
+        }
+    }
+
+    fn error(&self, message: String) {
+        match self.format {
+            OutputFormat::Human => eprintln!("{message}"),
+            OutputFormat::Json => println!("{}", json!({ "error": message })),
+        }
+    }
+}
+
+fn run_cli_command(command: CliCommand, output: &CliOutput) -> Result<(), i32> {
     match command {
-        CliCommand::Screenshot { target } => match take_screenshot(target) {
-            Ok(path) => {
-                println!("截图已保存: {}", path.display());
+        CliCommand::Screenshot { source, flags } => run_screenshot(source, &flags, output),
+        CliCommand::Grab(target) => run_grab(target, output),
+        CliCommand::RecordStart {
+            target,
+            options,
+            show_hud,
+            overlays,
+            hud_position,
+            delay_seconds,
+        } => run_record_start(
+            target,
+            &options,
+            show_hud,
+            overlays,
+            hud_position,
+            delay_seconds,
+            output,
+        ),
+        CliCommand::RecordStop { thumbnail } => run_record_stop(thumbnail, output),
+        CliCommand::RecordStatus => run_record_status(output),
+        CliCommand::RecordHud => run_record_hud(output),
+        CliCommand::RecordRecover => match recover_cli_recording() {
+            Ok(RecoveryOutcome::NothingToRecover) => {
+                output.success(
+                    || t!("record.nothing_to_recover").to_string(),
+                    json!({ "recovered": false, "path": null }),
+                );
                 Ok(())
             }
+            Ok(RecoveryOutcome::Recovered(path)) => {
+                output.success(
+                    || t!("record.recovered", path = path.display()),
+                    json!({ "recovered": true, "path": path }),
+                );
+                Ok(())
+            }
+            Ok(RecoveryOutcome::Unrecoverable(path)) => {
+                output.error(t!("record.unrecoverable", path = path.display()));
+                Err(1)
+            }
             Err(err) => {
-                eprintln!("截图失败: {err}");
+                output.error(t!("record.recover_failed", err = err));
                 Err(1)
             }
         },
-        CliCommand::RecordStart { target, audio } => {
-            match start_recording_detached(target, audio) {
-                Ok(state) => {
-                    println!(
-                        "录屏已开始，输出文件: {}\n已显示右上角录制小窗，可在小窗中暂停/停止，或使用 `ncaptura record stop` 停止录屏。",
-                        state.output_path.display()
-                    );
-                    run_cli_recording_hud(state);
-                    Ok(())
-                }
-                Err(err) => {
-                    eprintln!("开始录屏失败: {err}");
-                    Err(1)
-                }
-            }
+        CliCommand::Diff(request) => run_diff(request, output),
+        CliCommand::ScrollCapture { geometry, steps } => {
+            run_scroll_capture(geometry, steps, output)
         }
-        CliCommand::RecordStop => match stop_recording_detached() {
-            Ok(path) => {
-                println!("录屏已停止，文件保存为: {}", path.display());
+        CliCommand::ListWindows => match list_capturable_windows() {
+            Ok(windows) => {
+                let windows: Vec<Value> = windows
+                    .iter()
+                    .map(|window| {
+                        json!({
+                            "id": window.id,
+                            "title": window.title,
+                            "app_id": window.app_id,
+                            "workspace_id": window.workspace_id,
+                            "is_focused": window.is_focused,
+                        })
+                    })
+                    .collect();
+                println!("{}", Value::Array(windows));
                 Ok(())
             }
             Err(err) => {
-                eprintln!("停止录屏失败: {err}");
+                output.error(t!("windows.list_failed", err = err));
                 Err(1)
             }
         },
+        CliCommand::Share(source) => run_share(source, output),
+        CliCommand::Pin => run_pin(output),
+        CliCommand::CheckDeps => run_check_deps(output),
+        CliCommand::Version => {
+            run_version(output);
+            Ok(())
+        }
+        CliCommand::Completions(shell) => {
+            print!("{}", completion_script(shell));
+            Ok(())
+        }
+        CliCommand::Portal => run_portal(output),
+        CliCommand::Daemon => run_daemon().map_err(|err| {
+            output.error(t!("daemon.failed", err = format!("{err:#}")));
+            1
+        }),
+        CliCommand::Msg(command) => run_msg(&command, output),
+        CliCommand::History(limit) => run_history(limit, output),
+        CliCommand::Prune { dry_run } => run_prune(dry_run, output),
         CliCommand::Help => {
-            println!("{}", cli_usage());
+            let usage = cli_usage();
+            match output.format {
+                OutputFormat::Human => println!("{usage}"),
+                OutputFormat::Json => println!("{}", json!({ "usage": usage })),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_record_start(
+    target: CaptureTarget,
+    options: &RecordingOptions,
+    show_hud: bool,
+    overlays: RecordingOverlays,
+    hud_position: Option<HudPosition>,
+    delay_seconds: u32,
+    output: &CliOutput,
+) -> Result<(), i32> {
+    for remaining in (1..=delay_seconds).rev() {
+        if !output.quiet {
+            eprintln!("{}", t!("record.countdown", seconds = remaining));
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let target_label = target.slug();
+    match start_recording_detached(target, options) {
+        Ok(state) => {
+            log_capture(CaptureKind::Recording, target_label, Ok(&state.output_path));
+            output.success(
+                || {
+                    let hint = if show_hud {
+                        t!("record.hud_hint")
+                    } else {
+                        t!("record.stop_hint")
+                    };
+                    t!(
+                        "record.started",
+                        path = state.output_path.display(),
+                        hint = hint
+                    )
+                },
+                json!({ "path": state.output_path, "pid": state.pid }),
+            );
+            if show_hud {
+                run_cli_recording_hud(
+                    state,
+                    overlays,
+                    hud_position.unwrap_or(config().hud_position),
+                );
+            }
             Ok(())
         }
+        Err(CaptureError::Cancelled) => {
+            output.cancelled();
+            Ok(())
+        }
+        Err(err) => {
+            log_capture(CaptureKind::Recording, target_label, Err(err.to_string()));
+            output.error(t!("record.start_failed", err = err));
+            Err(1)
+        }
     }
 }
 
+fn run_screenshot(
+    source: ScreenshotSource,
+    flags: &ScreenshotFlags,
+    output: &CliOutput,
+) -> Result<(), i32> {
+    let copy_to_clipboard = flags.copy || flags.copy_only;
+    let source = match source {
+        ScreenshotSource::Target(_) if flags.repeat_region => last_region_geometry()
+            .map(ScreenshotSource::Geometry)
+            .unwrap_or(source),
+        source => source,
+    };
+
+    if flags.stdout {
+        let result = match &source {
+            ScreenshotSource::Geometry(geometry) => {
+                take_geometry_screenshot_to_stdout(geometry, flags.show_pointer, flags.format)
+            }
+            ScreenshotSource::Target(target) => {
+                take_screenshot_to_stdout(target.clone(), flags.show_pointer, flags.format)
+            }
+            ScreenshotSource::Window(_) => unreachable!("--stdout is rejected for windows"),
+        };
+        return match result {
+            Ok(()) => Ok(()),
+            Err(CaptureError::Cancelled) => {
+                output.cancelled();
+                Ok(())
+            }
+            Err(err) => {
+                output.error(t!("screenshot.failed", err = err));
+                Err(1)
+            }
+        };
+    }
+
+    let target_label = source.label();
+    let capture = || match source {
+        ScreenshotSource::Geometry(geometry) => take_screenshot_with_geometry(
+            &geometry,
+            flags.show_pointer,
+            copy_to_clipboard,
+            flags.format,
+        ),
+        ScreenshotSource::Target(target) => {
+            take_screenshot_in_format(target, flags.show_pointer, copy_to_clipboard, flags.format)
+        }
+        ScreenshotSource::Window(window_id) => match flags.padding {
+            Some(padding) => take_padded_window_screenshot(
+                window_id,
+                padding,
+                flags.show_pointer,
+                copy_to_clipboard,
+            ),
+            None => {
+                match take_window_screenshot(window_id, flags.show_pointer, copy_to_clipboard) {
+                    Err(CaptureError::ProtocolUnsupported(_)) => {
+                        take_window_screenshot_via_niri(window_id, copy_to_clipboard)
+                    }
+                    result => result,
+                }
+            }
+        },
+    };
+    let result = if flags.copy_only {
+        with_temporary_output(capture)
+    } else {
+        capture()
+    };
+
+    let path = match result {
+        Ok(path) => path,
+        Err(CaptureError::Cancelled) => {
+            output.cancelled();
+            return Ok(());
+        }
+        Err(err) => {
+            log_capture(CaptureKind::Screenshot, &target_label, Err(err.to_string()));
+            output.error(t!("screenshot.failed", err = err));
+            return Err(1);
+        }
+    };
+
+    if flags.copy_only {
+        if let Err(err) = fs::remove_file(&path) {
+            eprintln!(
+                "{}",
+                t!(
+                    "screenshot.remove_temp_failed",
+                    path = path.display(),
+                    err = err
+                )
+            );
+        }
+        output.success(
+            || t!("screenshot.copied").to_string(),
+            json!({ "path": null, "copied": true }),
+        );
+        notify(t!("screenshot.copied"), "", None);
+        return Ok(());
+    }
+
+    log_capture(CaptureKind::Screenshot, &target_label, Ok(&path));
+    output.success(
+        || {
+            let mut message = t!("screenshot.saved", path = path.display());
+            if flags.copy {
+                message.push('\n');
+                message.push_str(t!("screenshot.copied"));
+            }
+            message
+        },
+        json!({ "path": path, "copied": flags.copy }),
+    );
+    notify(
+        t!("screenshot.saved_title"),
+        &path.display().to_string(),
+        Some(&path),
+    );
+    if flags.open
+        && let Err(err) = open_with_default_app(&path)
+    {
+        eprintln!("{}", t!("screenshot.open_failed", err = err));
+    }
+    Ok(())
+}
+
+fn run_grab(target: CaptureTarget, output: &CliOutput) -> Result<(), i32> {
+    let target_label = target.slug();
+    let path = match take_screenshot_in_format(target, false, false, ImageFormat::Png) {
+        Ok(path) => path,
+        Err(CaptureError::Cancelled) => {
+            output.cancelled();
+            return Ok(());
+        }
+        Err(err) => {
+            log_capture(CaptureKind::Screenshot, target_label, Err(err.to_string()));
+            output.error(t!("screenshot.failed", err = err));
+            return Err(1);
+        }
+    };
+    log_capture(CaptureKind::Screenshot, target_label, Ok(&path));
+
+    let copied = match copy_image_to_clipboard(&path) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("{}", t!("grab.copy_failed", err = err));
+            false
+        }
+    };
+
+    output.success(
+        || {
+            let mut message = t!("screenshot.saved", path = path.display());
+            if copied {
+                message.push('\n');
+                message.push_str(t!("screenshot.copied"));
+            }
+            message
+        },
+        json!({ "path": path, "copied": copied }),
+    );
+    let summary = if copied {
+        t!("grab.notify_title")
+    } else {
+        t!("screenshot.saved_title")
+    };
+    notify(summary, &path.display().to_string(), Some(&path));
+    Ok(())
+}
+
+fn run_scroll_capture(geometry: Option<String>, steps: u32, output: &CliOutput) -> Result<(), i32> {
+    let geometry = match geometry.map_or_else(pick_region_geometry, Ok) {
+        Ok(geometry) => geometry,
+        Err(CaptureError::Cancelled) => {
+            output.cancelled();
+            return Ok(());
+        }
+        Err(err) => {
+            output.error(t!("scroll.failed", err = err));
+            return Err(1);
+        }
+    };
+
+    let saved = scroll_capture(&geometry, steps).and_then(|pixbuf| {
+        let path = build_output_path("screenshots", "screenshot-scroll", "png")?;
+        save_image(&pixbuf, &path, Encoding::Png)?;
+        Ok((path, pixbuf.height()))
+    });
+    let (path, height) = match saved {
+        Ok(saved) => saved,
+        Err(err) => {
+            log_capture(CaptureKind::Screenshot, "scroll", Err(format!("{err:#}")));
+            output.error(t!("scroll.failed", err = format!("{err:#}")));
+            return Err(1);
+        }
+    };
+    log_capture(CaptureKind::Screenshot, "scroll", Ok(&path));
+
+    output.success(
+        || t!("scroll.saved", path = path.display(), height = height),
+        json!({ "path": path, "height": height }),
+    );
+    notify(
+        t!("screenshot.saved_title"),
+        &path.display().to_string(),
+        Some(&path),
+    );
+    Ok(())
+}
+
+fn run_diff(request: DiffRequest, output: &CliOutput) -> Result<(), i32> {
+    let baseline = match load_baseline(&request.baseline) {
+        Ok(baseline) => baseline,
+        Err(err) => {
+            output.error(format!("{err:#}"));
+            return Err(2);
+        }
+    };
+
+    let bytes = match &request.source {
+        ScreenshotSource::Geometry(geometry) => {
+            take_geometry_screenshot_to_bytes(geometry, request.show_pointer, ImageFormat::Png)
+        }
+        ScreenshotSource::Target(target) => {
+            take_screenshot_to_bytes(target.clone(), request.show_pointer, ImageFormat::Png)
+        }
+        ScreenshotSource::Window(_) => unreachable!("diff only accepts region targets"),
+    };
+    let report = bytes.map_err(anyhow::Error::from).and_then(|bytes| {
+        let current = load_png_bytes(&bytes)?;
+        compare_images(&baseline, &current, request.diff_output.as_deref())
+    });
+    let report = match report {
+        Ok(report) => report,
+        Err(err) => {
+            output.error(t!("diff.failed", err = format!("{err:#}")));
+            return Err(2);
+        }
+    };
+
+    let ratio = report.ratio();
+    let passed = ratio <= request.threshold;
+    output.success(
+        || {
+            let mut message = if report.size_mismatch {
+                t!("diff.size_mismatch").to_string()
+            } else {
+                t!(
+                    "diff.summary",
+                    differing = report.differing_pixels,
+                    total = report.total_pixels,
+                    ratio = format!("{:.4}", ratio * 100.0),
+                    threshold = format!("{:.4}", request.threshold * 100.0)
+                )
+            };
+            message.push('\n');
+            message.push_str(if passed {
+                t!("diff.result_match")
+            } else {
+                t!("diff.result_differ")
+            });
+            if let Some(path) = &request.diff_output
+                && !report.size_mismatch
+            {
+                message.push('\n');
+                message.push_str(&t!("diff.output", path = path.display()));
+            }
+            message
+        },
+        json!({
+            "passed": passed,
+            "ratio": ratio,
+            "differing_pixels": report.differing_pixels,
+            "total_pixels": report.total_pixels,
+            "size_mismatch": report.size_mismatch,
+            "diff_output": request.diff_output,
+        }),
+    );
+
+    if passed { Ok(()) } else { Err(1) }
+}
+
+fn run_history(limit: usize, output: &CliOutput) -> Result<(), i32> {
+    let entries = match recent_history(limit) {
+        Ok(entries) => entries,
+        Err(err) => {
+            output.error(t!("history.failed", err = format!("{err:#}")));
+            return Err(1);
+        }
+    };
+
+    output.success(
+        || {
+            if entries.is_empty() {
+                return t!("history.empty").to_string();
+            }
+            entries
+                .iter()
+                .map(|entry| {
+                    let field = |key: &str| entry.get(key).and_then(Value::as_str).unwrap_or("-");
+                    let outcome = if entry.get("success").and_then(Value::as_bool) == Some(true) {
+                        field("path")
+                    } else {
+                        field("error")
+                    };
+                    format!(
+                        "{}  {:<10}  {:<12}  {outcome}",
+                        field("timestamp"),
+                        field("kind"),
+                        field("target")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+        Value::Array(entries.clone()),
+    );
+    Ok(())
+}
+
+fn run_prune(dry_run: bool, output: &CliOutput) -> Result<(), i32> {
+    let config = config();
+    if config.max_age_days.is_none() && config.max_total_size_mb.is_none() {
+        output.error(t!("prune.not_configured").to_string());
+        return Err(2);
+    }
+
+    let removed = match prune_captures(None, dry_run) {
+        Ok(removed) => removed,
+        Err(err) => {
+            output.error(t!("prune.failed", err = err));
+            return Err(1);
+        }
+    };
+
+    output.success(
+        || {
+            if removed.is_empty() {
+                return t!("prune.nothing").to_string();
+            }
+            let header = if dry_run {
+                t!("prune.would_remove", count = removed.len())
+            } else {
+                t!("prune.removed", count = removed.len())
+            };
+            removed.iter().fold(header, |mut message, path| {
+                message.push_str(&format!("\n  {}", path.display()));
+                message
+            })
+        },
+        json!({ "dry_run": dry_run, "removed": removed }),
+    );
+    Ok(())
+}
+
+fn run_check_deps(output: &CliOutput) -> Result<(), i32> {
+    let report = check_dependencies();
+    let tools: Vec<Value> = TOOLS
+        .iter()
+        .map(|tool| {
+            let installed = report.is_installed(tool.name);
+            json!({
+                "name": tool.name,
+                "purpose": tool.purpose,
+                "essential": tool.essential,
+                "installed": installed,
+                "install_hint": if installed { None } else { install_hint_for(tool.name) },
+            })
+        })
+        .collect();
+    let ok = report.missing_essential().next().is_none();
+
+    output.success(
+        || {
+            let mut lines: Vec<String> = TOOLS
+                .iter()
+                .map(|tool| {
+                    let status = match (report.is_installed(tool.name), tool.essential) {
+                        (true, _) => t!("deps.installed"),
+                        (false, true) => t!("deps.missing"),
+                        (false, false) => t!("deps.missing_optional"),
+                    };
+                    format!("{:<12} {status:<8} {}", tool.name, tool.purpose)
+                })
+                .collect();
+            if let Some(summary) = report.summary() {
+                lines.push(String::new());
+                lines.push(summary);
+            }
+            lines.join("\n")
+        },
+        json!({ "ok": ok, "tools": tools }),
+    );
+
+    if ok { Ok(()) } else { Err(1) }
+}
+
+fn run_version(output: &CliOutput) {
+    let version = env!("CARGO_PKG_VERSION");
+    let tools: Vec<(&str, Option<String>)> = VERSIONED_TOOLS
+        .iter()
+        .map(|tool| (*tool, tool_version(tool)))
+        .collect();
+
+    let json_tools: serde_json::Map<String, Value> = tools
+        .iter()
+        .map(|(tool, version)| (tool.to_string(), json!(version)))
+        .collect();
+    output.success(
+        || {
+            let mut lines = vec![format!("ncaptura {version}")];
+            lines.extend(tools.iter().map(|(tool, version)| {
+                format!(
+                    "{tool}: {}",
+                    version.as_deref().unwrap_or(t!("version.not_detected"))
+                )
+            }));
+            lines.join("\n")
+        },
+        json!({ "version": version, "tools": json_tools }),
+    );
+}
+
+fn run_msg(command: &str, output: &CliOutput) -> Result<(), i32> {
+    let reply = match send_command(command) {
+        Ok(reply) => reply,
+        Err(err) => {
+            output.error(format!("{err:#}"));
+            return Err(2);
+        }
+    };
+
+    if let Some(error) = reply.get("error").and_then(Value::as_str) {
+        output.error(error.to_string());
+        return Err(1);
+    }
+    if reply.get("cancelled").is_some() {
+        output.cancelled();
+        return Ok(());
+    }
+
+    let path = reply
+        .get("path")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    output.success(|| path.unwrap_or_else(|| "OK".to_string()), reply);
+    Ok(())
+}
+
+#[cfg(feature = "portal")]
+fn run_portal(output: &CliOutput) -> Result<(), i32> {
+    crate::portal::run_portal_service().map_err(|err| {
+        output.error(t!("portal.failed", err = format!("{err:#}")));
+        1
+    })
+}
+
+#[cfg(not(feature = "portal"))]
+fn run_portal(output: &CliOutput) -> Result<(), i32> {
+    output.error(t!("portal.disabled").to_string());
+    Err(2)
+}
+
+fn run_record_hud(output: &CliOutput) -> Result<(), i32> {
+    let state = match current_cli_recording_state() {
+        Ok(state) => state,
+        Err(_) => {
+            output.error(t!("record.no_cli_recording").to_string());
+            return Err(1);
+        }
+    };
+    if !state.is_live() {
+        output.error(t!("record.hud_process_exited", pid = state.pid));
+        return Err(1);
+    }
+
+    output.success(
+        || t!("record.hud_shown", path = state.output_path.display()),
+        json!({ "path": state.output_path, "pid": state.pid }),
+    );
+    run_cli_recording_hud(state, RecordingOverlays::default(), config().hud_position);
+    Ok(())
+}
+
+fn run_record_stop(thumbnail: Option<ThumbnailRequest>, output: &CliOutput) -> Result<(), i32> {
+    let path = match stop_recording_detached() {
+        Ok(path) => path,
+        Err(err) => {
+            output.error(t!("record.stop_failed", err = err));
+            return Err(1);
+        }
+    };
+    let media = probe_media(&path).ok();
+    let thumbnail = thumbnail.and_then(|request| {
+        let frame = match extract_frame(&path, request.at) {
+            Ok(frame) => frame,
+            Err(err) => {
+                eprintln!("{}", t!("record.thumbnail_failed", err = err));
+                return None;
+            }
+        };
+        let copied = request.copy
+            && match copy_image_to_clipboard(&frame) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("{}", t!("grab.copy_failed", err = err));
+                    false
+                }
+            };
+        Some((frame, copied))
+    });
+
+    output.success(
+        || {
+            let mut message = t!("record.stopped", path = path.display());
+            if let Some(media) = &media {
+                message.push('\n');
+                message.push_str(&t!("record.summary", info = media));
+            }
+            if let Some((frame, copied)) = &thumbnail {
+                message.push('\n');
+                message.push_str(&t!("record.thumbnail_saved", path = frame.display()));
+                if *copied {
+                    message.push('\n');
+                    message.push_str(t!("screenshot.copied"));
+                }
+            }
+            message
+        },
+        json!({
+            "path": path,
+            "media": media.as_ref().map(|media| json!({
+                "duration_secs": media.duration.map(|duration| duration.as_secs_f64()),
+                "width": media.width,
+                "height": media.height,
+                "fps": media.fps,
+                "size": media.size,
+            })),
+            "thumbnail": thumbnail.as_ref().map(|(frame, copied)| json!({
+                "path": frame,
+                "copied": copied,
+            })),
+        }),
+    );
+    notify(
+        t!("record.stopped_title"),
+        &path.display().to_string(),
+        None,
+    );
+    Ok(())
+}
+
+fn run_record_status(output: &CliOutput) -> Result<(), i32> {
+    let Ok(state) = current_cli_recording_state() else {
+        output.success(
+            || t!("record.no_cli_recording").to_string(),
+            json!({ "recording": false }),
+        );
+        return Err(3);
+    };
+
+    let alive = state.is_live();
+    let started_at = DateTime::<Local>::from(state.started_at);
+    let elapsed_secs = state.recorded_duration().as_secs();
+    output.success(
+        || {
+            t!(
+                "record.status",
+                state = if alive {
+                    t!("record.status_recording")
+                } else {
+                    t!("record.status_exited")
+                },
+                pid = state.pid,
+                target = state
+                    .target
+                    .as_deref()
+                    .unwrap_or(t!("record.status_unknown_target")),
+                path = state.output_path.display(),
+                started_at = started_at.format("%Y-%m-%d %H:%M:%S"),
+                elapsed = format!(
+                    "{:02}:{:02}:{:02}",
+                    elapsed_secs / 3600,
+                    (elapsed_secs % 3600) / 60,
+                    elapsed_secs % 60
+                ),
+                alive = if alive {
+                    t!("common.yes")
+                } else {
+                    t!("common.no")
+                }
+            )
+        },
+        json!({
+            "recording": alive,
+            "pid": state.pid,
+            "path": state.output_path,
+            "alive": alive,
+            "paused": state.paused,
+            "target": state.target,
+            "started_at": started_at.to_rfc3339(),
+            "elapsed_secs": elapsed_secs,
+            "max_duration_secs": state.max_duration.map(|duration| duration.as_secs()),
+        }),
+    );
+
+    if alive { Ok(()) } else { Err(3) }
+}
+
+fn run_pin(output: &CliOutput) -> Result<(), i32> {
+    let captured = pick_region_geometry().and_then(|geometry| {
+        let bytes = take_geometry_screenshot_to_bytes(&geometry, false, ImageFormat::Png)?;
+        Ok((geometry, bytes))
+    });
+    let (geometry, bytes) = match captured {
+        Ok(captured) => captured,
+        Err(CaptureError::Cancelled) => {
+            output.cancelled();
+            return Ok(());
+        }
+        Err(err) => {
+            output.error(t!("pin.failed", err = err));
+            return Err(1);
+        }
+    };
+    let pixbuf = match load_png_bytes(&bytes) {
+        Ok(pixbuf) => pixbuf,
+        Err(err) => {
+            output.error(t!("pin.failed", err = format!("{err:#}")));
+            return Err(1);
+        }
+    };
+
+    output.success(
+        || t!("pin.shown", geometry = geometry),
+        json!({ "geometry": geometry }),
+    );
+    run_pin_window(pixbuf);
+    Ok(())
+}
+
+fn run_share(source: ShareSource, output: &CliOutput) -> Result<(), i32> {
+    let (payload, timeout) = match source {
+        ShareSource::ClipboardText => match read_clipboard_text() {
+            Ok(text) => (text, None),
+            Err(err) => {
+                output.error(t!("share.clipboard_failed", err = err));
+                return Err(1);
+            }
+        },
+        ShareSource::Capture(target) => {
+            let path = match take_screenshot_with_clipboard(target, false, false) {
+                Ok(path) => path,
+                Err(CaptureError::Cancelled) => {
+                    output.cancelled();
+                    return Ok(());
+                }
+                Err(err) => {
+                    output.error(t!("screenshot.failed", err = err));
+                    return Err(1);
+                }
+            };
+            let timeout = config().share_timeout();
+            match serve_file(&path, timeout) {
+                Ok(server) => {
+                    output.success(
+                        || {
+                            t!(
+                                "share.serving",
+                                path = path.display(),
+                                seconds = timeout.as_secs(),
+                                url = server.url
+                            )
+                        },
+                        json!({
+                            "path": path,
+                            "url": server.url,
+                            "expires_in_secs": timeout.as_secs(),
+                        }),
+                    );
+                    (server.url, Some(timeout))
+                }
+                Err(err) => {
+                    output.error(t!("share.server_failed", err = err));
+                    return Err(1);
+                }
+            }
+        }
+    };
+
+    let qr_path = match render_qr_code(&payload) {
+        Ok(path) => path,
+        Err(err) => {
+            output.error(err.to_string());
+            return Err(1);
+        }
+    };
+
+    run_share_window(qr_path.clone(), payload, timeout);
+    let _ = fs::remove_file(&qr_path);
+    Ok(())
+}
+
 fn parse_cli_command(args: &[String]) -> Result<CliCommand, String> {
+    if args.is_empty() {
+        return Err(t!("cli.missing_command").to_string());
+    }
+
     if args[0] == "help" || args[0] == "--help" || args[0] == "-h" {
         return Ok(CliCommand::Help);
     }
 
+    if args[0] == "--portal" {
+        if args.len() != 1 {
+            return Err("--portal 不接受额外参数".to_string());
+        }
+        return Ok(CliCommand::Portal);
+    }
+
+    if args[0] == "--version" || args[0] == "-V" {
+        if args.len() != 1 {
+            return Err("--version 不接受额外参数".to_string());
+        }
+        return Ok(CliCommand::Version);
+    }
+
     if args[0] == "screenshot" {
-        if args.len() != 2 {
+        if args.len() < 2 {
             return Err("screenshot 命令格式错误".to_string());
         }
 
+        if args[1] == "window" {
+            let Some(window_id) = args.get(2) else {
+                return Err(
+                    "screenshot window 需要提供窗口 id，可通过 `ncaptura list-windows` 查询"
+                        .to_string(),
+                );
+            };
+            let window_id: u64 = window_id.parse().map_err(|_| {
+                format!("窗口 id 无效: \"{window_id}\"，应为非负整数，可通过 `ncaptura list-windows` 查询")
+            })?;
+            let flags = parse_screenshot_flags(&args[3..])?;
+            if flags.repeat_region {
+                return Err("--repeat-region 仅适用于 region 目标".to_string());
+            }
+            if flags.stdout || flags.format != ImageFormat::Png {
+                return Err("screenshot window 不支持 --stdout 与 --format".to_string());
+            }
+
+            return Ok(CliCommand::Screenshot {
+                source: ScreenshotSource::Window(window_id),
+                flags,
+            });
+        }
+
+        if args[1] == "workspace" {
+            let Some(workspace_id) = args.get(2) else {
+                return Err("screenshot workspace 需要提供工作区 id".to_string());
+            };
+            let workspace_id: u64 = workspace_id
+                .parse()
+                .map_err(|_| format!("工作区 id 无效: \"{workspace_id}\"，应为非负整数"))?;
+            let flags = parse_screenshot_flags(&args[3..])?;
+            if flags.repeat_region {
+                return Err("--repeat-region 仅适用于 region 目标".to_string());
+            }
+            if flags.padding.is_some() {
+                return Err("--padding 仅适用于 window 目标".to_string());
+            }
+
+            return Ok(CliCommand::Screenshot {
+                source: ScreenshotSource::Target(CaptureTarget::Workspace(workspace_id)),
+                flags,
+            });
+        }
+
+        if args[1] == "geometry" {
+            let Some(geometry) = args.get(2) else {
+                return Err("screenshot geometry 需要提供 \"X,Y WxH\" 格式的区域".to_string());
+            };
+            let geometry = parse_geometry(geometry).map_err(|err| err.to_string())?;
+            let flags = parse_screenshot_flags(&args[3..])?;
+            if flags.repeat_region {
+                return Err("--repeat-region 仅适用于 region 目标".to_string());
+            }
+            if flags.padding.is_some() {
+                return Err("--padding 仅适用于 window 目标".to_string());
+            }
+
+            return Ok(CliCommand::Screenshot {
+                source: ScreenshotSource::Geometry(geometry),
+                flags,
+            });
+        }
+
         let target = parse_target(&args[1])?;
-        return Ok(CliCommand::Screenshot { target });
+        let flags = parse_screenshot_flags(&args[2..])?;
+        if flags.repeat_region && !matches!(target, CaptureTarget::Region) {
+            return Err("--repeat-region 仅适用于 region 目标".to_string());
+        }
+        if flags.padding.is_some() {
+            return Err("--padding 仅适用于 window 目标".to_string());
+        }
+
+        return Ok(CliCommand::Screenshot {
+            source: ScreenshotSource::Target(target),
+            flags,
+        });
     }
 
     if args[0] == "record" {
         if args.len() >= 2 && args[1] == "start" {
-            if args.len() < 3 || args.len() > 4 {
+            if args.len() < 3 {
                 return Err("record start 命令格式错误".to_string());
             }
 
             let target = parse_target(&args[2])?;
-            let audio = if args.len() == 4 {
-                if args[3] == "--audio" {
-                    true
-                } else {
-                    return Err("record start 仅支持 --audio 参数".to_string());
+            let mut show_hud = true;
+            let mut overlays = RecordingOverlays::default();
+            let mut hud_position = None;
+            let mut delay_seconds = 0;
+            let mut option_args = Vec::new();
+            let mut rest = args[3..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--no-hud" => show_hud = false,
+                    "--highlight-clicks" => overlays.highlight_clicks = true,
+                    "--show-keys" => overlays.show_keys = true,
+                    "--hud-position" => {
+                        let value = rest.next().ok_or("--hud-position 需要提供位置")?;
+                        hud_position = Some(HudPosition::from_name(value).ok_or_else(|| {
+                            format!(
+                                "--hud-position 无效: \"{value}\"，可选 top-left、top-right、bottom-left、bottom-right"
+                            )
+                        })?);
+                    }
+                    "--delay" => {
+                        let value = rest.next().ok_or("--delay 需要提供秒数")?;
+                        delay_seconds = value
+                            .parse()
+                            .map_err(|_| format!("--delay 无效: \"{value}\"，应为非负整数秒数"))?;
+                    }
+                    _ => option_args.push(arg.clone()),
                 }
-            } else {
-                false
-            };
+            }
+            let options = parse_recording_options(&option_args)?;
+            if !show_hud && options.max_duration.is_some() {
+                return Err("--max-duration 依赖录制小窗计时，不能与 --no-hud 同时使用".to_string());
+            }
+            if !show_hud && overlays != RecordingOverlays::default() {
+                return Err(
+                    "--highlight-clicks 与 --show-keys 由录制小窗显示，不能与 --no-hud 同时使用"
+                        .to_string(),
+                );
+            }
+            return Ok(CliCommand::RecordStart {
+                target,
+                options,
+                show_hud,
+                overlays,
+                hud_position,
+                delay_seconds,
+            });
+        }
 
-            return Ok(CliCommand::RecordStart { target, audio });
+        if args.len() == 2 && args[1] == "recover" {
+            return Ok(CliCommand::RecordRecover);
         }
 
-        if args.len() == 2 && args[1] == "stop" {
-            return Ok(CliCommand::RecordStop);
+        if args.len() == 2 && args[1] == "hud" {
+            return Ok(CliCommand::RecordHud);
+        }
+
+        if args.len() >= 2 && args[1] == "stop" {
+            return parse_record_stop_flags(&args[2..]);
+        }
+
+        if args.len() == 2 && args[1] == "status" {
+            return Ok(CliCommand::RecordStatus);
         }
 
         return Err("record 命令格式错误".to_string());
     }
 
-    Err("未知命令".to_string())
+    if args[0] == "diff" {
+        return parse_diff_command(&args[1..]).map(CliCommand::Diff);
+    }
+
+    if args[0] == "scroll-capture" {
+        return parse_scroll_capture_command(&args[1..]);
+    }
+
+    if args[0] == "daemon" {
+        if args.len() != 1 {
+            return Err("daemon 不接受额外参数".to_string());
+        }
+        return Ok(CliCommand::Daemon);
+    }
+
+    if args[0] == "msg" {
+        if args.len() < 2 {
+            return Err("msg 需要提供发送给守护进程的命令".to_string());
+        }
+        return Ok(CliCommand::Msg(args[1..].join(" ")));
+    }
+
+    if args[0] == "history" {
+        let limit = match args {
+            [_] => DEFAULT_HISTORY_ENTRIES,
+            [_, count] => count
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| format!("无效的条目数: {count}"))?,
+            _ => return Err("history 最多接受一个条目数参数".to_string()),
+        };
+        return Ok(CliCommand::History(limit));
+    }
+
+    if args[0] == "prune" {
+        return match &args[1..] {
+            [] => Ok(CliCommand::Prune { dry_run: false }),
+            [flag] if flag == "--dry-run" => Ok(CliCommand::Prune { dry_run: true }),
+            _ => Err("prune 只接受 --dry-run 参数".to_string()),
+        };
+    }
+
+    if args[0] == "check-deps" {
+        if args.len() != 1 {
+            return Err("check-deps 不接受额外参数".to_string());
+        }
+        return Ok(CliCommand::CheckDeps);
+    }
+
+    if args[0] == "list-windows" {
+        if args.len() != 1 {
+            return Err("list-windows 不接受额外参数".to_string());
+        }
+        return Ok(CliCommand::ListWindows);
+    }
+
+    if args[0] == "completions" {
+        let [_, shell] = args else {
+            return Err("completions 需要提供 shell 类型（bash、zsh 或 fish）".to_string());
+        };
+        let shell = Shell::from_name(shell)
+            .ok_or_else(|| format!("不支持的 shell: {shell}，可选 bash、zsh、fish"))?;
+        return Ok(CliCommand::Completions(shell));
+    }
+
+    if args[0] == "grab" {
+        let [_, target] = args else {
+            return Err("grab 需要提供截图目标（region、fullscreen 或 all-outputs）".to_string());
+        };
+        return Ok(CliCommand::Grab(parse_target(target)?));
+    }
+
+    if args[0] == "pin" {
+        if args.len() != 2 || args[1] != "region" {
+            return Err("pin 目前只支持 region 目标".to_string());
+        }
+        return Ok(CliCommand::Pin);
+    }
+
+    if args[0] == "share" {
+        if args.len() != 2 {
+            return Err("share 命令格式错误".to_string());
+        }
+
+        if args[1] == "text" {
+            return Ok(CliCommand::Share(ShareSource::ClipboardText));
+        }
+
+        let target = parse_target(&args[1])?;
+        return Ok(CliCommand::Share(ShareSource::Capture(target)));
+    }
+
+    Err(t!("cli.unknown_command").to_string())
+}
+
+fn parse_recording_options(args: &[String]) -> Result<RecordingOptions, String> {
+    let mut options = RecordingOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--audio" => options.audio = true,
+            "--no-cursor" => options.show_cursor = false,
+            "--normalize-audio" => options.normalize_audio = true,
+            "--max-duration" => {
+                let value = args.next().ok_or("--max-duration 需要提供秒数")?;
+                let seconds = value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|seconds| *seconds > 0)
+                    .ok_or_else(|| format!("录制时长上限无效: {value}，应为正整数秒数"))?;
+                options.max_duration = Some(Duration::from_secs(seconds));
+            }
+            "--container" => {
+                let value = args.next().ok_or("--container 需要提供 mkv、mp4 或 webm")?;
+                options.container = Container::from_name(value)
+                    .ok_or_else(|| format!("不支持的容器格式: {value}，可选 mkv、mp4、webm"))?;
+            }
+            "--quality" => {
+                let value = args
+                    .next()
+                    .ok_or("--quality 需要提供 low、medium 或 high")?;
+                options.quality =
+                    Some(QualityPreset::from_name(value).ok_or_else(|| {
+                        format!("不支持的质量预设: {value}，可选 low、medium、high")
+                    })?);
+            }
+            "--codec" => {
+                let value = args
+                    .next()
+                    .ok_or("--codec 需要提供 wf-recorder 编码器名称")?;
+                options.video_codec = Some(value.clone());
+            }
+            _ => return Err(format!("record start 不支持的参数: {arg}")),
+        }
+    }
+
+    if let Some(codec) = &options.video_codec {
+        options
+            .container
+            .check_video_codec(codec)
+            .map_err(|err| err.to_string())?;
+    }
+    options.quality_params().map_err(|err| err.to_string())?;
+
+    Ok(options)
+}
+
+fn parse_screenshot_flags(args: &[String]) -> Result<ScreenshotFlags, String> {
+    let mut flags = ScreenshotFlags::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pointer" => flags.show_pointer = true,
+            "--repeat-region" => flags.repeat_region = true,
+            "--copy" => flags.copy = true,
+            "--copy-only" => flags.copy_only = true,
+            "--open" => flags.open = true,
+            "--stdout" | "-" => flags.stdout = true,
+            "--padding" => {
+                let value = args.next().ok_or("--padding 需要提供像素数")?;
+                let padding = value
+                    .parse()
+                    .map_err(|_| format!("--padding 无效: \"{value}\"，应为非负整数"))?;
+                flags.padding = Some(padding);
+            }
+            "--format" => {
+                let Some(name) = args.next() else {
+                    return Err("--format 需要提供格式（png 或 ppm）".to_string());
+                };
+                flags.format = ImageFormat::from_name(name)
+                    .ok_or_else(|| format!("不支持的图片格式: {name}（可选 png、ppm）"))?;
+            }
+            _ => return Err(format!("screenshot 不支持的参数: {arg}")),
+        }
+    }
+
+    if flags.stdout && (flags.copy || flags.copy_only) {
+        return Err("--stdout 不能与 --copy / --copy-only 同时使用".to_string());
+    }
+    if flags.open && (flags.stdout || flags.copy_only) {
+        return Err("--open 不能与 --stdout / --copy-only 同时使用".to_string());
+    }
+    if flags.format != ImageFormat::Png && (flags.copy || flags.copy_only) {
+        return Err("仅 PNG 格式支持 --copy / --copy-only".to_string());
+    }
+
+    Ok(flags)
+}
+
+fn parse_record_stop_flags(args: &[String]) -> Result<CliCommand, String> {
+    let mut thumbnail = false;
+    let mut at = None;
+    let mut copy = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--thumbnail" => thumbnail = true,
+            "--thumbnail-at" => {
+                let value = args.next().ok_or("--thumbnail-at 需要提供秒数")?;
+                let seconds = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| format!("--thumbnail-at 无效: \"{value}\"，应为非负秒数"))?;
+                thumbnail = true;
+                at = Some(Duration::from_secs_f64(seconds));
+            }
+            "--copy" => copy = true,
+            _ => return Err(format!("record stop 不支持的参数: {arg}")),
+        }
+    }
+
+    if copy && !thumbnail {
+        return Err("--copy 需要与 --thumbnail 或 --thumbnail-at 一起使用".to_string());
+    }
+
+    Ok(CliCommand::RecordStop {
+        thumbnail: thumbnail.then_some(ThumbnailRequest { at, copy }),
+    })
+}
+
+fn parse_diff_command(args: &[String]) -> Result<DiffRequest, String> {
+    let mut baseline = None;
+    let mut source = None;
+    let mut threshold = 0.0;
+    let mut diff_output = None;
+    let mut show_pointer = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => {
+                let path = args.next().ok_or("--baseline 需要提供基准图路径")?;
+                baseline = Some(PathBuf::from(path));
+            }
+            "--threshold" => {
+                let value = args
+                    .next()
+                    .ok_or("--threshold 需要提供 0 到 1 之间的比例")?;
+                threshold = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| (0.0..=1.0).contains(value))
+                    .ok_or_else(|| format!("阈值无效: {value}，应为 0 到 1 之间的比例"))?;
+            }
+            "--diff-output" => {
+                let path = args.next().ok_or("--diff-output 需要提供输出路径")?;
+                diff_output = Some(PathBuf::from(path));
+            }
+            "--pointer" => show_pointer = true,
+            "geometry" if source.is_none() => {
+                let geometry = args
+                    .next()
+                    .ok_or("diff geometry 需要提供 \"X,Y WxH\" 格式的区域")?;
+                let geometry = parse_geometry(geometry).map_err(|err| err.to_string())?;
+                source = Some(ScreenshotSource::Geometry(geometry));
+            }
+            target if source.is_none() && !target.starts_with("--") => {
+                source = Some(ScreenshotSource::Target(parse_target(target)?));
+            }
+            _ => return Err(format!("diff 不支持的参数: {arg}")),
+        }
+    }
+
+    Ok(DiffRequest {
+        baseline: baseline.ok_or("diff 需要通过 --baseline 指定基准图")?,
+        source: source.ok_or("diff 需要指定截图目标（region、fullscreen 或 geometry）")?,
+        threshold,
+        diff_output,
+        show_pointer,
+    })
+}
+
+fn parse_scroll_capture_command(args: &[String]) -> Result<CliCommand, String> {
+    let mut geometry = None;
+    let mut steps = DEFAULT_SCROLL_STEPS;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => {
+                let value = args.next().ok_or("--steps 需要提供滚动次数")?;
+                steps = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|steps| *steps > 0)
+                    .ok_or_else(|| format!("滚动次数无效: {value}，应为正整数"))?;
+            }
+            "geometry" if geometry.is_none() => {
+                let value = args
+                    .next()
+                    .ok_or("scroll-capture geometry 需要提供 \"X,Y WxH\" 格式的区域")?;
+                geometry = Some(parse_geometry(value).map_err(|err| err.to_string())?);
+            }
+            "region" if geometry.is_none() => {}
+            _ => return Err(format!("scroll-capture 不支持的参数: {arg}")),
+        }
+    }
+
+    Ok(CliCommand::ScrollCapture { geometry, steps })
 }
 
 fn parse_target(input: &str) -> Result<CaptureTarget, String> {
     match input {
         "region" => Ok(CaptureTarget::Region),
         "fullscreen" => Ok(CaptureTarget::Fullscreen),
+        "all-outputs" => Ok(CaptureTarget::AllOutputs),
         _ => Err(format!("不支持的目标类型: {input}")),
     }
 }
 
-fn cli_usage() -> &'static str {
-    "NCaptura CLI
-
-用法:
-  ncaptura                      启动图形界面
-  ncaptura screenshot region
-  ncaptura screenshot fullscreen
-  ncaptura record start region [--audio]
-  ncaptura record start fullscreen [--audio]
-  ncaptura record stop
-  ncaptura help
+fn cli_usage() -> String {
+    let keybinds = RECOMMENDED_KEYBINDS
+        .iter()
+        .map(|keybind| format!("\n  {}", keybind.niri_snippet()))
+        .collect::<String>();
 
-niri 快捷键示例:
-  Mod+Shift+S    { spawn \"ncaptura\" \"screenshot\" \"region\"; }
-  Mod+Shift+F    { spawn \"ncaptura\" \"screenshot\" \"fullscreen\"; }
-  Mod+Shift+R    { spawn \"ncaptura\" \"record\" \"start\" \"region\"; }
-  Mod+Shift+A    { spawn \"ncaptura\" \"record\" \"start\" \"region\" \"--audio\"; }
-  Mod+Shift+E    { spawn \"ncaptura\" \"record\" \"stop\"; }"
+    t!("cli.usage", keybinds = keybinds)
 }
 
 enum CliCommand {
-    Screenshot { target: CaptureTarget },
-    RecordStart { target: CaptureTarget, audio: bool },
-    RecordStop,
+    Screenshot {
+        source: ScreenshotSource,
+        flags: ScreenshotFlags,
+    },
+    Grab(CaptureTarget),
+    RecordStart {
+        target: CaptureTarget,
+        options: RecordingOptions,
+        show_hud: bool,
+        overlays: RecordingOverlays,
+        hud_position: Option<HudPosition>,
+        delay_seconds: u32,
+    },
+    RecordStop {
+        thumbnail: Option<ThumbnailRequest>,
+    },
+    RecordStatus,
+    RecordHud,
+    RecordRecover,
+    Diff(DiffRequest),
+    ScrollCapture {
+        geometry: Option<String>,
+        steps: u32,
+    },
+    ListWindows,
+    Share(ShareSource),
+    Pin,
+    CheckDeps,
+    Version,
+    Completions(Shell),
+    Portal,
+    Daemon,
+    Msg(String),
+    History(usize),
+    Prune {
+        dry_run: bool,
+    },
     Help,
 }
+
+struct ThumbnailRequest {
+    at: Option<Duration>,
+    copy: bool,
+}
+
+struct DiffRequest {
+    baseline: PathBuf,
+    source: ScreenshotSource,
+    threshold: f64,
+    diff_output: Option<PathBuf>,
+    show_pointer: bool,
+}
+
+enum ShareSource {
+    Capture(CaptureTarget),
+    ClipboardText,
+}
+
+enum ScreenshotSource {
+    Target(CaptureTarget),
+    Geometry(String),
+    Window(u64),
+}
+
+impl ScreenshotSource {
+    fn label(&self) -> String {
+        match self {
+            ScreenshotSource::Target(target) => target.slug().to_string(),
+            ScreenshotSource::Geometry(geometry) => format!("geometry {geometry}"),
+            ScreenshotSource::Window(window_id) => format!("window {window_id}"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ScreenshotFlags {
+    show_pointer: bool,
+    repeat_region: bool,
+    copy: bool,
+    copy_only: bool,
+    open: bool,
+    stdout: bool,
+    padding: Option<u32>,
+    format: ImageFormat,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_cli_command(&args)
+    }
+
+    #[test]
+    fn bare_record_is_a_format_error() {
+        assert_eq!(
+            parse(&["record"]).err().as_deref(),
+            Some("record 命令格式错误")
+        );
+    }
+
+    #[test]
+    fn record_stop_parses_thumbnail_flags() {
+        assert!(matches!(
+            parse(&["record", "stop"]),
+            Ok(CliCommand::RecordStop { thumbnail: None })
+        ));
+        assert!(matches!(
+            parse(&["record", "stop", "--thumbnail-at", "2.5", "--copy"]),
+            Ok(CliCommand::RecordStop {
+                thumbnail: Some(ThumbnailRequest { at: Some(at), copy: true })
+            }) if at == Duration::from_millis(2500)
+        ));
+        assert!(parse(&["record", "stop", "--copy"]).is_err());
+        assert!(parse(&["record", "stop", "--thumbnail-at", "-1"]).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use std::fmt::Write;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+struct CommandSpec {
+    path: &'static str,
+    words: &'static [&'static str],
+    flags: &'static [&'static str],
+}
+
+const GLOBAL_FLAGS: &[&str] = &[
+    "--quiet",
+    "-q",
+    "--json",
+    "--verbose",
+    "--lang",
+    "--version",
+    "-V",
+    "--portal",
+];
+const SCREENSHOT_FLAGS: &[&str] = &[
+    "--pointer",
+    "--repeat-region",
+    "--copy",
+    "--copy-only",
+    "--open",
+    "--stdout",
+    "--padding",
+    "--format",
+];
+const RECORD_START_FLAGS: &[&str] = &[
+    "--audio",
+    "--normalize-audio",
+    "--max-duration",
+    "--container",
+    "--codec",
+    "--quality",
+    "--delay",
+    "--no-cursor",
+    "--no-hud",
+    "--highlight-clicks",
+    "--show-keys",
+    "--hud-position",
+];
+const RECORD_STOP_FLAGS: &[&str] = &["--thumbnail", "--thumbnail-at", "--copy"];
+const DIFF_FLAGS: &[&str] = &["--baseline", "--threshold", "--diff-output", "--pointer"];
+const SCROLL_CAPTURE_FLAGS: &[&str] = &["--steps"];
+const PRUNE_FLAGS: &[&str] = &["--dry-run"];
+
+const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        path: "",
+        words: &[
+            "screenshot",
+            "grab",
+            "record",
+            "diff",
+            "scroll-capture",
+            "list-windows",
+            "daemon",
+            "msg",
+            "history",
+            "prune",
+            "pin",
+            "share",
+            "check-deps",
+            "completions",
+            "help",
+        ],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "screenshot",
+        words: &[
+            "region",
+            "fullscreen",
+            "all-outputs",
+            "geometry",
+            "window",
+            "workspace",
+        ],
+        flags: SCREENSHOT_FLAGS,
+    },
+    CommandSpec {
+        path: "grab",
+        words: &["region", "fullscreen", "all-outputs"],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "record",
+        words: &["start", "stop", "recover", "hud", "status"],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "record start",
+        words: &["region", "fullscreen", "all-outputs"],
+        flags: RECORD_START_FLAGS,
+    },
+    CommandSpec {
+        path: "record stop",
+        words: &[],
+        flags: RECORD_STOP_FLAGS,
+    },
+    CommandSpec {
+        path: "diff",
+        words: &["region", "fullscreen", "geometry"],
+        flags: DIFF_FLAGS,
+    },
+    CommandSpec {
+        path: "scroll-capture",
+        words: &["region", "geometry"],
+        flags: SCROLL_CAPTURE_FLAGS,
+    },
+    CommandSpec {
+        path: "prune",
+        words: &[],
+        flags: PRUNE_FLAGS,
+    },
+    CommandSpec {
+        path: "pin",
+        words: &["region"],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "share",
+        words: &["region", "fullscreen", "all-outputs", "text"],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "msg",
+        words: &["screenshot", "record", "status", "quit"],
+        flags: &[],
+    },
+    CommandSpec {
+        path: "completions",
+        words: &["bash", "zsh", "fish"],
+        flags: &[],
+    },
+];
+
+pub(crate) fn completion_script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_script(),
+        Shell::Zsh => zsh_script(),
+        Shell::Fish => fish_script(),
+    }
+}
+
+fn known_paths() -> String {
+    COMMANDS
+        .iter()
+        .filter(|spec| !spec.path.is_empty())
+        .map(|spec| format!("\"{}\"", spec.path))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn spec_flags(spec: &CommandSpec) -> String {
+    spec.flags
+        .iter()
+        .chain(GLOBAL_FLAGS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash_script() -> String {
+    let mut cases = String::new();
+    for spec in COMMANDS {
+        let _ = write!(
+            cases,
+            "        \"{}\")\n            candidates=\"{}\"\n            flags=\"{}\"\n            ;;\n",
+            spec.path,
+            spec.words.join(" "),
+            spec_flags(spec)
+        );
+    }
+
+    format!(
+        r#"_ncaptura() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local path="" leaf=0 word candidate known_path i
+    local known=({paths})
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        word="${{COMP_WORDS[i]}}"
+        [[ "$word" == -* ]] && continue
+        candidate="${{path:+$path }}$word"
+        for known_path in "${{known[@]}}"; do
+            if [[ "$known_path" == "$candidate" ]]; then
+                path="$candidate"
+                continue 2
+            fi
+        done
+        leaf=1
+    done
+
+    local candidates="" flags=""
+    case "$path" in
+{cases}    esac
+    [[ $leaf -eq 1 ]] && candidates=""
+
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "$flags" -- "$cur"))
+    else
+        COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+    fi
+}}
+complete -F _ncaptura ncaptura
+"#,
+        paths = known_paths(),
+    )
+}
+
+fn zsh_script() -> String {
+    let mut cases = String::new();
+    for spec in COMMANDS {
+        let _ = write!(
+            cases,
+            "        \"{}\")\n            candidates=({})\n            flags=({})\n            ;;\n",
+            spec.path,
+            spec.words.join(" "),
+            spec_flags(spec)
+        );
+    }
+
+    format!(
+        r#"#compdef ncaptura
+
+_ncaptura() {{
+    local command_path="" leaf=0 word candidate i
+    local -a known candidates flags
+    known=({paths})
+    for ((i = 2; i < CURRENT; i++)); do
+        word="${{words[i]}}"
+        [[ "$word" == -* ]] && continue
+        candidate="${{command_path:+$command_path }}$word"
+        if (( ${{known[(Ie)$candidate]}} )); then
+            command_path="$candidate"
+        else
+            leaf=1
+        fi
+    done
+
+    case "$command_path" in
+{cases}    esac
+    (( leaf )) && candidates=()
+
+    if [[ "$PREFIX" == -* ]]; then
+        compadd -- $flags
+    else
+        compadd -- $candidates
+    fi
+}}
+
+if [[ "${{zsh_eval_context[-1]}}" == loadautofunc ]]; then
+    _ncaptura "$@"
+else
+    compdef _ncaptura ncaptura
+fi
+"#,
+        paths = known_paths(),
+    )
+}
+
+fn fish_script() -> String {
+    let mut script = format!(
+        r#"set -g __ncaptura_paths {paths}
+
+function __ncaptura_path
+    set -l tokens (commandline -opc)
+    set -l command_path ""
+    set -l leaf 0
+    for token in $tokens[2..-1]
+        string match -q -- '-*' $token; and continue
+        set -l candidate (string trim -- "$command_path $token")
+        if contains -- $candidate $__ncaptura_paths
+            set command_path $candidate
+        else
+            set leaf 1
+        end
+    end
+    echo "$command_path:$leaf"
+end
+
+complete -c ncaptura -f
+"#,
+        paths = known_paths(),
+    );
+
+    for spec in COMMANDS {
+        let _ = writeln!(
+            script,
+            "complete -c ncaptura -n 'test (__ncaptura_path) = \"{}:0\"' -a '{}'",
+            spec.path,
+            spec.words.join(" ")
+        );
+        for flag in spec.flags.iter().chain(GLOBAL_FLAGS) {
+            let option = match flag.strip_prefix("--") {
+                Some(long) => format!("-l {long}"),
+                None => format!("-s {}", flag.trim_start_matches('-')),
+            };
+            let _ = writeln!(
+                script,
+                "complete -c ncaptura -n 'string match -q \"{}:*\" (__ncaptura_path)' {option}",
+                spec.path
+            );
+        }
+    }
+    script
+}
@@ -0,0 +1,336 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::capture::{WebcamConfig, WebcamPosition};
+use crate::ui::HudPosition;
+
+const CONFIG_FILE: &str = "config.json";
+const DEFAULT_WEBCAM_WIDTH: u32 = 320;
+pub const DEFAULT_INDICATOR_COLOR: &str = "#e53935";
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub niri_focus_settle_ms: u64,
+    pub notifications: bool,
+    pub share_timeout_secs: u64,
+    pub hide_window_before_capture: bool,
+    pub hide_window_settle_ms: u64,
+    pub window_padding: u32,
+    pub compositor_timeout_ms: u64,
+    pub grim_retries: u32,
+    pub slurp_background_color: Option<String>,
+    pub slurp_border_color: Option<String>,
+    pub slurp_selection_color: Option<String>,
+    pub slurp_border_weight: Option<u32>,
+    pub upload_endpoint: Option<String>,
+    pub upload_headers: Vec<(String, String)>,
+    pub upload_field: String,
+    pub upload_response_path: String,
+    pub normalize_audio: bool,
+    pub segment_pause_with_audio: bool,
+    pub webcam: Option<WebcamConfig>,
+    pub key_display_blocklist: Vec<String>,
+    pub hud_position: HudPosition,
+    pub hud_margin: i32,
+    pub hud_blink: bool,
+    pub hud_blink_interval_ms: u64,
+    pub hud_indicator_color: String,
+    pub snap_region_to_windows: bool,
+    pub history_max_bytes: u64,
+    pub max_age_days: Option<u64>,
+    pub max_total_size_mb: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            niri_focus_settle_ms: 100,
+            notifications: true,
+            share_timeout_secs: 120,
+            hide_window_before_capture: true,
+            hide_window_settle_ms: 150,
+            window_padding: 24,
+            compositor_timeout_ms: 3000,
+            grim_retries: 2,
+            slurp_background_color: None,
+            slurp_border_color: None,
+            slurp_selection_color: None,
+            slurp_border_weight: None,
+            upload_endpoint: None,
+            upload_headers: Vec::new(),
+            upload_field: "image".to_string(),
+            upload_response_path: "data.link".to_string(),
+            normalize_audio: false,
+            segment_pause_with_audio: true,
+            webcam: None,
+            hud_position: HudPosition::default(),
+            hud_margin: 12,
+            hud_blink: true,
+            hud_blink_interval_ms: 500,
+            hud_indicator_color: DEFAULT_INDICATOR_COLOR.to_string(),
+            key_display_blocklist: [
+                "keepassxc",
+                "bitwarden",
+                "1password",
+                "pinentry",
+                "password",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            snap_region_to_windows: false,
+            history_max_bytes: 1024 * 1024,
+            max_age_days: None,
+            max_total_size_mb: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn niri_focus_settle(&self) -> Duration {
+        Duration::from_millis(self.niri_focus_settle_ms)
+    }
+
+    pub fn hide_window_settle(&self) -> Duration {
+        Duration::from_millis(self.hide_window_settle_ms)
+    }
+
+    pub fn compositor_timeout(&self) -> Duration {
+        Duration::from_millis(self.compositor_timeout_ms.max(1))
+    }
+
+    pub fn hud_blink_interval(&self) -> Option<Duration> {
+        (self.hud_blink && self.hud_blink_interval_ms > 0)
+            .then(|| Duration::from_millis(self.hud_blink_interval_ms))
+    }
+
+    pub fn share_timeout(&self) -> Duration {
+        Duration::from_secs(self.share_timeout_secs.max(1))
+    }
+}
+
+pub fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(load_config)
+}
+
+fn load_config() -> Config {
+    let mut config = Config::default();
+    let Some(file_path) = config_file_path() else {
+        return config;
+    };
+    let Ok(data) = fs::read_to_string(&file_path) else {
+        return config;
+    };
+
+    let value: Value = match serde_json::from_str(&data) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("配置文件解析失败，将使用默认配置: {err}");
+            return config;
+        }
+    };
+
+    if let Some(ms) = value.get("niri_focus_settle_ms").and_then(Value::as_u64) {
+        config.niri_focus_settle_ms = ms;
+    }
+
+    if let Some(enabled) = value.get("notifications").and_then(Value::as_bool) {
+        config.notifications = enabled;
+    }
+
+    if let Some(secs) = value.get("share_timeout_secs").and_then(Value::as_u64) {
+        config.share_timeout_secs = secs;
+    }
+
+    if let Some(enabled) = value
+        .get("hide_window_before_capture")
+        .and_then(Value::as_bool)
+    {
+        config.hide_window_before_capture = enabled;
+    }
+
+    if let Some(ms) = value.get("hide_window_settle_ms").and_then(Value::as_u64) {
+        config.hide_window_settle_ms = ms;
+    }
+
+    if let Some(padding) = value
+        .get("window_padding")
+        .and_then(Value::as_u64)
+        .and_then(|padding| u32::try_from(padding).ok())
+    {
+        config.window_padding = padding;
+    }
+
+    if let Some(ms) = value.get("compositor_timeout_ms").and_then(Value::as_u64) {
+        config.compositor_timeout_ms = ms;
+    }
+
+    if let Some(retries) = value
+        .get("grim_retries")
+        .and_then(Value::as_u64)
+        .and_then(|retries| u32::try_from(retries).ok())
+    {
+        config.grim_retries = retries;
+    }
+
+    if let Some(color) = value.get("slurp_background_color").and_then(Value::as_str) {
+        config.slurp_background_color = Some(color.to_string());
+    }
+
+    if let Some(color) = value.get("slurp_border_color").and_then(Value::as_str) {
+        config.slurp_border_color = Some(color.to_string());
+    }
+
+    if let Some(color) = value.get("slurp_selection_color").and_then(Value::as_str) {
+        config.slurp_selection_color = Some(color.to_string());
+    }
+
+    if let Some(weight) = value
+        .get("slurp_border_weight")
+        .and_then(Value::as_u64)
+        .and_then(|weight| u32::try_from(weight).ok())
+    {
+        config.slurp_border_weight = Some(weight);
+    }
+
+    if let Some(endpoint) = value.get("upload_endpoint").and_then(Value::as_str) {
+        config.upload_endpoint = Some(endpoint.to_string());
+    }
+
+    if let Some(headers) = value.get("upload_headers").and_then(Value::as_object) {
+        config.upload_headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+            .collect();
+    }
+
+    if let Some(field) = value.get("upload_field").and_then(Value::as_str) {
+        config.upload_field = field.to_string();
+    }
+
+    if let Some(path) = value.get("upload_response_path").and_then(Value::as_str) {
+        config.upload_response_path = path.to_string();
+    }
+
+    if let Some(enabled) = value.get("normalize_audio").and_then(Value::as_bool) {
+        config.normalize_audio = enabled;
+    }
+
+    if let Some(enabled) = value
+        .get("segment_pause_with_audio")
+        .and_then(Value::as_bool)
+    {
+        config.segment_pause_with_audio = enabled;
+    }
+
+    if let Some(enabled) = value.get("snap_region_to_windows").and_then(Value::as_bool) {
+        config.snap_region_to_windows = enabled;
+    }
+
+    if let Some(bytes) = value
+        .get("history_max_bytes")
+        .and_then(Value::as_u64)
+        .filter(|bytes| *bytes > 0)
+    {
+        config.history_max_bytes = bytes;
+    }
+
+    if let Some(days) = value
+        .get("max_age_days")
+        .and_then(Value::as_u64)
+        .filter(|days| *days > 0)
+    {
+        config.max_age_days = Some(days);
+    }
+
+    if let Some(mb) = value
+        .get("max_total_size_mb")
+        .and_then(Value::as_u64)
+        .filter(|mb| *mb > 0)
+    {
+        config.max_total_size_mb = Some(mb);
+    }
+
+    if let Some(position) = value
+        .get("hud_position")
+        .and_then(Value::as_str)
+        .and_then(HudPosition::from_name)
+    {
+        config.hud_position = position;
+    }
+
+    if let Some(margin) = value
+        .get("hud_margin")
+        .and_then(Value::as_u64)
+        .and_then(|margin| i32::try_from(margin).ok())
+    {
+        config.hud_margin = margin;
+    }
+
+    if let Some(enabled) = value.get("hud_blink").and_then(Value::as_bool) {
+        config.hud_blink = enabled;
+    }
+
+    if let Some(ms) = value.get("hud_blink_interval_ms").and_then(Value::as_u64) {
+        config.hud_blink_interval_ms = ms;
+    }
+
+    if let Some(color) = value.get("hud_indicator_color").and_then(Value::as_str) {
+        config.hud_indicator_color = color.to_string();
+    }
+
+    if let Some(entries) = value.get("key_display_blocklist").and_then(Value::as_array) {
+        config.key_display_blocklist = entries
+            .iter()
+            .filter_map(|entry| Some(entry.as_str()?.to_string()))
+            .collect();
+    }
+
+    if let Some(webcam) = value.get("webcam").and_then(Value::as_object)
+        && let Some(device) = webcam.get("device").and_then(Value::as_str)
+    {
+        config.webcam = Some(WebcamConfig {
+            device: PathBuf::from(device),
+            position: webcam
+                .get("position")
+                .and_then(Value::as_str)
+                .and_then(WebcamPosition::from_name)
+                .unwrap_or_default(),
+            width: webcam
+                .get("width")
+                .and_then(Value::as_u64)
+                .and_then(|width| u32::try_from(width).ok())
+                .filter(|width| *width > 0)
+                .unwrap_or(DEFAULT_WEBCAM_WIDTH),
+        });
+    }
+
+    config
+}
+
+pub(crate) fn ensure_config_file() -> Result<PathBuf> {
+    let path = config_file_path().context("无法确定配置文件路径")?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建配置目录: {}", parent.display()))?;
+        }
+        fs::write(&path, "{}\n")
+            .with_context(|| format!("无法创建配置文件: {}", path.display()))?;
+    }
+    Ok(path)
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    if let Some(config_dir) = dirs::config_dir() {
+        return Some(config_dir.join("ncaptura").join(CONFIG_FILE));
+    }
+
+    dirs::home_dir().map(|home_dir| home_dir.join(".config").join("ncaptura").join(CONFIG_FILE))
+}
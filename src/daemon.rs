@@ -0,0 +1,91 @@
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::Result;
+use serde_json::{Value, json};
+
+use crate::capture::{
+    CaptureError, CaptureTarget, RecordingOptions, RecordingSession, start_recording,
+    stop_recording, take_screenshot,
+};
+use crate::ipc::{Reply, serve};
+
+pub(crate) fn run_daemon() -> Result<()> {
+    let session: Mutex<Option<RecordingSession>> = Mutex::new(None);
+    serve(move |words| {
+        if words == ["quit"] {
+            if let Some(active) = lock_session(&session).take() {
+                let _ = stop_recording(active);
+            }
+            return Reply::Quit;
+        }
+
+        Reply::Value(match execute(words, &session) {
+            Ok(reply) => reply,
+            Err(CaptureError::Cancelled) => json!({ "cancelled": true }),
+            Err(err) => json!({ "error": err.to_string() }),
+        })
+    })
+}
+
+fn execute(
+    words: &[&str],
+    session: &Mutex<Option<RecordingSession>>,
+) -> Result<Value, CaptureError> {
+    match words {
+        ["screenshot", target] => {
+            let path = take_screenshot(parse_target(target)?, false)?;
+            Ok(json!({ "ok": true, "path": path }))
+        }
+        ["record", "start", target] => {
+            let target = parse_target(target)?;
+            let mut session = lock_session(session);
+            if let Some(active) = session.as_ref() {
+                return Err(CaptureError::Failed(format!(
+                    "已有录屏正在进行: {}",
+                    active.output_path().display()
+                )));
+            }
+            let started = start_recording(target, &RecordingOptions::default())?;
+            let path = started.output_path().to_path_buf();
+            *session = Some(started);
+            Ok(json!({ "ok": true, "path": path }))
+        }
+        ["record", "stop"] => {
+            let active = lock_session(session)
+                .take()
+                .ok_or_else(|| CaptureError::Failed("当前没有正在进行的录屏".to_string()))?;
+            let path = stop_recording(active)?;
+            Ok(json!({ "ok": true, "path": path }))
+        }
+        ["status"] => Ok(match lock_session(session).as_ref() {
+            Some(active) => json!({
+                "ok": true,
+                "recording": true,
+                "paused": active.is_paused(),
+                "path": active.output_path(),
+            }),
+            None => json!({ "ok": true, "recording": false }),
+        }),
+        _ => Err(CaptureError::Failed(format!(
+            "未知的控制命令: {}",
+            words.join(" ")
+        ))),
+    }
+}
+
+fn parse_target(name: &str) -> Result<CaptureTarget, CaptureError> {
+    match name {
+        "region" => Ok(CaptureTarget::Region),
+        "fullscreen" => Ok(CaptureTarget::Fullscreen),
+        "all-outputs" => Ok(CaptureTarget::AllOutputs),
+        _ => Err(CaptureError::Failed(format!("不支持的目标类型: {name}"))),
+    }
+}
+
+fn lock_session(
+    session: &Mutex<Option<RecordingSession>>,
+) -> MutexGuard<'_, Option<RecordingSession>> {
+    session
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
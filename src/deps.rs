@@ -0,0 +1,290 @@
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+const OS_RELEASE: &str = "/etc/os-release";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PackageManager {
+    Pacman,
+    Apt,
+    Dnf,
+}
+
+impl PackageManager {
+    fn install_command(self, package: &str) -> String {
+        match self {
+            PackageManager::Pacman => format!("sudo pacman -S {package}"),
+            PackageManager::Apt => format!("sudo apt install {package}"),
+            PackageManager::Dnf => format!("sudo dnf install {package}"),
+        }
+    }
+}
+
+pub(crate) struct Tool {
+    pub name: &'static str,
+    pub purpose: &'static str,
+    pub essential: bool,
+    pacman: Option<&'static str>,
+    apt: Option<&'static str>,
+    dnf: Option<&'static str>,
+}
+
+impl Tool {
+    fn package_for(&self, manager: PackageManager) -> Option<&'static str> {
+        match manager {
+            PackageManager::Pacman => self.pacman,
+            PackageManager::Apt => self.apt,
+            PackageManager::Dnf => self.dnf,
+        }
+    }
+}
+
+pub(crate) const TOOLS: &[Tool] = &[
+    Tool {
+        name: "grim",
+        purpose: "截图",
+        essential: true,
+        pacman: Some("grim"),
+        apt: Some("grim"),
+        dnf: Some("grim"),
+    },
+    Tool {
+        name: "slurp",
+        purpose: "区域选择",
+        essential: true,
+        pacman: Some("slurp"),
+        apt: Some("slurp"),
+        dnf: Some("slurp"),
+    },
+    Tool {
+        name: "wf-recorder",
+        purpose: "录屏",
+        essential: true,
+        pacman: Some("wf-recorder"),
+        apt: Some("wf-recorder"),
+        dnf: Some("wf-recorder"),
+    },
+    Tool {
+        name: "wl-copy",
+        purpose: "复制到剪贴板",
+        essential: true,
+        pacman: Some("wl-clipboard"),
+        apt: Some("wl-clipboard"),
+        dnf: Some("wl-clipboard"),
+    },
+    Tool {
+        name: "wl-paste",
+        purpose: "读取剪贴板",
+        essential: false,
+        pacman: Some("wl-clipboard"),
+        apt: Some("wl-clipboard"),
+        dnf: Some("wl-clipboard"),
+    },
+    Tool {
+        name: "ffmpeg",
+        purpose: "合并暂停前后的录屏分段",
+        essential: false,
+        pacman: Some("ffmpeg"),
+        apt: Some("ffmpeg"),
+        dnf: Some("ffmpeg-free"),
+    },
+    Tool {
+        name: "pactl",
+        purpose: "选择系统混音设备",
+        essential: true,
+        pacman: Some("libpulse"),
+        apt: Some("pulseaudio-utils"),
+        dnf: Some("pulseaudio-utils"),
+    },
+    Tool {
+        name: "xdg-open",
+        purpose: "打开截图文件或所在目录",
+        essential: false,
+        pacman: Some("xdg-utils"),
+        apt: Some("xdg-utils"),
+        dnf: Some("xdg-utils"),
+    },
+    Tool {
+        name: "ydotool",
+        purpose: "滚动截图时发送滚轮事件",
+        essential: false,
+        pacman: Some("ydotool"),
+        apt: Some("ydotool"),
+        dnf: Some("ydotool"),
+    },
+    Tool {
+        name: "wtype",
+        purpose: "滚动截图时发送翻页按键",
+        essential: false,
+        pacman: Some("wtype"),
+        apt: Some("wtype"),
+        dnf: Some("wtype"),
+    },
+    Tool {
+        name: "notify-send",
+        purpose: "桌面通知",
+        essential: false,
+        pacman: Some("libnotify"),
+        apt: Some("libnotify-bin"),
+        dnf: Some("libnotify"),
+    },
+    Tool {
+        name: "qrencode",
+        purpose: "生成二维码",
+        essential: false,
+        pacman: Some("qrencode"),
+        apt: Some("qrencode"),
+        dnf: Some("qrencode"),
+    },
+    Tool {
+        name: "curl",
+        purpose: "上传截图",
+        essential: false,
+        pacman: Some("curl"),
+        apt: Some("curl"),
+        dnf: Some("curl"),
+    },
+    Tool {
+        name: "niri",
+        purpose: "niri 合成器集成",
+        essential: false,
+        pacman: Some("niri"),
+        apt: None,
+        dnf: Some("niri"),
+    },
+];
+
+pub(crate) struct DependencyReport {
+    pub missing: Vec<&'static Tool>,
+}
+
+impl DependencyReport {
+    pub fn is_installed(&self, name: &str) -> bool {
+        !self.missing.iter().any(|tool| tool.name == name)
+    }
+
+    pub fn missing_essential(&self) -> impl Iterator<Item = &'static Tool> + '_ {
+        self.missing.iter().copied().filter(|tool| tool.essential)
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        let lines: Vec<String> = self
+            .missing_essential()
+            .map(|tool| match install_hint_for(tool.name) {
+                Some(hint) => format!("{}（{}不可用）: {hint}", tool.name, tool.purpose),
+                None => format!("{}（{}不可用）", tool.name, tool.purpose),
+            })
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+
+        Some(format!("缺少以下依赖:\n  {}", lines.join("\n  ")))
+    }
+}
+
+pub(crate) fn check_dependencies() -> DependencyReport {
+    DependencyReport {
+        missing: TOOLS
+            .iter()
+            .filter(|tool| !tool_in_path(tool.name))
+            .collect(),
+    }
+}
+
+pub(crate) fn tool_in_path(name: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+
+    env::split_paths(&path).any(|dir| is_executable(&dir.join(name)))
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+pub(crate) fn install_hint_for(tool: &str) -> Option<String> {
+    let tool = TOOLS.iter().find(|candidate| candidate.name == tool)?;
+
+    if let Some(manager) = detect_package_manager() {
+        let package = tool.package_for(manager)?;
+        return Some(manager.install_command(package));
+    }
+
+    let package = tool.pacman.or(tool.apt).or(tool.dnf)?;
+    Some(format!("通过系统包管理器安装 {package}"))
+}
+
+pub(crate) fn missing_tool_message(tool: &str) -> String {
+    let purpose = TOOLS
+        .iter()
+        .find(|candidate| candidate.name == tool)
+        .map(|candidate| format!("（用于{}）", candidate.purpose))
+        .unwrap_or_default();
+
+    match install_hint_for(tool) {
+        Some(hint) => format!("无法启动 {tool}{purpose}，请确认已安装: {hint}"),
+        None => format!("无法启动 {tool}{purpose}，请确认已安装并在 PATH 中"),
+    }
+}
+
+pub(crate) const VERSIONED_TOOLS: &[&str] = &["grim", "slurp", "wf-recorder"];
+
+pub(crate) fn tool_version(tool: &str) -> Option<String> {
+    ["--version", "-v"].iter().find_map(|flag| {
+        let output = Command::new(tool)
+            .arg(flag)
+            .stdin(Stdio::null())
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        parse_version(&stdout).or_else(|| parse_version(&stderr))
+    })
+}
+
+fn parse_version(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .map(|token| {
+            token
+                .trim_start_matches(['v', 'V'])
+                .trim_end_matches(|ch: char| !ch.is_ascii_alphanumeric())
+        })
+        .find(|token| token.starts_with(|ch: char| ch.is_ascii_digit()) && token.contains('.'))
+        .map(str::to_string)
+}
+
+fn detect_package_manager() -> Option<PackageManager> {
+    static MANAGER: OnceLock<Option<PackageManager>> = OnceLock::new();
+    *MANAGER.get_or_init(|| {
+        let data = fs::read_to_string(OS_RELEASE).ok()?;
+        let mut ids = Vec::new();
+        for line in data.lines() {
+            if let Some(value) = line
+                .strip_prefix("ID=")
+                .or_else(|| line.strip_prefix("ID_LIKE="))
+            {
+                ids.extend(
+                    value
+                        .trim_matches('"')
+                        .split_whitespace()
+                        .map(str::to_string),
+                );
+            }
+        }
+
+        ids.iter().find_map(|id| match id.as_str() {
+            "arch" | "manjaro" | "endeavouros" | "cachyos" => Some(PackageManager::Pacman),
+            "debian" | "ubuntu" | "linuxmint" | "pop" => Some(PackageManager::Apt),
+            "fedora" | "rhel" | "centos" => Some(PackageManager::Dnf),
+            _ => None,
+        })
+    })
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use gtk::gdk_pixbuf::{Colorspace, Pixbuf};
+
+const DIFF_HIGHLIGHT: [u8; 3] = [255, 0, 64];
+
+pub(crate) struct DiffReport {
+    pub differing_pixels: u64,
+    pub total_pixels: u64,
+    pub size_mismatch: bool,
+}
+
+impl DiffReport {
+    pub fn ratio(&self) -> f64 {
+        if self.size_mismatch {
+            return 1.0;
+        }
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+
+        self.differing_pixels as f64 / self.total_pixels as f64
+    }
+}
+
+pub(crate) fn compare_images(
+    baseline: &Pixbuf,
+    current: &Pixbuf,
+    diff_output: Option<&Path>,
+) -> Result<DiffReport> {
+    let width = current.width();
+    let height = current.height();
+    if baseline.width() != width || baseline.height() != height {
+        return Ok(DiffReport {
+            differing_pixels: 0,
+            total_pixels: (width.max(0) as u64) * (height.max(0) as u64),
+            size_mismatch: true,
+        });
+    }
+
+    let baseline_bytes = baseline.read_pixel_bytes();
+    let current_bytes = current.read_pixel_bytes();
+    let baseline_layout = PixelLayout::of(baseline);
+    let current_layout = PixelLayout::of(current);
+
+    let mut highlighted = diff_output.map(|_| vec![0_u8; width as usize * height as usize * 3]);
+    let mut differing_pixels = 0_u64;
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let expected = baseline_layout.rgb(&baseline_bytes, x, y);
+            let actual = current_layout.rgb(&current_bytes, x, y);
+            let differs = expected != actual;
+            if differs {
+                differing_pixels += 1;
+            }
+
+            if let Some(highlighted) = highlighted.as_mut() {
+                let offset = (y * width as usize + x) * 3;
+                let pixel = if differs {
+                    DIFF_HIGHLIGHT
+                } else {
+                    actual.map(|channel| channel / 3)
+                };
+                highlighted[offset..offset + 3].copy_from_slice(&pixel);
+            }
+        }
+    }
+
+    if let (Some(path), Some(highlighted)) = (diff_output, highlighted) {
+        let image = Pixbuf::from_mut_slice(
+            highlighted,
+            Colorspace::Rgb,
+            false,
+            8,
+            width,
+            height,
+            width * 3,
+        );
+        image
+            .savev(path, "png", &[])
+            .with_context(|| format!("无法写入差异图: {}", path.display()))?;
+    }
+
+    Ok(DiffReport {
+        differing_pixels,
+        total_pixels: width as u64 * height as u64,
+        size_mismatch: false,
+    })
+}
+
+struct PixelLayout {
+    rowstride: usize,
+    channels: usize,
+}
+
+impl PixelLayout {
+    fn of(pixbuf: &Pixbuf) -> Self {
+        Self {
+            rowstride: pixbuf.rowstride() as usize,
+            channels: pixbuf.n_channels() as usize,
+        }
+    }
+
+    fn rgb(&self, bytes: &[u8], x: usize, y: usize) -> [u8; 3] {
+        let offset = y * self.rowstride + x * self.channels;
+        [bytes[offset], bytes[offset + 1], bytes[offset + 2]]
+    }
+}
+
+pub(crate) fn load_baseline(path: &Path) -> Result<Pixbuf> {
+    if !path.is_file() {
+        bail!("基准图不存在: {}", path.display());
+    }
+
+    Pixbuf::from_file(path).with_context(|| format!("无法读取基准图: {}", path.display()))
+}
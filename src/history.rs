@@ -0,0 +1,110 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde_json::{Value, json};
+
+use crate::capture::{cli_state_dir, prune_captures};
+use crate::config::config;
+
+const HISTORY_FILE: &str = "history.jsonl";
+const ROTATED_HISTORY_FILE: &str = "history.jsonl.1";
+
+#[derive(Clone, Copy)]
+pub(crate) enum CaptureKind {
+    Screenshot,
+    Recording,
+}
+
+impl CaptureKind {
+    fn name(self) -> &'static str {
+        match self {
+            CaptureKind::Screenshot => "screenshot",
+            CaptureKind::Recording => "recording",
+        }
+    }
+}
+
+pub(crate) fn log_capture(kind: CaptureKind, target: &str, outcome: Result<&Path, String>) {
+    let entry = match &outcome {
+        Ok(path) => json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "kind": kind.name(),
+            "target": target,
+            "path": path,
+            "success": true,
+        }),
+        Err(error) => json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "kind": kind.name(),
+            "target": target,
+            "path": null,
+            "success": false,
+            "error": error,
+        }),
+    };
+
+    if let Err(err) = append_entry(&entry) {
+        eprintln!("无法写入截图历史: {err:#}");
+    }
+    if let Ok(path) = outcome {
+        apply_retention(Some(path));
+    }
+}
+
+pub(crate) fn apply_retention(keep: Option<&Path>) {
+    match prune_captures(keep, false) {
+        Ok(removed) => {
+            for path in removed {
+                eprintln!("已清理旧文件: {}", path.display());
+            }
+        }
+        Err(err) => eprintln!("清理旧文件失败: {err}"),
+    }
+}
+
+pub(crate) fn recent_history(limit: usize) -> Result<Vec<Value>> {
+    let dir = cli_state_dir()?;
+    let mut entries = Vec::new();
+    for file in [ROTATED_HISTORY_FILE, HISTORY_FILE] {
+        let Ok(content) = fs::read_to_string(dir.join(file)) else {
+            continue;
+        };
+        entries.extend(
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok()),
+        );
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(skip))
+}
+
+fn append_entry(entry: &Value) -> Result<()> {
+    let dir = cli_state_dir()?;
+    fs::create_dir_all(&dir).with_context(|| format!("无法创建状态目录: {}", dir.display()))?;
+    let path = dir.join(HISTORY_FILE);
+    rotate_if_needed(&dir, &path)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("无法打开历史文件: {}", path.display()))?;
+    writeln!(file, "{entry}").with_context(|| format!("无法写入历史文件: {}", path.display()))
+}
+
+fn rotate_if_needed(dir: &Path, path: &Path) -> Result<()> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.len() < config().history_max_bytes {
+        return Ok(());
+    }
+
+    let rotated = dir.join(ROTATED_HISTORY_FILE);
+    fs::rename(path, &rotated).with_context(|| format!("无法轮转历史文件: {}", path.display()))
+}
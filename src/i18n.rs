@@ -0,0 +1,397 @@
+use std::env;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Locale {
+    En,
+    Zh,
+}
+
+impl Locale {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        if name.starts_with("zh") {
+            Some(Locale::Zh)
+        } else if name.starts_with("en") || name == "c" || name.starts_with("c.") || name == "posix"
+        {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+
+    fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::from_name(&value))
+            .unwrap_or(Locale::En)
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+pub(crate) fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+pub(crate) fn locale() -> Locale {
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+pub(crate) fn message(key: &'static str) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|(candidate, _, _)| *candidate == key)
+        .map(|(_, en, zh)| match locale() {
+            Locale::En => *en,
+            Locale::Zh => *zh,
+        })
+        .unwrap_or(key)
+}
+
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::message($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut message = $crate::i18n::message($key).to_string();
+        $(
+            message = message.replace(concat!("{", stringify!($name), "}"), &$value.to_string());
+        )+
+        message
+    }};
+}
+
+pub(crate) use t;
+
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("cli.missing_command", "Missing command", "缺少命令"),
+    ("cli.unknown_command", "Unknown command", "未知命令"),
+    (
+        "cli.lang_missing",
+        "--lang requires a language (en or zh)",
+        "--lang 需要提供语言（en 或 zh）",
+    ),
+    (
+        "cli.lang_unsupported",
+        "Unsupported language: {lang} (available: en, zh)",
+        "不支持的语言: {lang}（可选 en、zh）",
+    ),
+    ("cli.cancelled", "Selection cancelled", "已取消选择"),
+    ("screenshot.failed", "Screenshot failed: {err}", "截图失败: {err}"),
+    (
+        "screenshot.remove_temp_failed",
+        "Failed to remove temporary screenshot {path}: {err}",
+        "无法删除临时截图文件 {path}: {err}",
+    ),
+    (
+        "screenshot.copied",
+        "Screenshot copied to clipboard",
+        "截图已复制到剪贴板",
+    ),
+    ("screenshot.saved", "Screenshot saved: {path}", "截图已保存: {path}"),
+    ("screenshot.saved_title", "Screenshot saved", "截图已保存"),
+    (
+        "grab.notify_title",
+        "Screenshot saved and copied",
+        "截图已保存并复制",
+    ),
+    (
+        "grab.copy_failed",
+        "Screenshot saved, but copying it failed: {err}",
+        "截图已保存，但复制到剪贴板失败: {err}",
+    ),
+    (
+        "screenshot.open_failed",
+        "Failed to open screenshot: {err}",
+        "无法打开截图: {err}",
+    ),
+    (
+        "record.countdown",
+        "Recording starts in {seconds}s…",
+        "{seconds} 秒后开始录屏…",
+    ),
+    (
+        "record.hud_hint",
+        "The recording HUD is shown on screen; pause/stop there, or run `ncaptura record stop`.",
+        "已显示录制小窗，可在小窗中暂停/停止，或使用 `ncaptura record stop` 停止录屏。",
+    ),
+    (
+        "record.stop_hint",
+        "Run `ncaptura record stop` to stop recording.",
+        "请使用 `ncaptura record stop` 停止录屏。",
+    ),
+    (
+        "record.started",
+        "Recording started, output file: {path}\n{hint}",
+        "录屏已开始，输出文件: {path}\n{hint}",
+    ),
+    (
+        "record.start_failed",
+        "Failed to start recording: {err}",
+        "开始录屏失败: {err}",
+    ),
+    (
+        "record.stopped",
+        "Recording stopped, saved to: {path}",
+        "录屏已停止，文件保存为: {path}",
+    ),
+    ("record.stopped_title", "Recording stopped", "录屏已停止"),
+    (
+        "record.thumbnail_saved",
+        "Thumbnail saved to: {path}",
+        "缩略图已保存为: {path}",
+    ),
+    (
+        "record.thumbnail_failed",
+        "Recording saved, but extracting the thumbnail failed: {err}",
+        "录屏已保存，但提取缩略图失败: {err}",
+    ),
+    (
+        "record.stop_failed",
+        "Failed to stop recording: {err}",
+        "停止录屏失败: {err}",
+    ),
+    (
+        "record.nothing_to_recover",
+        "No recording needs recovery",
+        "没有需要恢复的录屏",
+    ),
+    (
+        "record.recovered",
+        "Cleared stale recording state and repaired the recording: {path}",
+        "已清理失效的录屏状态，并修复录制内容: {path}",
+    ),
+    (
+        "record.unrecoverable",
+        "Cleared stale recording state, but the recording could not be repaired: {path}",
+        "已清理失效的录屏状态，但无法修复录屏文件: {path}",
+    ),
+    (
+        "record.recover_failed",
+        "Failed to recover recording: {err}",
+        "恢复录屏失败: {err}",
+    ),
+    (
+        "record.no_cli_recording",
+        "No recording started from the CLI",
+        "当前没有通过 CLI 启动的录屏",
+    ),
+    (
+        "record.hud_process_exited",
+        "Recording process {pid} has exited; cannot show the recording HUD again",
+        "录屏进程 {pid} 已退出，无法重新显示录制小窗",
+    ),
+    (
+        "record.hud_shown",
+        "Recording HUD shown again, output file: {path}",
+        "已重新显示录制小窗，输出文件: {path}",
+    ),
+    ("record.status_recording", "Recording", "正在录屏"),
+    (
+        "record.status_exited",
+        "State file exists, but the recording process has exited",
+        "状态文件存在，但录屏进程已退出",
+    ),
+    (
+        "record.status",
+        "{state}\nPID: {pid}\nTarget: {target}\nOutput file: {path}\nStarted at: {started_at}\nElapsed: {elapsed}\nProcess alive: {alive}",
+        "{state}\nPID: {pid}\n目标: {target}\n输出文件: {path}\n开始时间: {started_at}\n已录制: {elapsed}\n进程存活: {alive}",
+    ),
+    ("record.status_unknown_target", "unknown", "未知"),
+    ("common.yes", "yes", "是"),
+    ("common.no", "no", "否"),
+    (
+        "windows.list_failed",
+        "Failed to list windows: {err}",
+        "读取窗口列表失败: {err}",
+    ),
+    (
+        "diff.failed",
+        "Screenshot comparison failed: {err}",
+        "截图对比失败: {err}",
+    ),
+    (
+        "diff.size_mismatch",
+        "Screenshot size differs from the baseline",
+        "截图与基准图尺寸不一致",
+    ),
+    (
+        "diff.summary",
+        "Differing pixels: {differing}/{total} ({ratio}%), threshold {threshold}%",
+        "差异像素: {differing}/{total} ({ratio}%)，阈值 {threshold}%",
+    ),
+    ("diff.result_match", "Result: identical", "结果: 一致"),
+    ("diff.result_differ", "Result: different", "结果: 存在差异"),
+    ("diff.output", "Diff image: {path}", "差异图: {path}"),
+    (
+        "scroll.failed",
+        "Scroll capture failed: {err}",
+        "滚动截图失败: {err}",
+    ),
+    (
+        "scroll.saved",
+        "Scroll capture saved ({height}px tall): {path}",
+        "滚动截图已保存（高 {height}px）: {path}",
+    ),
+    (
+        "history.failed",
+        "Failed to read capture history: {err}",
+        "读取截图历史失败: {err}",
+    ),
+    ("history.empty", "No capture history yet", "暂无截图历史"),
+    (
+        "prune.not_configured",
+        "No retention policy configured; set max_age_days or max_total_size_mb in config.json",
+        "未配置保留策略，请在 config.json 中设置 max_age_days 或 max_total_size_mb",
+    ),
+    (
+        "prune.failed",
+        "Failed to clean up old captures: {err}",
+        "清理旧文件失败: {err}",
+    ),
+    (
+        "prune.nothing",
+        "No captures exceed the retention policy",
+        "没有超出保留策略的文件",
+    ),
+    (
+        "prune.would_remove",
+        "{count} file(s) would be removed:",
+        "将删除 {count} 个文件:",
+    ),
+    ("prune.removed", "Removed {count} file(s):", "已删除 {count} 个文件:"),
+    ("pin.failed", "Pin failed: {err}", "钉图失败: {err}"),
+    (
+        "pin.shown",
+        "Pinned {geometry} to the screen; press Esc on it to close",
+        "已将 {geometry} 钉在屏幕上，在图片上按 Esc 关闭",
+    ),
+    (
+        "daemon.failed",
+        "Daemon stopped: {err}",
+        "守护进程已退出: {err}",
+    ),
+    (
+        "portal.failed",
+        "Portal service stopped: {err}",
+        "portal 服务已退出: {err}",
+    ),
+    (
+        "portal.disabled",
+        "This build has no portal support; rebuild with `cargo build --features portal`",
+        "当前构建未启用 portal 支持，请使用 `cargo build --features portal` 重新编译",
+    ),
+    ("version.not_detected", "not detected", "未检测到"),
+    ("deps.installed", "installed", "已安装"),
+    ("deps.missing", "missing", "缺失"),
+    ("deps.missing_optional", "missing (optional)", "缺失（可选）"),
+    (
+        "share.clipboard_failed",
+        "Failed to read clipboard: {err}",
+        "读取剪贴板失败: {err}",
+    ),
+    (
+        "share.serving",
+        "Screenshot saved: {path}\nShare link (expires in {seconds}s): {url}",
+        "截图已保存: {path}\n分享链接（{seconds} 秒后失效）: {url}",
+    ),
+    (
+        "share.server_failed",
+        "Failed to start share server: {err}",
+        "启动分享服务失败: {err}",
+    ),
+    (
+        "cli.usage",
+        "NCaptura CLI
+
+Usage:
+  ncaptura                      Launch the graphical interface
+  ncaptura screenshot region [--pointer] [--repeat-region] [--copy | --copy-only] [--open]
+  ncaptura screenshot fullscreen [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot all-outputs [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot geometry \"X,Y WxH\" [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot window <id> [--pointer] [--padding N] [--copy | --copy-only] [--open]
+  ncaptura screenshot workspace <id> [--pointer] [--copy | --copy-only] [--open]
+    Common screenshot options: [--format png|ppm] [--stdout | -]
+  ncaptura grab region|fullscreen|all-outputs  Capture, save, copy to the clipboard and notify in one step
+  ncaptura record start region|fullscreen|all-outputs [--audio [--normalize-audio]] [--max-duration <seconds>]
+    Common recording options: [--container mkv|mp4|webm] [--codec <encoder>] [--quality low|medium|high] [--delay <seconds>] [--no-cursor] [--no-hud] [--highlight-clicks] [--show-keys] [--hud-position <corner>]
+  ncaptura record stop [--thumbnail | --thumbnail-at <seconds>] [--copy]
+  ncaptura record recover       Clear interrupted recording state and try to repair the file with ffmpeg
+  ncaptura record hud           Show the recording HUD again for a running CLI recording
+  ncaptura record status        Query recording status (exit code 0 while recording, 3 when idle)
+  ncaptura diff --baseline <a.png> <region|fullscreen|geometry \"X,Y WxH\">
+               [--threshold 0.01] [--diff-output diff.png] [--pointer]
+                                Capture and compare with a baseline pixel by pixel (exit 0 identical, 1 different, 2 error)
+  ncaptura scroll-capture [region | geometry \"X,Y WxH\"] [--steps N]
+                                Scroll the area N times (default 5) and stitch the frames into one long screenshot
+  ncaptura list-windows          Print capturable windows as JSON
+  ncaptura share region|fullscreen  Capture and share via a temporary LAN link (shows a QR code)
+  ncaptura share text           Show clipboard text as a QR code
+  ncaptura pin region           Select a region and pin it on top of everything (drag to move, Esc to close)
+  ncaptura daemon               Stay resident and accept commands on $XDG_RUNTIME_DIR/ncaptura.sock
+  ncaptura msg <command>        Send a command to the daemon (screenshot <target>, record start <target>, record stop, status, quit)
+  ncaptura history [N]          Print the last N capture history entries (default 20)
+  ncaptura prune [--dry-run]    Delete captures beyond max_age_days / max_total_size_mb (--dry-run only lists them)
+  ncaptura check-deps           Check external dependencies (exit code 1 if essential ones are missing)
+  ncaptura completions bash|zsh|fish  Print a shell completion script
+  ncaptura help
+  ncaptura --version, -V        Print the ncaptura version and detected grim/slurp/wf-recorder versions
+  ncaptura --portal             Run as an org.freedesktop.impl.portal.Screenshot backend (requires the portal feature)
+
+Global options:
+  -q, --quiet                   Suppress success output (errors still go to stderr, exit codes unchanged)
+  --json                        Print results as JSON; errors are printed to stdout as {\"error\": ...}
+  --lang en|zh                  Message language (defaults to LC_ALL / LC_MESSAGES / LANG)
+  --verbose                     Print diagnostic details to stderr (e.g. which clipboard path was used)
+
+niri keybinding examples:{keybinds}",
+        "NCaptura CLI
+
+用法:
+  ncaptura                      启动图形界面
+  ncaptura screenshot region [--pointer] [--repeat-region] [--copy | --copy-only] [--open]
+  ncaptura screenshot fullscreen [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot all-outputs [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot geometry \"X,Y WxH\" [--pointer] [--copy | --copy-only] [--open]
+  ncaptura screenshot window <id> [--pointer] [--padding N] [--copy | --copy-only] [--open]
+  ncaptura screenshot workspace <id> [--pointer] [--copy | --copy-only] [--open]
+    截图通用参数: [--format png|ppm] [--stdout | -]
+  ncaptura grab region|fullscreen|all-outputs  一步完成截图、保存、复制到剪贴板并发送通知
+  ncaptura record start region|fullscreen|all-outputs [--audio [--normalize-audio]] [--max-duration <秒>]
+    录屏通用参数: [--container mkv|mp4|webm] [--codec <编码器>] [--quality low|medium|high] [--delay <秒>] [--no-cursor] [--no-hud] [--highlight-clicks] [--show-keys] [--hud-position <位置>]
+  ncaptura record stop [--thumbnail | --thumbnail-at <秒>] [--copy]
+  ncaptura record recover       清理异常中断的录屏状态，并尝试用 ffmpeg 修复已录制的文件
+  ncaptura record hud           为正在进行的 CLI 录屏重新显示录制小窗
+  ncaptura record status        查询录屏状态（录制中退出码 0，空闲退出码 3）
+  ncaptura diff --baseline <a.png> <region|fullscreen|geometry \"X,Y WxH\">
+               [--threshold 0.01] [--diff-output diff.png] [--pointer]
+                                截图并与基准图逐像素对比（一致退出码 0，存在差异 1，出错 2）
+  ncaptura scroll-capture [region | geometry \"X,Y WxH\"] [--steps N]
+                                滚动区域 N 次（默认 5 次）并将各帧拼接为一张长截图
+  ncaptura list-windows          以 JSON 输出可截图的窗口列表
+  ncaptura share region|fullscreen  截图并通过局域网临时链接分享（显示二维码）
+  ncaptura share text           将剪贴板文本显示为二维码
+  ncaptura pin region           选择区域并将截图钉在屏幕最上层（可拖动，按 Esc 关闭）
+  ncaptura daemon               常驻后台，通过 $XDG_RUNTIME_DIR/ncaptura.sock 接收命令
+  ncaptura msg <命令>           向守护进程发送命令（screenshot <目标>、record start <目标>、record stop、status、quit）
+  ncaptura history [N]          输出最近 N 条截图/录屏历史（默认 20 条）
+  ncaptura prune [--dry-run]    删除超出 max_age_days / max_total_size_mb 的旧文件（--dry-run 仅列出）
+  ncaptura check-deps           检查外部依赖是否安装（缺少必需依赖时退出码 1）
+  ncaptura completions bash|zsh|fish  输出 shell 补全脚本
+  ncaptura help
+  ncaptura --version, -V        输出 ncaptura 版本以及检测到的 grim/slurp/wf-recorder 版本
+  ncaptura --portal             作为 org.freedesktop.impl.portal.Screenshot 后端运行（需启用 portal 特性）
+
+全局参数:
+  -q, --quiet                   不输出成功信息（错误仍输出到 stderr，退出码不变）
+  --json                        以 JSON 输出结果，错误以 {\"error\": ...} 输出到 stdout
+  --lang en|zh                  输出语言（默认根据 LC_ALL / LC_MESSAGES / LANG 判断）
+  --verbose                     向 stderr 输出诊断信息（例如复制到剪贴板时使用的方式）
+
+niri 快捷键示例:{keybinds}",
+    ),
+];
@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use gtk::gdk_pixbuf::prelude::*;
+use gtk::gdk_pixbuf::{InterpType, Pixbuf, PixbufLoader};
+
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Png,
+    Jpeg { quality: u8 },
+}
+
+impl Encoding {
+    pub(crate) fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        match extension.as_str() {
+            "png" => Some(Encoding::Png),
+            "jpg" | "jpeg" => Some(Encoding::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY,
+            }),
+            _ => None,
+        }
+    }
+
+    fn pixbuf_type(self) -> &'static str {
+        match self {
+            Encoding::Png => "png",
+            Encoding::Jpeg { .. } => "jpeg",
+        }
+    }
+
+    fn options(self) -> Vec<(&'static str, String)> {
+        match self {
+            Encoding::Png => Vec::new(),
+            Encoding::Jpeg { quality } => vec![("quality", quality.clamp(1, 100).to_string())],
+        }
+    }
+}
+
+pub(crate) fn load_image(path: &Path) -> Result<Pixbuf> {
+    Pixbuf::from_file(path).with_context(|| format!("无法加载图片: {}", path.display()))
+}
+
+pub(crate) fn load_png_bytes(bytes: &[u8]) -> Result<Pixbuf> {
+    let loader = PixbufLoader::with_type("png").context("无法创建 PNG 解码器")?;
+    loader.write(bytes).context("解码截图失败")?;
+    loader.close().context("解码截图失败")?;
+    loader.pixbuf().context("解码截图失败: 未得到图像")
+}
+
+pub(crate) fn save_image(image: &Pixbuf, path: &Path, encoding: Encoding) -> Result<()> {
+    let options = encoding.options();
+    let options: Vec<(&str, &str)> = options
+        .iter()
+        .map(|(key, value)| (*key, value.as_str()))
+        .collect();
+    image
+        .savev(path, encoding.pixbuf_type(), &options)
+        .with_context(|| format!("无法保存图片: {}", path.display()))
+}
+
+pub(crate) fn thumbnail(image: &Pixbuf, max_width: i32, max_height: i32) -> Result<Pixbuf> {
+    let (width, height) = thumbnail_size(image.width(), image.height(), max_width, max_height);
+    if (width, height) == (image.width(), image.height()) {
+        return Ok(image.clone());
+    }
+    image
+        .scale_simple(width, height, InterpType::Bilinear)
+        .context("无法生成缩略图")
+}
+
+fn thumbnail_size(width: i32, height: i32, max_width: i32, max_height: i32) -> (i32, i32) {
+    if width <= 0 || height <= 0 {
+        return (width, height);
+    }
+    let scale = (f64::from(max_width) / f64::from(width))
+        .min(f64::from(max_height) / f64::from(height))
+        .min(1.0);
+    let scaled = |length: i32| ((f64::from(length) * scale).round() as i32).max(1);
+    (scaled(width), scaled(height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnail_size_keeps_aspect_ratio() {
+        assert_eq!(thumbnail_size(1920, 1080, 160, 100), (160, 90));
+        assert_eq!(thumbnail_size(1080, 1920, 160, 100), (56, 100));
+    }
+
+    #[test]
+    fn thumbnail_size_never_upscales() {
+        assert_eq!(thumbnail_size(120, 80, 160, 100), (120, 80));
+    }
+
+    #[test]
+    fn thumbnail_size_keeps_thin_images_visible() {
+        assert_eq!(thumbnail_size(10_000, 10, 160, 100), (160, 1));
+    }
+
+    #[test]
+    fn encoding_from_extension_ignores_case() {
+        assert_eq!(
+            Encoding::from_extension(Path::new("shot.PNG")),
+            Some(Encoding::Png)
+        );
+        assert_eq!(
+            Encoding::from_extension(Path::new("shot.Jpg")),
+            Some(Encoding::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY
+            })
+        );
+        assert_eq!(Encoding::from_extension(Path::new("shot.webp")), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+
+const SOCKET_NAME: &str = "ncaptura.sock";
+
+pub(crate) enum Reply {
+    Value(Value),
+    Quit,
+}
+
+pub(crate) fn socket_path() -> Result<PathBuf> {
+    let runtime_dir = dirs::runtime_dir().context("未设置 XDG_RUNTIME_DIR，无法创建控制套接字")?;
+    Ok(runtime_dir.join(SOCKET_NAME))
+}
+
+pub(crate) fn daemon_running() -> bool {
+    socket_path().is_ok_and(|path| UnixStream::connect(path).is_ok())
+}
+
+pub(crate) fn serve(handler: impl Fn(&[&str]) -> Reply + Send + Sync + 'static) -> Result<()> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        bail!("守护进程已在运行: {}", path.display());
+    }
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("无法监听控制套接字: {}", path.display()))?;
+
+    let handler = Arc::new(handler);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("接受控制连接失败: {err}");
+                continue;
+            }
+        };
+        let handler = handler.clone();
+        let path = path.clone();
+        thread::spawn(move || {
+            if let Err(err) = serve_client(stream, handler.as_ref(), &path) {
+                eprintln!("处理控制连接失败: {err:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn send_command(command: &str) -> Result<Value> {
+    let path = socket_path()?;
+    let mut stream = UnixStream::connect(&path).with_context(|| {
+        format!(
+            "无法连接守护进程（{}），请先运行 `ncaptura daemon`",
+            path.display()
+        )
+    })?;
+    writeln!(stream, "{command}").context("发送命令失败")?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .context("读取守护进程响应失败")?;
+    serde_json::from_str(&reply).context("守护进程响应格式错误")
+}
+
+fn serve_client(
+    stream: UnixStream,
+    handler: &(impl Fn(&[&str]) -> Reply + ?Sized),
+    path: &Path,
+) -> Result<()> {
+    let mut writer = stream.try_clone().context("无法复制控制连接")?;
+    for line in BufReader::new(stream).lines() {
+        let line = line.context("读取控制命令失败")?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        match handler(&words) {
+            Reply::Value(reply) => writeln!(writer, "{reply}").context("发送响应失败")?,
+            Reply::Quit => {
+                writeln!(writer, "{}", json!({ "ok": true })).context("发送响应失败")?;
+                let _ = fs::remove_file(path);
+                process::exit(0);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub(crate) struct Keybind {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub args: &'static [&'static str],
+}
+
+pub(crate) const RECOMMENDED_KEYBINDS: &[Keybind] = &[
+    Keybind {
+        key: "Mod+Shift+S",
+        description: "Screenshot a region",
+        args: &["screenshot", "region"],
+    },
+    Keybind {
+        key: "Mod+Shift+F",
+        description: "Screenshot the focused output",
+        args: &["screenshot", "fullscreen"],
+    },
+    Keybind {
+        key: "Mod+Shift+C",
+        description: "Copy a region to the clipboard",
+        args: &["screenshot", "region", "--copy-only"],
+    },
+    Keybind {
+        key: "Mod+Shift+R",
+        description: "Start recording a region",
+        args: &["record", "start", "region"],
+    },
+    Keybind {
+        key: "Mod+Shift+A",
+        description: "Start recording a region with audio",
+        args: &["record", "start", "region", "--audio"],
+    },
+    Keybind {
+        key: "Mod+Shift+E",
+        description: "Stop recording",
+        args: &["record", "stop"],
+    },
+];
+
+impl Keybind {
+    pub fn niri_snippet(&self) -> String {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| format!(" \"{arg}\""))
+            .collect::<String>();
+        format!("{:<14} {{ spawn \"ncaptura\"{args}; }}", self.key)
+    }
+}
+
+pub(crate) struct NiriKeybindStatus {
+    pub config_path: PathBuf,
+    pub configured: Vec<bool>,
+}
+
+pub(crate) fn detect_niri_keybinds() -> Option<NiriKeybindStatus> {
+    let config_path = niri_config_path()?;
+    let data = fs::read_to_string(&config_path).ok()?;
+
+    let spawned: Vec<Vec<String>> = data
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .filter_map(ncaptura_spawn_args)
+        .collect();
+
+    let configured = RECOMMENDED_KEYBINDS
+        .iter()
+        .map(|keybind| spawned.iter().any(|args| args == keybind.args))
+        .collect();
+
+    Some(NiriKeybindStatus {
+        config_path,
+        configured,
+    })
+}
+
+fn ncaptura_spawn_args(line: &str) -> Option<Vec<String>> {
+    let (_, after_spawn) = line.split_once("spawn")?;
+    let mut quoted = after_spawn
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string);
+
+    let program = quoted.next()?;
+    if program != "ncaptura" && !program.ends_with("/ncaptura") {
+        return None;
+    }
+
+    Some(
+        quoted
+            .filter(|arg| arg != "--quiet" && arg != "-q")
+            .collect(),
+    )
+}
+
+pub(crate) fn niri_config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("NIRI_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    dirs::config_dir().map(|config_dir| config_dir.join("niri").join("config.kdl"))
+}
@@ -0,0 +1,22 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use anyhow::{Context, Result};
+
+use crate::deps::missing_tool_message;
+
+pub(crate) fn open_with_default_app(path: &Path) -> Result<()> {
+    let mut child = Command::new("xdg-open")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| missing_tool_message("xdg-open"))?;
+
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
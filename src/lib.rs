@@ -0,0 +1,24 @@
+mod app;
+pub mod capture;
+mod cli;
+mod completions;
+mod config;
+mod daemon;
+mod deps;
+mod diff;
+mod history;
+mod i18n;
+mod imaging;
+mod ipc;
+mod keybinds;
+mod launch;
+mod notify;
+#[cfg(feature = "portal")]
+mod portal;
+mod scroll_capture;
+mod share;
+mod ui;
+mod upload;
+
+pub use app::run;
+pub use cli::handle_cli_if_requested;
@@ -1,12 +1,7 @@
-mod app;
-mod capture;
-mod cli;
-mod ui;
-
 fn main() {
-    if let Err(code) = cli::handle_cli_if_requested() {
+    if let Err(code) = ncaptura::handle_cli_if_requested() {
         std::process::exit(code);
     }
 
-    app::run();
+    ncaptura::run();
 }
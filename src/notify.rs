@@ -0,0 +1,22 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::config;
+
+pub(crate) fn notify(summary: &str, body: &str, image_path: Option<&Path>) {
+    if !config().notifications {
+        return;
+    }
+
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name", "NCaptura"]);
+    if let Some(image_path) = image_path {
+        command.arg("-i").arg(image_path);
+    }
+    command.arg(summary).arg(body);
+    command.stdout(Stdio::null()).stderr(Stdio::null());
+
+    if let Err(err) = command.status() {
+        eprintln!("无法发送桌面通知: {err}");
+    }
+}
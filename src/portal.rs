@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use anyhow::{Context, Result, anyhow, bail};
+use gtk::gio;
+use gtk::glib::{self, Variant, VariantDict};
+
+use crate::capture::{CaptureError, CaptureTarget, take_screenshot};
+
+const BUS_NAME: &str = "org.freedesktop.impl.portal.desktop.ncaptura";
+const OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const SCREENSHOT_INTERFACE: &str = "org.freedesktop.impl.portal.Screenshot";
+const INTERFACE_XML: &str = r#"<node>
+  <interface name="org.freedesktop.impl.portal.Screenshot">
+    <method name="Screenshot">
+      <arg type="o" name="handle" direction="in"/>
+      <arg type="s" name="app_id" direction="in"/>
+      <arg type="s" name="parent_window" direction="in"/>
+      <arg type="a{sv}" name="options" direction="in"/>
+      <arg type="u" name="response" direction="out"/>
+      <arg type="a{sv}" name="results" direction="out"/>
+    </method>
+    <property name="version" type="u" access="read"/>
+  </interface>
+</node>"#;
+const INTERFACE_VERSION: u32 = 2;
+
+const RESPONSE_SUCCESS: u32 = 0;
+const RESPONSE_CANCELLED: u32 = 1;
+const RESPONSE_FAILED: u32 = 2;
+
+pub(crate) fn run_portal_service() -> Result<()> {
+    let connection = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)
+        .context("无法连接 D-Bus 会话总线")?;
+    let node = gio::DBusNodeInfo::for_xml(INTERFACE_XML).context("解析 portal 接口描述失败")?;
+    let interface = node
+        .lookup_interface(SCREENSHOT_INTERFACE)
+        .context("portal 接口描述缺少 Screenshot 接口")?;
+
+    let _registration = connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(|_, _, _, _, method, parameters, invocation| {
+            if method != "Screenshot" {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.UnknownMethod",
+                    &format!("不支持的方法: {method}"),
+                );
+                return;
+            }
+            handle_screenshot(&parameters, invocation);
+        })
+        .property(|_, _, _, _, property| match property {
+            "version" => INTERFACE_VERSION.to_variant(),
+            _ => ().to_variant(),
+        })
+        .build()
+        .context("注册 portal 对象失败")?;
+
+    let main_loop = glib::MainLoop::new(None, false);
+    let lost_reason: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let _owner = {
+        let main_loop = main_loop.clone();
+        let lost_reason = lost_reason.clone();
+        gio::bus_own_name_on_connection(
+            &connection,
+            BUS_NAME,
+            gio::BusNameOwnerFlags::NONE,
+            |_, _| {},
+            move |_, name| {
+                *lost_reason.borrow_mut() = Some(format!("无法占用 D-Bus 名称 {name}"));
+                main_loop.quit();
+            },
+        )
+    };
+
+    main_loop.run();
+    match lost_reason.borrow_mut().take() {
+        Some(reason) => bail!("{reason}"),
+        None => Ok(()),
+    }
+}
+
+fn handle_screenshot(parameters: &Variant, invocation: gio::DBusMethodInvocation) {
+    let interactive = parameters
+        .try_child_value(3)
+        .map(|options| VariantDict::new(Some(&options)))
+        .and_then(|options| options.lookup::<bool>("interactive").ok().flatten())
+        .unwrap_or(false);
+    let target = if interactive {
+        CaptureTarget::Region
+    } else {
+        CaptureTarget::Fullscreen
+    };
+
+    glib::spawn_future_local(async move {
+        let result = gio::spawn_blocking(move || take_screenshot(target, false))
+            .await
+            .map_err(|_| anyhow!("工作线程异常退出"));
+        let results = VariantDict::new(None);
+        let response = match result {
+            Ok(Ok(path)) => {
+                results.insert_value("uri", &gio::File::for_path(&path).uri().to_variant());
+                RESPONSE_SUCCESS
+            }
+            Ok(Err(CaptureError::Cancelled)) => RESPONSE_CANCELLED,
+            Ok(Err(err)) => {
+                eprintln!("portal 截图失败: {err}");
+                RESPONSE_FAILED
+            }
+            Err(err) => {
+                eprintln!("portal 截图失败: {err}");
+                RESPONSE_FAILED
+            }
+        };
+        let reply = Variant::tuple_from_iter([response.to_variant(), results.end()]);
+        invocation.return_value(Some(&reply));
+    });
+}
@@ -0,0 +1,146 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::capture::{ImageFormat, take_geometry_screenshot_to_bytes};
+use crate::deps::{missing_tool_message, tool_in_path};
+use crate::imaging::load_png_bytes;
+
+const SCROLL_SETTLE: Duration = Duration::from_millis(400);
+const YDOTOOL_WHEEL_CLICKS: u32 = 5;
+const MIN_OVERLAP_ROWS: usize = 16;
+const OVERLAP_MATCH_RATIO: f64 = 0.98;
+
+pub(crate) fn scroll_capture(geometry: &str, steps: u32) -> Result<Pixbuf> {
+    let scroller = Scroller::detect()?;
+    let mut frames = vec![capture_frame(geometry)?];
+    let mut shifts = Vec::new();
+
+    for _ in 0..steps {
+        scroller.scroll()?;
+        thread::sleep(SCROLL_SETTLE);
+
+        let frame = capture_frame(geometry)?;
+        let previous = frames.last().context("滚动截图缺少起始画面")?;
+        match new_rows(previous, &frame) {
+            Some(0) => break,
+            Some(shift) => shifts.push(shift),
+            None => shifts.push(frame.height() as usize),
+        }
+        frames.push(frame);
+    }
+
+    stitch(&frames, &shifts)
+}
+
+enum Scroller {
+    Ydotool,
+    Wtype,
+}
+
+impl Scroller {
+    fn detect() -> Result<Self> {
+        if tool_in_path("ydotool") {
+            Ok(Scroller::Ydotool)
+        } else if tool_in_path("wtype") {
+            Ok(Scroller::Wtype)
+        } else {
+            bail!(
+                "滚动截图需要 ydotool 或 wtype: {}",
+                missing_tool_message("ydotool")
+            )
+        }
+    }
+
+    fn scroll(&self) -> Result<()> {
+        let status = match self {
+            Scroller::Ydotool => Command::new("ydotool")
+                .args(["mousemove", "--wheel", "-x", "0", "-y"])
+                .arg(format!("-{YDOTOOL_WHEEL_CLICKS}"))
+                .status()
+                .with_context(|| missing_tool_message("ydotool"))?,
+            Scroller::Wtype => Command::new("wtype")
+                .args(["-k", "Page_Down"])
+                .status()
+                .with_context(|| missing_tool_message("wtype"))?,
+        };
+        if !status.success() {
+            bail!("发送滚动事件失败: {status}");
+        }
+        Ok(())
+    }
+}
+
+fn capture_frame(geometry: &str) -> Result<Pixbuf> {
+    let bytes = take_geometry_screenshot_to_bytes(geometry, false, ImageFormat::Png)?;
+    load_png_bytes(&bytes)
+}
+
+fn row_hashes(frame: &Pixbuf) -> Vec<(u64, bool)> {
+    let bytes = frame.read_pixel_bytes();
+    let rowstride = frame.rowstride() as usize;
+    let row_len = frame.width() as usize * frame.n_channels() as usize;
+    (0..frame.height() as usize)
+        .map(|y| {
+            let row = &bytes[y * rowstride..y * rowstride + row_len];
+            let mut hasher = DefaultHasher::new();
+            row.hash(&mut hasher);
+            let pixel = frame.n_channels() as usize;
+            let uniform = row.chunks(pixel).all(|chunk| chunk == &row[..pixel]);
+            (hasher.finish(), uniform)
+        })
+        .collect()
+}
+
+fn new_rows(previous: &Pixbuf, current: &Pixbuf) -> Option<usize> {
+    if previous.width() != current.width() || previous.height() != current.height() {
+        return None;
+    }
+
+    let previous_rows = row_hashes(previous);
+    let current_rows = row_hashes(current);
+    let height = current_rows.len();
+    (0..height.saturating_sub(MIN_OVERLAP_ROWS)).find(|shift| {
+        let overlap = height - shift;
+        let (matched, significant) = (0..overlap).filter(|row| !current_rows[*row].1).fold(
+            (0usize, 0usize),
+            |(matched, significant), row| {
+                let same = previous_rows[row + shift].0 == current_rows[row].0;
+                (matched + usize::from(same), significant + 1)
+            },
+        );
+        significant >= MIN_OVERLAP_ROWS
+            && matched as f64 / significant as f64 >= OVERLAP_MATCH_RATIO
+    })
+}
+
+fn stitch(frames: &[Pixbuf], shifts: &[usize]) -> Result<Pixbuf> {
+    let first = frames.first().context("滚动截图没有任何画面")?;
+    let width = first.width();
+    let height = first.height();
+    let total_height = height + shifts.iter().sum::<usize>() as i32;
+
+    let combined = Pixbuf::new(
+        first.colorspace(),
+        first.has_alpha(),
+        first.bits_per_sample(),
+        width,
+        total_height,
+    )
+    .context("无法分配拼接后的图像")?;
+    first.copy_area(0, 0, width, height, &combined, 0, 0);
+
+    let mut offset = height;
+    for (frame, shift) in frames.iter().skip(1).zip(shifts) {
+        let shift = *shift as i32;
+        frame.copy_area(0, height - shift, width, shift, &combined, 0, offset);
+        offset += shift;
+    }
+
+    Ok(combined)
+}
@@ -0,0 +1,154 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::deps::missing_tool_message;
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) struct ShareServer {
+    pub url: String,
+}
+
+pub(crate) fn serve_file(path: &Path, timeout: Duration) -> Result<ShareServer> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("分享文件名无效")?
+        .to_string();
+    let data = fs::read(path).with_context(|| format!("无法读取分享文件: {}", path.display()))?;
+
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).context("无法启动本地分享服务")?;
+    listener
+        .set_nonblocking(true)
+        .context("无法配置本地分享服务")?;
+    let port = listener.local_addr().context("无法读取分享端口")?.port();
+
+    let request_path = format!("/{}/{file_name}", share_token());
+    let url = format!("http://{}:{port}{request_path}", lan_address());
+    let content_type = content_type_for(path);
+
+    let deadline = Instant::now() + timeout;
+    thread::spawn(move || {
+        while Instant::now() < deadline {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = respond(stream, &request_path, content_type, &data) {
+                        eprintln!("分享请求处理失败: {err}");
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(ACCEPT_POLL_INTERVAL);
+                }
+                Err(err) => {
+                    eprintln!("分享服务异常: {err}");
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(ShareServer { url })
+}
+
+pub(crate) fn render_qr_code(text: &str) -> Result<PathBuf> {
+    let qr_path = env::temp_dir().join(format!("ncaptura-qr-{}.png", process::id()));
+    let output = Command::new("qrencode")
+        .args(["-s", "8", "-m", "2", "-o"])
+        .arg(&qr_path)
+        .arg(text)
+        .output()
+        .with_context(|| missing_tool_message("qrencode"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("生成二维码失败: {}", stderr.trim());
+    }
+
+    Ok(qr_path)
+}
+
+pub(crate) fn read_clipboard_text() -> Result<String> {
+    let output = Command::new("wl-paste")
+        .args(["--no-newline", "--type", "text"])
+        .output()
+        .with_context(|| missing_tool_message("wl-paste"))?;
+
+    if !output.status.success() {
+        bail!("剪贴板中没有文本");
+    }
+
+    let text = String::from_utf8(output.stdout).context("剪贴板文本不是有效的 UTF-8")?;
+    if text.trim().is_empty() {
+        bail!("剪贴板中没有文本");
+    }
+
+    Ok(text)
+}
+
+fn respond(
+    mut stream: TcpStream,
+    request_path: &str,
+    content_type: &str,
+    data: &[u8],
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    if method != "GET" || path != request_path {
+        stream.write_all(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )?;
+        return Ok(());
+    }
+
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        data.len()
+    );
+    stream.write_all(header.as_bytes())?;
+    stream.write_all(data)?;
+    Ok(())
+}
+
+fn lan_address() -> IpAddr {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9))?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+fn share_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("{:x}", nanos ^ ((process::id() as u128) << 64))
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("mkv") => "video/x-matroska",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,390 @@
+use std::cell::{Cell, RefCell};
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::cairo;
+use gtk::gdk;
+use gtk::gdk::prelude::GdkCairoContextExt;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::{Align, Box as GtkBox, Button, Orientation, ToggleButton};
+
+const LINE_WIDTH: f64 = 4.0;
+const ARROW_HEAD_LENGTH: f64 = 18.0;
+const TEXT_SIZE: f64 = 28.0;
+
+type Point = (f64, f64);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tool {
+    Arrow,
+    Rectangle,
+    Ellipse,
+    Pen,
+    Text,
+}
+
+#[derive(Clone)]
+enum Shape {
+    Arrow { from: Point, to: Point },
+    Rectangle { from: Point, to: Point },
+    Ellipse { from: Point, to: Point },
+    Pen { points: Vec<Point> },
+    Text { at: Point, text: String },
+}
+
+#[derive(Clone)]
+struct Annotation {
+    shape: Shape,
+    color: gdk::RGBA,
+}
+
+pub(super) fn show_annotation_editor(
+    app: &adw::Application,
+    parent: &adw::ApplicationWindow,
+    screenshot: &Pixbuf,
+    on_apply: impl Fn(Pixbuf) + 'static,
+) {
+    let screenshot = screenshot.clone();
+    let annotations: Rc<RefCell<Vec<Annotation>>> = Rc::new(RefCell::new(Vec::new()));
+    let in_progress: Rc<RefCell<Option<Annotation>>> = Rc::new(RefCell::new(None));
+    let tool = Rc::new(Cell::new(Tool::Arrow));
+
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .transient_for(parent)
+        .modal(true)
+        .title("Edit Screenshot")
+        .default_width(900)
+        .default_height(640)
+        .build();
+
+    let header = adw::HeaderBar::new();
+    let cancel_button = Button::with_label("Cancel");
+    let apply_button = Button::with_label("Apply");
+    apply_button.add_css_class("suggested-action");
+    header.pack_start(&cancel_button);
+    header.pack_end(&apply_button);
+
+    let toolbar = GtkBox::new(Orientation::Horizontal, 12);
+    toolbar.set_halign(Align::Center);
+    toolbar.set_margin_top(12);
+    toolbar.set_margin_bottom(12);
+
+    let tool_row = GtkBox::new(Orientation::Horizontal, 0);
+    tool_row.add_css_class("linked");
+    let tool_buttons = [
+        (Tool::Arrow, "Arrow"),
+        (Tool::Rectangle, "Rectangle"),
+        (Tool::Ellipse, "Ellipse"),
+        (Tool::Pen, "Pen"),
+        (Tool::Text, "Text"),
+    ]
+    .map(|(kind, label)| {
+        let button = ToggleButton::with_label(label);
+        {
+            let tool = tool.clone();
+            button.connect_toggled(move |button| {
+                if button.is_active() {
+                    tool.set(kind);
+                }
+            });
+        }
+        tool_row.append(&button);
+        button
+    });
+    for button in &tool_buttons[1..] {
+        button.set_group(Some(&tool_buttons[0]));
+    }
+    tool_buttons[0].set_active(true);
+
+    let text_entry = gtk::Entry::new();
+    text_entry.set_placeholder_text(Some("Text to place"));
+    text_entry.set_width_chars(16);
+
+    let color_button = gtk::ColorButton::with_rgba(&gdk::RGBA::new(0.9, 0.22, 0.21, 1.0));
+    color_button.set_tooltip_text(Some("Color"));
+
+    let undo_button = Button::from_icon_name("edit-undo-symbolic");
+    undo_button.set_tooltip_text(Some("Undo"));
+
+    toolbar.append(&tool_row);
+    toolbar.append(&text_entry);
+    toolbar.append(&color_button);
+    toolbar.append(&undo_button);
+
+    let canvas = gtk::DrawingArea::new();
+    canvas.set_hexpand(true);
+    canvas.set_vexpand(true);
+    canvas.set_margin_start(12);
+    canvas.set_margin_end(12);
+    canvas.set_margin_bottom(12);
+    {
+        let screenshot = screenshot.clone();
+        let annotations = annotations.clone();
+        let in_progress = in_progress.clone();
+        canvas.set_draw_func(move |_, cr, width, height| {
+            let Some((scale, offset_x, offset_y)) = view_transform(&screenshot, width, height)
+            else {
+                return;
+            };
+
+            cr.save().ok();
+            cr.translate(offset_x, offset_y);
+            cr.scale(scale, scale);
+            cr.set_source_pixbuf(&screenshot, 0.0, 0.0);
+            let _ = cr.paint();
+            for annotation in annotations.borrow().iter() {
+                draw_annotation(cr, annotation);
+            }
+            if let Some(annotation) = in_progress.borrow().as_ref() {
+                draw_annotation(cr, annotation);
+            }
+            cr.restore().ok();
+        });
+    }
+
+    let to_image_point = {
+        let screenshot = screenshot.clone();
+        let canvas = canvas.clone();
+        move |x: f64, y: f64| -> Option<Point> {
+            let (scale, offset_x, offset_y) =
+                view_transform(&screenshot, canvas.width(), canvas.height())?;
+            Some(((x - offset_x) / scale, (y - offset_y) / scale))
+        }
+    };
+
+    let drag = gtk::GestureDrag::new();
+    {
+        let annotations = annotations.clone();
+        let in_progress = in_progress.clone();
+        let tool = tool.clone();
+        let text_entry = text_entry.clone();
+        let color_button = color_button.clone();
+        let canvas = canvas.clone();
+        let to_image_point = to_image_point.clone();
+        drag.connect_drag_begin(move |_, x, y| {
+            let Some(point) = to_image_point(x, y) else {
+                return;
+            };
+            let color = color_button.rgba();
+
+            let shape = match tool.get() {
+                Tool::Text => {
+                    let text = text_entry.text().to_string();
+                    if !text.is_empty() {
+                        annotations.borrow_mut().push(Annotation {
+                            shape: Shape::Text { at: point, text },
+                            color,
+                        });
+                        canvas.queue_draw();
+                    }
+                    return;
+                }
+                Tool::Pen => Shape::Pen {
+                    points: vec![point],
+                },
+                Tool::Arrow => Shape::Arrow {
+                    from: point,
+                    to: point,
+                },
+                Tool::Rectangle => Shape::Rectangle {
+                    from: point,
+                    to: point,
+                },
+                Tool::Ellipse => Shape::Ellipse {
+                    from: point,
+                    to: point,
+                },
+            };
+            *in_progress.borrow_mut() = Some(Annotation { shape, color });
+        });
+    }
+    {
+        let in_progress = in_progress.clone();
+        let canvas = canvas.clone();
+        drag.connect_drag_update(move |gesture, offset_x, offset_y| {
+            let Some((start_x, start_y)) = gesture.start_point() else {
+                return;
+            };
+            let Some(point) = to_image_point(start_x + offset_x, start_y + offset_y) else {
+                return;
+            };
+
+            if let Some(annotation) = in_progress.borrow_mut().as_mut() {
+                match &mut annotation.shape {
+                    Shape::Pen { points } => points.push(point),
+                    Shape::Arrow { to, .. }
+                    | Shape::Rectangle { to, .. }
+                    | Shape::Ellipse { to, .. } => *to = point,
+                    Shape::Text { .. } => {}
+                }
+            }
+            canvas.queue_draw();
+        });
+    }
+    {
+        let annotations = annotations.clone();
+        let in_progress = in_progress.clone();
+        let canvas = canvas.clone();
+        drag.connect_drag_end(move |_, _, _| {
+            if let Some(annotation) = in_progress.borrow_mut().take() {
+                annotations.borrow_mut().push(annotation);
+            }
+            canvas.queue_draw();
+        });
+    }
+    canvas.add_controller(drag);
+
+    {
+        let annotations = annotations.clone();
+        let canvas = canvas.clone();
+        undo_button.connect_clicked(move |_| {
+            annotations.borrow_mut().pop();
+            canvas.queue_draw();
+        });
+    }
+
+    {
+        let window = window.clone();
+        cancel_button.connect_clicked(move |_| {
+            window.close();
+        });
+    }
+
+    {
+        let window = window.clone();
+        let annotations = annotations.clone();
+        apply_button.connect_clicked(move |_| {
+            match render_annotations(&screenshot, &annotations.borrow()) {
+                Some(edited) => on_apply(edited),
+                None => eprintln!("无法合成标注后的截图"),
+            }
+            window.close();
+        });
+    }
+
+    let key_controller = gtk::EventControllerKey::new();
+    {
+        let window = window.clone();
+        key_controller.connect_key_pressed(move |_, key, _, _| {
+            if key == gdk::Key::Escape {
+                window.close();
+                return gtk::glib::Propagation::Stop;
+            }
+
+            gtk::glib::Propagation::Proceed
+        });
+    }
+    window.add_controller(key_controller);
+
+    let root = GtkBox::new(Orientation::Vertical, 0);
+    root.append(&header);
+    root.append(&toolbar);
+    root.append(&canvas);
+    window.set_content(Some(&root));
+    window.present();
+}
+
+pub(super) fn view_transform(
+    screenshot: &Pixbuf,
+    width: i32,
+    height: i32,
+) -> Option<(f64, f64, f64)> {
+    let source_width = screenshot.width() as f64;
+    let source_height = screenshot.height() as f64;
+    if source_width <= 0.0 || source_height <= 0.0 || width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let scale = f64::min(width as f64 / source_width, height as f64 / source_height);
+    let offset_x = (width as f64 - source_width * scale) / 2.0;
+    let offset_y = (height as f64 - source_height * scale) / 2.0;
+    Some((scale, offset_x, offset_y))
+}
+
+fn render_annotations(screenshot: &Pixbuf, annotations: &[Annotation]) -> Option<Pixbuf> {
+    let width = screenshot.width();
+    let height = screenshot.height();
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).ok()?;
+
+    {
+        let cr = cairo::Context::new(&surface).ok()?;
+        cr.set_source_pixbuf(screenshot, 0.0, 0.0);
+        cr.paint().ok()?;
+        for annotation in annotations {
+            draw_annotation(&cr, annotation);
+        }
+    }
+    surface.flush();
+
+    gdk::pixbuf_get_from_surface(&surface, 0, 0, width, height)
+}
+
+fn draw_annotation(cr: &cairo::Context, annotation: &Annotation) {
+    let color = annotation.color;
+    cr.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        color.alpha() as f64,
+    );
+    cr.set_line_width(LINE_WIDTH);
+    cr.set_line_cap(cairo::LineCap::Round);
+    cr.set_line_join(cairo::LineJoin::Round);
+
+    match &annotation.shape {
+        Shape::Arrow { from, to } => {
+            cr.move_to(from.0, from.1);
+            cr.line_to(to.0, to.1);
+            let angle = (to.1 - from.1).atan2(to.0 - from.0);
+            for side in [-1.0, 1.0] {
+                let head_angle = angle + PI - side * PI / 7.0;
+                cr.move_to(to.0, to.1);
+                cr.line_to(
+                    to.0 + ARROW_HEAD_LENGTH * head_angle.cos(),
+                    to.1 + ARROW_HEAD_LENGTH * head_angle.sin(),
+                );
+            }
+            let _ = cr.stroke();
+        }
+        Shape::Rectangle { from, to } => {
+            cr.rectangle(
+                from.0.min(to.0),
+                from.1.min(to.1),
+                (to.0 - from.0).abs(),
+                (to.1 - from.1).abs(),
+            );
+            let _ = cr.stroke();
+        }
+        Shape::Ellipse { from, to } => {
+            let radius_x = (to.0 - from.0).abs() / 2.0;
+            let radius_y = (to.1 - from.1).abs() / 2.0;
+            if radius_x < 1.0 || radius_y < 1.0 {
+                return;
+            }
+            cr.save().ok();
+            cr.translate((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+            cr.scale(radius_x, radius_y);
+            cr.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+            cr.restore().ok();
+            let _ = cr.stroke();
+        }
+        Shape::Pen { points } => {
+            let Some((first, rest)) = points.split_first() else {
+                return;
+            };
+            cr.move_to(first.0, first.1);
+            for point in rest {
+                cr.line_to(point.0, point.1);
+            }
+            let _ = cr.stroke();
+        }
+        Shape::Text { at, text } => {
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+            cr.set_font_size(TEXT_SIZE);
+            cr.move_to(at.0, at.1);
+            let _ = cr.show_text(text);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use adw::prelude::*;
+
+use crate::config::ensure_config_file;
+use crate::deps::{VERSIONED_TOOLS, tool_version};
+use crate::launch::open_with_default_app;
+
+pub fn install_app_actions(app: &adw::Application) {
+    let preferences_action = gtk::gio::SimpleAction::new("preferences", None);
+    preferences_action.connect_activate(|_, _| {
+        let opened = ensure_config_file().and_then(|path| open_with_default_app(&path));
+        if let Err(err) = opened {
+            eprintln!("无法打开配置文件: {err:#}");
+        }
+    });
+    app.add_action(&preferences_action);
+
+    let about_action = gtk::gio::SimpleAction::new("about", None);
+    {
+        let app = app.clone();
+        about_action.connect_activate(move |_, _| {
+            show_about(&app);
+        });
+    }
+    app.add_action(&about_action);
+
+    let quit_action = gtk::gio::SimpleAction::new("quit", None);
+    {
+        let app = app.clone();
+        quit_action.connect_activate(move |_, _| {
+            app.quit();
+        });
+    }
+    app.add_action(&quit_action);
+    app.set_accels_for_action("app.quit", &["<Control>q"]);
+    app.set_accels_for_action("app.preferences", &["<Control>comma"]);
+    app.set_accels_for_action("win.show-help-overlay", &["<Control>question"]);
+}
+
+fn show_about(app: &adw::Application) {
+    let app = app.clone();
+    gtk::glib::spawn_future_local(async move {
+        let versions = gtk::gio::spawn_blocking(|| {
+            VERSIONED_TOOLS
+                .iter()
+                .map(|tool| {
+                    let version = tool_version(tool).unwrap_or_else(|| "not detected".to_string());
+                    format!("{tool} {version}")
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+
+        let dialog = gtk::AboutDialog::builder()
+            .program_name("NCaptura")
+            .logo_icon_name("camera-photo-symbolic")
+            .version(env!("CARGO_PKG_VERSION"))
+            .comments("Screenshots and screen recordings for Wayland compositors")
+            .license_type(gtk::License::Custom)
+            .system_information(versions.join("\n"))
+            .modal(true)
+            .build();
+        if let Some(parent) = app.active_window() {
+            dialog.set_transient_for(Some(&parent));
+        }
+        dialog.present();
+    });
+}
@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use gtk::DrawingArea;
+use gtk::gdk;
+use gtk::glib::{self, ControlFlow, SourceId};
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+use crate::capture;
+use crate::ui::input_events::{INPUT_DIR, InputWatch, watch_key_events};
+use crate::ui::style::install_app_css;
+
+const BTN_LEFT: u16 = 0x110;
+const BTN_MIDDLE: u16 = 0x112;
+const RIPPLE_DURATION: Duration = Duration::from_millis(450);
+const RIPPLE_RADIUS: f64 = 36.0;
+const RIPPLE_FRAME_INTERVAL: Duration = Duration::from_millis(16);
+const UNKNOWN_POSITION_INSET: f64 = 64.0;
+
+struct Ripple {
+    x: f64,
+    y: f64,
+    started: Instant,
+}
+
+struct OverlaySurface {
+    window: adw::ApplicationWindow,
+    area: DrawingArea,
+    geometry: gdk::Rectangle,
+    ripples: Rc<RefCell<Vec<Ripple>>>,
+}
+
+pub(super) struct ClickOverlay {
+    windows: Vec<adw::ApplicationWindow>,
+    _input_watch: InputWatch,
+    frame_source: Option<SourceId>,
+}
+
+impl Drop for ClickOverlay {
+    fn drop(&mut self) {
+        if let Some(source) = self.frame_source.take() {
+            source.remove();
+        }
+        for window in &self.windows {
+            window.destroy();
+        }
+    }
+}
+
+pub(super) fn show_click_overlay(app: &adw::Application) -> Option<ClickOverlay> {
+    if !gtk4_layer_shell::is_supported() {
+        eprintln!("当前合成器不支持 layer-shell，无法显示点击高亮");
+        return None;
+    }
+
+    let surfaces: Rc<RefCell<Vec<OverlaySurface>>> = Rc::new(RefCell::new(Vec::new()));
+    let input_watch = {
+        let surfaces = surfaces.clone();
+        watch_key_events(move |event| {
+            if (BTN_LEFT..=BTN_MIDDLE).contains(&event.code) && event.value == 1 {
+                add_ripple(&surfaces.borrow());
+            }
+        })
+    };
+    let Some(input_watch) = input_watch else {
+        eprintln!("无法读取 {INPUT_DIR} 下的输入设备，点击高亮需要当前用户属于 input 组");
+        return None;
+    };
+
+    install_app_css();
+    let display = gdk::Display::default()?;
+    let monitors = display.monitors();
+    *surfaces.borrow_mut() = (0..monitors.n_items())
+        .filter_map(|index| monitors.item(index)?.downcast::<gdk::Monitor>().ok())
+        .map(|monitor| build_overlay_surface(app, &monitor))
+        .collect();
+
+    let frame_source = {
+        let surfaces = surfaces.clone();
+        glib::timeout_add_local(RIPPLE_FRAME_INTERVAL, move || {
+            for surface in surfaces.borrow().iter() {
+                let mut ripples = surface.ripples.borrow_mut();
+                if ripples.is_empty() {
+                    continue;
+                }
+                ripples.retain(|ripple| ripple.started.elapsed() < RIPPLE_DURATION);
+                surface.area.queue_draw();
+            }
+            ControlFlow::Continue
+        })
+    };
+
+    Some(ClickOverlay {
+        windows: surfaces
+            .borrow()
+            .iter()
+            .map(|surface| surface.window.clone())
+            .collect(),
+        _input_watch: input_watch,
+        frame_source: Some(frame_source),
+    })
+}
+
+fn build_overlay_surface(app: &adw::Application, monitor: &gdk::Monitor) -> OverlaySurface {
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Click Highlight")
+        .build();
+    window.set_decorated(false);
+    window.add_css_class("click-overlay");
+    window.init_layer_shell();
+    window.set_layer(Layer::Overlay);
+    window.set_monitor(Some(monitor));
+    for edge in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+        window.set_anchor(edge, true);
+    }
+    window.set_exclusive_zone(-1);
+    window.set_keyboard_mode(KeyboardMode::None);
+    window.set_namespace(Some("ncaptura-click-overlay"));
+    pass_input_through(&window);
+
+    let ripples: Rc<RefCell<Vec<Ripple>>> = Rc::new(RefCell::new(Vec::new()));
+    let area = DrawingArea::new();
+    {
+        let ripples = ripples.clone();
+        area.set_draw_func(move |_, cr, _, _| {
+            for ripple in ripples.borrow().iter() {
+                let progress = (ripple.started.elapsed().as_secs_f64()
+                    / RIPPLE_DURATION.as_secs_f64())
+                .min(1.0);
+                let radius = RIPPLE_RADIUS * (0.3 + 0.7 * progress);
+                let alpha = 1.0 - progress;
+                cr.arc(ripple.x, ripple.y, radius, 0.0, std::f64::consts::TAU);
+                cr.set_source_rgba(0.96, 0.26, 0.21, 0.35 * alpha);
+                let _ = cr.fill_preserve();
+                cr.set_source_rgba(0.96, 0.26, 0.21, 0.9 * alpha);
+                cr.set_line_width(3.0);
+                let _ = cr.stroke();
+            }
+        });
+    }
+    window.set_content(Some(&area));
+    window.present();
+
+    OverlaySurface {
+        window,
+        area,
+        geometry: monitor.geometry(),
+        ripples,
+    }
+}
+
+fn add_ripple(surfaces: &[OverlaySurface]) {
+    let started = Instant::now();
+    let Some((x, y)) = capture::cursor_position() else {
+        for surface in surfaces {
+            surface.ripples.borrow_mut().push(Ripple {
+                x: f64::from(surface.geometry.width()) - UNKNOWN_POSITION_INSET,
+                y: f64::from(surface.geometry.height()) - UNKNOWN_POSITION_INSET,
+                started,
+            });
+        }
+        return;
+    };
+
+    let Some(surface) = surfaces.iter().find(|surface| {
+        let geometry = surface.geometry;
+        x >= f64::from(geometry.x())
+            && y >= f64::from(geometry.y())
+            && x < f64::from(geometry.x() + geometry.width())
+            && y < f64::from(geometry.y() + geometry.height())
+    }) else {
+        return;
+    };
+    surface.ripples.borrow_mut().push(Ripple {
+        x: x - f64::from(surface.geometry.x()),
+        y: y - f64::from(surface.geometry.y()),
+        started,
+    });
+}
+
+pub(super) fn pass_input_through(window: &adw::ApplicationWindow) {
+    window.connect_realize(|window| {
+        if let Some(surface) = window.surface() {
+            surface.set_input_region(&gtk::cairo::Region::create());
+        }
+    });
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use gtk::Label;
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+
+use crate::ui::style::install_app_css;
+
+const OVERLAY_TEARDOWN_DELAY: Duration = Duration::from_millis(150);
+
+pub fn show_countdown_overlay(
+    app: &adw::Application,
+    seconds: u32,
+    on_finished: impl FnOnce() + 'static,
+) {
+    install_app_css();
+
+    let overlay = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Countdown")
+        .default_width(120)
+        .default_height(120)
+        .resizable(false)
+        .build();
+    overlay.set_decorated(false);
+    overlay.set_size_request(120, 120);
+    overlay.add_css_class("countdown-overlay");
+
+    if gtk4_layer_shell::is_supported() {
+        overlay.init_layer_shell();
+        overlay.set_layer(Layer::Overlay);
+        overlay.set_keyboard_mode(KeyboardMode::None);
+        overlay.set_namespace(Some("ncaptura-countdown"));
+    }
+
+    let label = Label::new(Some(&seconds.to_string()));
+    label.add_css_class("countdown-label");
+    overlay.set_content(Some(&label));
+
+    let mut remaining = seconds;
+    let mut on_finished = Some(on_finished);
+    {
+        let overlay = overlay.clone();
+        gtk::glib::timeout_add_local(Duration::from_secs(1), move || {
+            remaining = remaining.saturating_sub(1);
+            if remaining > 0 {
+                label.set_text(&remaining.to_string());
+                return gtk::glib::ControlFlow::Continue;
+            }
+
+            overlay.destroy();
+            if let Some(on_finished) = on_finished.take() {
+                gtk::glib::timeout_add_local_once(OVERLAY_TEARDOWN_DELAY, on_finished);
+            }
+            gtk::glib::ControlFlow::Break
+        });
+    }
+
+    overlay.present();
+}
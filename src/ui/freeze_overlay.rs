@@ -0,0 +1,308 @@
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::rc::Rc;
+use std::time::Duration;
+
+use adw::prelude::*;
+use anyhow::{Context, Result, anyhow};
+use gtk::cairo;
+use gtk::gdk;
+use gtk::gdk::prelude::GdkCairoContextExt;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+use crate::capture::{pick_region_geometry, take_frozen_frame};
+
+const MIN_SELECTION_SIZE: f64 = 4.0;
+const BACKDROP_SETTLE: Duration = Duration::from_millis(100);
+
+type Selection = (f64, f64, f64, f64);
+
+pub fn load_frozen_frame(show_pointer: bool) -> Result<Pixbuf> {
+    let frame_path = take_frozen_frame(show_pointer)?;
+    let frame = Pixbuf::from_file(&frame_path);
+    let _ = fs::remove_file(&frame_path);
+    frame.context("无法加载冻结画面")
+}
+
+pub fn select_region_on_frozen_frame(
+    app: &adw::Application,
+    on_selected: impl FnOnce(Result<String>) + 'static,
+) {
+    let frame = match load_frozen_frame(false) {
+        Ok(frame) => frame,
+        Err(err) => {
+            on_selected(Err(err));
+            return;
+        }
+    };
+
+    let backdrop = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Frozen Screen")
+        .build();
+    backdrop.set_decorated(false);
+
+    if gtk4_layer_shell::is_supported() {
+        backdrop.init_layer_shell();
+        backdrop.set_layer(Layer::Overlay);
+        for edge in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+            backdrop.set_anchor(edge, true);
+        }
+        backdrop.set_exclusive_zone(-1);
+        backdrop.set_keyboard_mode(KeyboardMode::None);
+        backdrop.set_namespace(Some("ncaptura-freeze"));
+    } else {
+        backdrop.fullscreen();
+    }
+
+    let area = gtk::DrawingArea::new();
+    area.set_hexpand(true);
+    area.set_vexpand(true);
+    area.set_draw_func(move |_, cr, width, height| {
+        draw_frozen_frame(cr, &frame, width, height);
+    });
+    backdrop.set_content(Some(&area));
+    backdrop.present();
+
+    gtk::glib::spawn_future_local(async move {
+        gtk::glib::timeout_future(BACKDROP_SETTLE).await;
+        let result = gtk::gio::spawn_blocking(|| pick_region_geometry().map_err(Into::into))
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("区域选择线程异常退出")));
+        backdrop.destroy();
+        on_selected(result);
+    });
+}
+
+pub fn show_frozen_countdown(
+    app: &adw::Application,
+    frame: &Pixbuf,
+    seconds: u32,
+    select_region: bool,
+    on_finished: impl FnOnce(Option<Pixbuf>) + 'static,
+) {
+    let overlay = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Frozen Screen")
+        .build();
+    overlay.set_decorated(false);
+
+    if gtk4_layer_shell::is_supported() {
+        overlay.init_layer_shell();
+        overlay.set_layer(Layer::Overlay);
+        for edge in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+            overlay.set_anchor(edge, true);
+        }
+        overlay.set_exclusive_zone(-1);
+        overlay.set_keyboard_mode(KeyboardMode::Exclusive);
+        overlay.set_namespace(Some("ncaptura-freeze"));
+    } else {
+        overlay.fullscreen();
+    }
+
+    let remaining = Rc::new(Cell::new(seconds));
+    let selection: Rc<Cell<Option<Selection>>> = Rc::new(Cell::new(None));
+    let countdown_source: Rc<RefCell<Option<gtk::glib::SourceId>>> = Rc::new(RefCell::new(None));
+    let on_finished = Rc::new(RefCell::new(Some(on_finished)));
+
+    let area = gtk::DrawingArea::new();
+    area.set_hexpand(true);
+    area.set_vexpand(true);
+    {
+        let frame = frame.clone();
+        let remaining = remaining.clone();
+        let selection = selection.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            draw_frozen_frame(cr, &frame, width, height);
+
+            if let Some((x, y, w, h)) = selection.get() {
+                cr.set_source_rgba(0.21, 0.52, 0.89, 0.25);
+                cr.rectangle(x, y, w, h);
+                let _ = cr.fill_preserve();
+                cr.set_source_rgba(0.21, 0.52, 0.89, 1.0);
+                cr.set_line_width(2.0);
+                let _ = cr.stroke();
+            }
+
+            let left = remaining.get();
+            if left > 0 {
+                draw_countdown(cr, &left.to_string(), width, height);
+            }
+        });
+    }
+    overlay.set_content(Some(&area));
+
+    let finish: Rc<dyn Fn(bool)> = Rc::new({
+        let overlay = overlay.clone();
+        let area = area.clone();
+        let frame = frame.clone();
+        let selection = selection.clone();
+        let countdown_source = countdown_source.clone();
+        let on_finished = on_finished.clone();
+        move |capture| {
+            let Some(on_finished) = on_finished.borrow_mut().take() else {
+                return;
+            };
+            if let Some(source) = countdown_source.borrow_mut().take() {
+                source.remove();
+            }
+
+            let captured = if capture {
+                crop_frame(&frame, selection.get(), area.width(), area.height())
+            } else {
+                None
+            };
+            overlay.destroy();
+            on_finished(captured);
+        }
+    });
+
+    if select_region {
+        let drag = gtk::GestureDrag::new();
+        {
+            let selection = selection.clone();
+            let area = area.clone();
+            drag.connect_drag_begin(move |_, x, y| {
+                selection.set(Some((x, y, 0.0, 0.0)));
+                area.queue_draw();
+            });
+        }
+        {
+            let selection = selection.clone();
+            let area = area.clone();
+            drag.connect_drag_update(move |gesture, offset_x, offset_y| {
+                if let Some((start_x, start_y)) = gesture.start_point() {
+                    selection.set(Some(normalize_selection(
+                        start_x, start_y, offset_x, offset_y,
+                    )));
+                    area.queue_draw();
+                }
+            });
+        }
+        {
+            let selection = selection.clone();
+            let remaining = remaining.clone();
+            let finish = finish.clone();
+            drag.connect_drag_end(move |_, _, _| {
+                let usable = selection
+                    .get()
+                    .is_some_and(|(_, _, w, h)| w >= MIN_SELECTION_SIZE && h >= MIN_SELECTION_SIZE);
+                if !usable {
+                    selection.set(None);
+                    return;
+                }
+                if remaining.get() == 0 {
+                    finish(true);
+                }
+            });
+        }
+        area.add_controller(drag);
+    }
+
+    let key_controller = gtk::EventControllerKey::new();
+    {
+        let finish = finish.clone();
+        key_controller.connect_key_pressed(move |_, key, _, _| {
+            if key == gdk::Key::Escape {
+                finish(false);
+                return gtk::glib::Propagation::Stop;
+            }
+
+            gtk::glib::Propagation::Proceed
+        });
+    }
+    overlay.add_controller(key_controller);
+
+    if seconds > 0 {
+        let remaining = remaining.clone();
+        let selection = selection.clone();
+        let area = area.clone();
+        let countdown_handle = countdown_source.clone();
+        let finish = finish.clone();
+        let source = gtk::glib::timeout_add_local(Duration::from_secs(1), move || {
+            let left = remaining.get().saturating_sub(1);
+            remaining.set(left);
+            area.queue_draw();
+            if left > 0 {
+                return gtk::glib::ControlFlow::Continue;
+            }
+
+            countdown_handle.borrow_mut().take();
+            if !select_region || selection.get().is_some() {
+                finish(true);
+            }
+            gtk::glib::ControlFlow::Break
+        });
+        *countdown_source.borrow_mut() = Some(source);
+    } else if !select_region {
+        let finish = finish.clone();
+        gtk::glib::idle_add_local_once(move || finish(true));
+    }
+
+    overlay.present();
+}
+
+fn draw_frozen_frame(cr: &cairo::Context, frame: &Pixbuf, width: i32, height: i32) {
+    if frame.width() <= 0 || frame.height() <= 0 {
+        return;
+    }
+
+    cr.save().ok();
+    cr.scale(
+        width as f64 / frame.width() as f64,
+        height as f64 / frame.height() as f64,
+    );
+    cr.set_source_pixbuf(frame, 0.0, 0.0);
+    let _ = cr.paint();
+    cr.restore().ok();
+}
+
+fn draw_countdown(cr: &cairo::Context, text: &str, width: i32, height: i32) {
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(160.0);
+    let Ok(extents) = cr.text_extents(text) else {
+        return;
+    };
+
+    let x = (width as f64 - extents.width()) / 2.0 - extents.x_bearing();
+    let y = (height as f64 - extents.height()) / 2.0 - extents.y_bearing();
+    cr.move_to(x, y);
+    cr.text_path(text);
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+    let _ = cr.fill_preserve();
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+    cr.set_line_width(4.0);
+    let _ = cr.stroke();
+}
+
+fn normalize_selection(start_x: f64, start_y: f64, offset_x: f64, offset_y: f64) -> Selection {
+    let x = start_x.min(start_x + offset_x);
+    let y = start_y.min(start_y + offset_y);
+    (x, y, offset_x.abs(), offset_y.abs())
+}
+
+fn crop_frame(
+    frame: &Pixbuf,
+    selection: Option<Selection>,
+    area_width: i32,
+    area_height: i32,
+) -> Option<Pixbuf> {
+    let Some((x, y, w, h)) = selection else {
+        return Some(frame.clone());
+    };
+    if area_width <= 0 || area_height <= 0 {
+        return None;
+    }
+
+    let scale_x = frame.width() as f64 / area_width as f64;
+    let scale_y = frame.height() as f64 / area_height as f64;
+    let left = ((x * scale_x).round() as i32).clamp(0, frame.width() - 1);
+    let top = ((y * scale_y).round() as i32).clamp(0, frame.height() - 1);
+    let right = (((x + w) * scale_x).round() as i32).clamp(left + 1, frame.width());
+    let bottom = (((y + h) * scale_y).round() as i32).clamp(top + 1, frame.height());
+
+    frame
+        .new_subpixbuf(left, top, right - left, bottom - top)
+        .copy()
+}
@@ -0,0 +1,181 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use chrono::{DateTime, Local};
+use gtk::gdk;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::{Align, Box as GtkBox, Button, FlowBox, Image, Label, Orientation, SelectionMode};
+
+use crate::capture::{RecentCapture, recent_captures};
+use crate::imaging::{self, load_image};
+use crate::ui::save_dialog::build_save_dialog;
+
+const RECENT_CAPTURE_LIMIT: usize = 24;
+const THUMBNAIL_WIDTH: i32 = 160;
+const THUMBNAIL_HEIGHT: i32 = 100;
+
+pub(super) fn show_recent_captures(app: &adw::Application, parent: &adw::ApplicationWindow) {
+    let captures = match recent_captures(RECENT_CAPTURE_LIMIT) {
+        Ok(captures) => Rc::new(captures),
+        Err(err) => {
+            eprintln!("读取最近截图失败: {err:#}");
+            return;
+        }
+    };
+
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .transient_for(parent)
+        .title("Recent Captures")
+        .default_width(720)
+        .default_height(480)
+        .build();
+
+    let header = adw::HeaderBar::new();
+    let copy_button = Button::with_label("Copy to Clipboard");
+    copy_button.set_sensitive(false);
+    header.pack_start(&copy_button);
+
+    let flow_box = FlowBox::new();
+    flow_box.set_selection_mode(SelectionMode::Single);
+    flow_box.set_valign(Align::Start);
+    flow_box.set_homogeneous(true);
+    flow_box.set_max_children_per_line(4);
+    flow_box.set_row_spacing(12);
+    flow_box.set_column_spacing(12);
+    flow_box.set_margin_top(16);
+    flow_box.set_margin_bottom(16);
+    flow_box.set_margin_start(16);
+    flow_box.set_margin_end(16);
+
+    for capture in captures.iter() {
+        flow_box.insert(&capture_tile(capture), -1);
+    }
+
+    {
+        let captures = captures.clone();
+        let copy_button = copy_button.clone();
+        flow_box.connect_selected_children_changed(move |flow_box| {
+            let selected = selected_capture(flow_box, &captures);
+            copy_button.set_sensitive(selected.is_some());
+        });
+    }
+
+    {
+        let captures = captures.clone();
+        let flow_box = flow_box.clone();
+        copy_button.connect_clicked(move |button| {
+            if let Some(capture) = selected_capture(&flow_box, &captures) {
+                copy_capture(button, capture);
+            }
+        });
+    }
+
+    {
+        let app = app.clone();
+        let captures = captures.clone();
+        flow_box.connect_child_activated(move |flow_box, child| {
+            let Some(capture) = usize::try_from(child.index())
+                .ok()
+                .and_then(|index| captures.get(index))
+            else {
+                return;
+            };
+
+            if capture.is_recording() {
+                copy_capture(flow_box, capture);
+            } else {
+                open_capture(&app, capture.path.clone());
+            }
+        });
+    }
+
+    let root = GtkBox::new(Orientation::Vertical, 0);
+    root.append(&header);
+    if captures.is_empty() {
+        let status = adw::StatusPage::builder()
+            .icon_name("image-missing-symbolic")
+            .title("No Captures Yet")
+            .vexpand(true)
+            .build();
+        root.append(&status);
+    } else {
+        let scroller = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&flow_box)
+            .build();
+        root.append(&scroller);
+    }
+
+    window.set_content(Some(&root));
+    window.present();
+}
+
+fn capture_tile(capture: &RecentCapture) -> GtkBox {
+    let tile = GtkBox::new(Orientation::Vertical, 6);
+
+    let preview = load_image(&capture.path)
+        .and_then(|image| imaging::thumbnail(&image, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT));
+    let thumbnail = match preview {
+        Ok(pixbuf) => Image::from_paintable(Some(&gdk::Texture::for_pixbuf(&pixbuf))),
+        Err(_) if capture.is_recording() => Image::from_icon_name("video-x-generic-symbolic"),
+        Err(_) => Image::from_icon_name("image-missing-symbolic"),
+    };
+    thumbnail.set_size_request(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+    thumbnail.set_pixel_size(48);
+    tile.append(&thumbnail);
+
+    let modified: DateTime<Local> = capture.modified.into();
+    let label = Label::new(Some(&modified.format("%Y-%m-%d %H:%M:%S").to_string()));
+    label.add_css_class("caption");
+    label.add_css_class("dim-label");
+    tile.append(&label);
+
+    let file_name = capture
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    tile.set_tooltip_text(Some(&file_name));
+    tile
+}
+
+fn selected_capture<'a>(
+    flow_box: &FlowBox,
+    captures: &'a [RecentCapture],
+) -> Option<&'a RecentCapture> {
+    let child = flow_box.selected_children().into_iter().next()?;
+    captures.get(usize::try_from(child.index()).ok()?)
+}
+
+fn copy_capture(widget: &impl IsA<gtk::Widget>, capture: &RecentCapture) {
+    let clipboard = widget.clipboard();
+    if !capture.is_recording()
+        && let Ok(pixbuf) = Pixbuf::from_file(&capture.path)
+    {
+        clipboard.set_texture(&gdk::Texture::for_pixbuf(&pixbuf));
+        return;
+    }
+
+    clipboard.set_text(&capture.path.to_string_lossy());
+}
+
+fn open_capture(app: &adw::Application, path: PathBuf) {
+    let pixbuf = match Pixbuf::from_file(&path) {
+        Ok(pixbuf) => pixbuf,
+        Err(err) => {
+            eprintln!("无法加载截图: {err}");
+            return;
+        }
+    };
+
+    let folder = path.parent().map(PathBuf::from).unwrap_or_default();
+    let filename = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    build_save_dialog(app, &pixbuf, &folder, &filename);
+}
@@ -0,0 +1,518 @@
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use gtk::glib::{ControlFlow, SourceId};
+use gtk::{Align, Box as GtkBox, Button, Image, Label, Orientation};
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+
+use crate::capture::{self, CaptureError};
+use crate::config::config;
+use crate::ui::hud_placement::{HudMargins, HudPosition, place_hud};
+use crate::ui::preferences::{DialogPreferences, SavedHudMargins};
+use crate::ui::recording_overlays::{ActiveOverlays, RecordingOverlays};
+use crate::ui::sleep_watch::{SleepWatch, watch_system_sleep};
+use crate::ui::style::install_app_css;
+
+const SINK_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const ALIVE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const HUD_WIDTH: i32 = 300;
+const HUD_HEIGHT: i32 = 50;
+const COLLAPSED_HUD_SIZE: i32 = 36;
+
+pub(super) trait RecordingControl {
+    fn pause(&self) -> Result<(), CaptureError>;
+    fn resume(&self) -> Result<(), CaptureError>;
+    fn stop(&self) -> impl FnOnce() -> Result<PathBuf, CaptureError> + Send + 'static;
+    fn is_alive(&self) -> bool;
+}
+
+pub(super) struct RecordingHud {
+    namespace: &'static str,
+    started_at: Instant,
+    started_text: Option<String>,
+    max_duration: Option<Duration>,
+    paused_duration: Duration,
+    paused_since: Option<Instant>,
+    audio_device: Option<String>,
+    position: HudPosition,
+    overlays: RecordingOverlays,
+}
+
+impl RecordingHud {
+    pub(super) fn new(namespace: &'static str) -> Self {
+        Self {
+            namespace,
+            started_at: Instant::now(),
+            started_text: None,
+            max_duration: None,
+            paused_duration: Duration::ZERO,
+            paused_since: None,
+            audio_device: None,
+            position: config().hud_position,
+            overlays: RecordingOverlays::default(),
+        }
+    }
+
+    pub(super) fn started_at(mut self, started_at: Instant, started_text: String) -> Self {
+        self.started_at = started_at;
+        self.started_text = Some(started_text);
+        self
+    }
+
+    pub(super) fn max_duration(mut self, max_duration: Option<Duration>) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    pub(super) fn paused(
+        mut self,
+        paused_duration: Duration,
+        paused_since: Option<Instant>,
+    ) -> Self {
+        self.paused_duration = paused_duration;
+        self.paused_since = paused_since;
+        self
+    }
+
+    pub(super) fn audio_device(mut self, audio_device: Option<String>) -> Self {
+        self.audio_device = audio_device;
+        self
+    }
+
+    pub(super) fn position(mut self, position: HudPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub(super) fn overlays(mut self, overlays: RecordingOverlays) -> Self {
+        self.overlays = overlays;
+        self
+    }
+
+    pub(super) fn present(
+        self,
+        app: &adw::Application,
+        control: impl RecordingControl + 'static,
+        on_finished: impl Fn() + 'static,
+    ) {
+        install_app_css();
+
+        let hud = adw::ApplicationWindow::builder()
+            .application(app)
+            .title("Recording")
+            .default_width(HUD_WIDTH)
+            .default_height(HUD_HEIGHT)
+            .resizable(false)
+            .build();
+        hud.set_decorated(false);
+        hud.set_size_request(HUD_WIDTH, HUD_HEIGHT);
+        hud.add_css_class("recording-hud");
+
+        let base_margin = config().hud_margin;
+        let margins = Rc::new(Cell::new(
+            DialogPreferences::load()
+                .hud_margins(self.position, base_margin)
+                .unwrap_or_else(|| HudMargins::uniform(base_margin)),
+        ));
+        if gtk4_layer_shell::is_supported() {
+            hud.init_layer_shell();
+            hud.set_layer(Layer::Overlay);
+            place_hud(&hud, self.position, margins.get());
+            hud.set_keyboard_mode(KeyboardMode::OnDemand);
+            hud.set_namespace(Some(self.namespace));
+        }
+
+        let row = GtkBox::new(Orientation::Horizontal, 10);
+        row.set_margin_top(4);
+        row.set_margin_bottom(4);
+        row.set_margin_start(12);
+        row.set_margin_end(12);
+        row.set_halign(Align::Fill);
+
+        let indicator = Label::new(Some("●"));
+        indicator.add_css_class("recording-indicator");
+
+        let controls = GtkBox::new(Orientation::Horizontal, 10);
+        controls.set_hexpand(true);
+
+        let timer_label = Label::new(Some("00:00:00"));
+        timer_label.add_css_class("title-4");
+        timer_label.set_hexpand(true);
+        timer_label.set_halign(Align::Start);
+
+        let audio_warning = Image::from_icon_name("dialog-warning-symbolic");
+        audio_warning.add_css_class("audio-warning");
+        audio_warning.set_tooltip_text(Some(
+            "Default audio output changed; system audio may be missing from the recording",
+        ));
+        audio_warning.set_visible(false);
+
+        let pause_button = Button::builder()
+            .icon_name("media-playback-pause-symbolic")
+            .tooltip_text("Pause/Resume")
+            .build();
+        pause_button.add_css_class("pause-record-btn");
+        if self.paused_since.is_some() {
+            indicator.add_css_class("paused");
+            pause_button.set_icon_name("media-playback-start-symbolic");
+        }
+
+        let stop_button = Button::builder()
+            .icon_name("media-record-symbolic")
+            .tooltip_text("Stop Recording")
+            .build();
+        stop_button.add_css_class("stop-record-btn");
+
+        let collapse_button = Button::builder()
+            .icon_name("pan-start-symbolic")
+            .tooltip_text("Collapse to Indicator")
+            .valign(Align::Center)
+            .build();
+        collapse_button.add_css_class("flat");
+        collapse_button.add_css_class("collapse-hud-btn");
+
+        controls.append(&timer_label);
+        controls.append(&audio_warning);
+        controls.append(&pause_button);
+        controls.append(&stop_button);
+        controls.append(&collapse_button);
+        row.append(&indicator);
+        row.append(&controls);
+        hud.set_content(Some(&row));
+
+        let set_collapsed: Rc<dyn Fn(bool)> = Rc::new({
+            let hud = hud.clone();
+            let row = row.clone();
+            let controls = controls.clone();
+            let indicator = indicator.clone();
+            move |collapsed| {
+                controls.set_visible(!collapsed);
+                if collapsed {
+                    hud.add_css_class("collapsed");
+                    row.set_halign(Align::Center);
+                    hud.set_size_request(COLLAPSED_HUD_SIZE, COLLAPSED_HUD_SIZE);
+                    hud.set_default_size(COLLAPSED_HUD_SIZE, COLLAPSED_HUD_SIZE);
+                    indicator
+                        .set_tooltip_text(Some("Click to expand; middle-click to stop recording"));
+                } else {
+                    hud.remove_css_class("collapsed");
+                    row.set_halign(Align::Fill);
+                    hud.set_size_request(HUD_WIDTH, HUD_HEIGHT);
+                    hud.set_default_size(HUD_WIDTH, HUD_HEIGHT);
+                    indicator.set_tooltip_text(None);
+                }
+            }
+        });
+
+        {
+            let set_collapsed = set_collapsed.clone();
+            collapse_button.connect_clicked(move |_| set_collapsed(true));
+        }
+
+        if gtk4_layer_shell::is_supported() {
+            let drag = gtk::GestureDrag::new();
+            let position = self.position;
+            {
+                let hud = hud.clone();
+                let margins = margins.clone();
+                drag.connect_drag_update(move |_, dx, dy| {
+                    let dragged = margins.get().dragged(position, dx, dy);
+                    margins.set(dragged);
+                    place_hud(&hud, position, dragged);
+                });
+            }
+            {
+                let margins = margins.clone();
+                drag.connect_drag_end(move |_, dx, dy| {
+                    if dx == 0.0 && dy == 0.0 {
+                        return;
+                    }
+                    let saved = SavedHudMargins {
+                        base_margin,
+                        margins: margins.get(),
+                    };
+                    DialogPreferences::load().update(|preferences| {
+                        preferences.hud_margins.insert(position, saved);
+                    });
+                });
+            }
+            row.add_controller(drag);
+
+            let reset = gtk::GestureClick::new();
+            {
+                let hud = hud.clone();
+                reset.connect_pressed(move |_, n_press, _, _| {
+                    if n_press != 2 {
+                        return;
+                    }
+                    let defaults = HudMargins::uniform(base_margin);
+                    margins.set(defaults);
+                    place_hud(&hud, position, defaults);
+                    DialogPreferences::load().update(|preferences| {
+                        preferences.hud_margins.remove(&position);
+                    });
+                });
+            }
+            row.add_controller(reset);
+        }
+
+        let control = Rc::new(control);
+        let started_at = self.started_at;
+        let max_duration = self.max_duration;
+        let paused_since: Rc<RefCell<Option<Instant>>> = Rc::new(RefCell::new(self.paused_since));
+        let paused_total = Rc::new(RefCell::new(self.paused_duration));
+        let blinking_visible = Rc::new(RefCell::new(true));
+        let closing = Rc::new(Cell::new(false));
+
+        let sources: Rc<RefCell<Vec<SourceId>>> = Rc::new(RefCell::new(Vec::new()));
+        let sleep_watch: Rc<RefCell<Option<SleepWatch>>> = Rc::new(RefCell::new(None));
+        let active_overlays: Rc<RefCell<Option<ActiveOverlays>>> =
+            Rc::new(RefCell::new(Some(self.overlays.show(app))));
+
+        let on_finished = Rc::new(on_finished);
+        let finalize: Rc<dyn Fn(bool)> = Rc::new({
+            let hud = hud.clone();
+            let control = control.clone();
+            let closing = closing.clone();
+            let sources = sources.clone();
+            let sleep_watch = sleep_watch.clone();
+            let active_overlays = active_overlays.clone();
+            let set_collapsed = set_collapsed.clone();
+            let timer_label = timer_label.clone();
+            let controls = controls.clone();
+            move |request_stop| {
+                if closing.replace(true) {
+                    return;
+                }
+
+                active_overlays.borrow_mut().take();
+                for source in sources.borrow_mut().drain(..) {
+                    source.remove();
+                }
+                sleep_watch.borrow_mut().take();
+
+                if !request_stop {
+                    hud.destroy();
+                    on_finished();
+                    return;
+                }
+
+                set_collapsed(false);
+                timer_label.set_text("Finishing…");
+                timer_label.set_tooltip_text(None);
+                controls.set_sensitive(false);
+
+                let stop = control.stop();
+                let hud = hud.clone();
+                let on_finished = on_finished.clone();
+                gtk::glib::spawn_future_local(async move {
+                    let result = gtk::gio::spawn_blocking(stop).await.unwrap_or_else(|_| {
+                        Err(CaptureError::Failed("工作线程异常退出".to_string()))
+                    });
+                    match result {
+                        Ok(path) => {
+                            eprintln!("录屏已保存: {}", path.display());
+                            if let Ok(media) = capture::probe_media(&path) {
+                                eprintln!("录屏信息: {media}");
+                            }
+                        }
+                        Err(err) => eprintln!("停止录屏失败: {err}"),
+                    }
+                    hud.destroy();
+                    on_finished();
+                });
+            }
+        });
+
+        match (&self.started_text, max_duration) {
+            (Some(started_text), Some(max_duration)) => {
+                timer_label.set_text(&format_hud_time(max_duration));
+                timer_label.set_tooltip_text(Some(&format!(
+                    "Started at {started_text}; time left before recording stops automatically"
+                )));
+            }
+            (None, Some(max_duration)) => {
+                timer_label.set_text(&format_hud_time(max_duration));
+                timer_label
+                    .set_tooltip_text(Some("Time left before recording stops automatically"));
+            }
+            (Some(started_text), None) => {
+                timer_label.set_tooltip_text(Some(&format!("Started at {started_text}")));
+            }
+            (None, None) => {}
+        }
+
+        {
+            let timer_label = timer_label.clone();
+            let paused_since = paused_since.clone();
+            let paused_total = paused_total.clone();
+            let finalize = finalize.clone();
+            let source = gtk::glib::timeout_add_local(Duration::from_secs(1), move || {
+                let now = Instant::now();
+                let extra_paused = paused_since
+                    .borrow()
+                    .map(|start| now.duration_since(start))
+                    .unwrap_or(Duration::ZERO);
+                let elapsed =
+                    now.duration_since(started_at) - *paused_total.borrow() - extra_paused;
+                let Some(max_duration) = max_duration else {
+                    timer_label.set_text(&format_hud_time(elapsed));
+                    return ControlFlow::Continue;
+                };
+
+                if elapsed >= max_duration {
+                    eprintln!("已达到录制时长上限，自动停止录屏");
+                    finalize(true);
+                    return ControlFlow::Continue;
+                }
+                timer_label.set_text(&format_hud_time(max_duration - elapsed));
+                ControlFlow::Continue
+            });
+            sources.borrow_mut().push(source);
+        }
+
+        if let Some(blink_interval) = config().hud_blink_interval() {
+            let indicator = indicator.clone();
+            let paused_since = paused_since.clone();
+            let blinking_visible = blinking_visible.clone();
+            let source = gtk::glib::timeout_add_local(blink_interval, move || {
+                if paused_since.borrow().is_some() {
+                    indicator.set_opacity(1.0);
+                    return ControlFlow::Continue;
+                }
+                let mut visible = blinking_visible.borrow_mut();
+                *visible = !*visible;
+                indicator.set_opacity(if *visible { 1.0 } else { 0.2 });
+                ControlFlow::Continue
+            });
+            sources.borrow_mut().push(source);
+        }
+
+        if let Some(audio_device) = self.audio_device {
+            let audio_warning = audio_warning.clone();
+            let source = gtk::glib::timeout_add_local(SINK_CHECK_INTERVAL, move || {
+                let changed = capture::default_system_mix_audio_device()
+                    .is_some_and(|current| current != audio_device);
+                if changed && !audio_warning.is_visible() {
+                    eprintln!("默认音频输出已变更，录屏中的系统声音可能已中断");
+                }
+                audio_warning.set_visible(changed);
+                ControlFlow::Continue
+            });
+            sources.borrow_mut().push(source);
+        }
+
+        {
+            let control = control.clone();
+            let finalize = finalize.clone();
+            let source = gtk::glib::timeout_add_local(ALIVE_CHECK_INTERVAL, move || {
+                if !control.is_alive() {
+                    finalize(false);
+                }
+                ControlFlow::Continue
+            });
+            sources.borrow_mut().push(source);
+        }
+
+        let set_paused: Rc<dyn Fn(bool)> = Rc::new({
+            let control = control.clone();
+            let paused_since = paused_since.clone();
+            let paused_total = paused_total.clone();
+            let indicator = indicator.clone();
+            let pause_button = pause_button.clone();
+            move |paused| {
+                if paused {
+                    match control.pause() {
+                        Ok(()) => {
+                            *paused_since.borrow_mut() = Some(Instant::now());
+                            indicator.add_css_class("paused");
+                            indicator.set_opacity(1.0);
+                            pause_button.set_icon_name("media-playback-start-symbolic");
+                        }
+                        Err(err) => eprintln!("暂停录屏失败: {err}"),
+                    }
+                    return;
+                }
+
+                match control.resume() {
+                    Ok(()) => {
+                        if let Some(start) = paused_since.borrow_mut().take() {
+                            *paused_total.borrow_mut() += Instant::now().duration_since(start);
+                        }
+                        indicator.remove_css_class("paused");
+                        pause_button.set_icon_name("media-playback-pause-symbolic");
+                    }
+                    Err(err) => eprintln!("恢复录屏失败: {err}"),
+                }
+            }
+        });
+
+        {
+            let paused_since = paused_since.clone();
+            let set_paused = set_paused.clone();
+            pause_button.connect_clicked(move |_| {
+                let paused = paused_since.borrow().is_some();
+                set_paused(!paused);
+            });
+        }
+
+        {
+            let paused_since = paused_since.clone();
+            let set_paused = set_paused.clone();
+            let paused_for_sleep = Rc::new(Cell::new(false));
+            *sleep_watch.borrow_mut() = watch_system_sleep(move |sleeping| {
+                let paused = paused_since.borrow().is_some();
+                if sleeping && !paused {
+                    set_paused(true);
+                    paused_for_sleep.set(paused_since.borrow().is_some());
+                } else if !sleeping && paused_for_sleep.replace(false) && paused {
+                    set_paused(false);
+                }
+            });
+        }
+
+        {
+            let finalize = finalize.clone();
+            stop_button.connect_clicked(move |_| finalize(true));
+        }
+
+        {
+            let controls = controls.clone();
+            let finalize = finalize.clone();
+            let click = gtk::GestureClick::new();
+            click.set_button(0);
+            click.connect_released(move |gesture, _, _, _| {
+                if controls.is_visible() {
+                    return;
+                }
+                match gesture.current_button() {
+                    gtk::gdk::BUTTON_PRIMARY => set_collapsed(false),
+                    gtk::gdk::BUTTON_MIDDLE => finalize(true),
+                    _ => {}
+                }
+            });
+            row.add_controller(click);
+        }
+
+        {
+            let finalize = finalize.clone();
+            hud.connect_close_request(move |_| {
+                finalize(true);
+                gtk::glib::Propagation::Stop
+            });
+        }
+
+        hud.present();
+    }
+}
+
+fn format_hud_time(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let h = seconds / 3600;
+    let m = (seconds % 3600) / 60;
+    let s = seconds % 60;
+    format!("{h:02}:{m:02}:{s:02}")
+}
@@ -0,0 +1,73 @@
+use gtk4_layer_shell::{Edge, LayerShell};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HudPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HudPosition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left" => Some(HudPosition::TopLeft),
+            "top-right" => Some(HudPosition::TopRight),
+            "bottom-left" => Some(HudPosition::BottomLeft),
+            "bottom-right" => Some(HudPosition::BottomRight),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HudPosition::TopLeft => "top-left",
+            HudPosition::TopRight => "top-right",
+            HudPosition::BottomLeft => "bottom-left",
+            HudPosition::BottomRight => "bottom-right",
+        }
+    }
+
+    fn edges(self) -> (Edge, Edge) {
+        match self {
+            HudPosition::TopLeft => (Edge::Top, Edge::Left),
+            HudPosition::TopRight => (Edge::Top, Edge::Right),
+            HudPosition::BottomLeft => (Edge::Bottom, Edge::Left),
+            HudPosition::BottomRight => (Edge::Bottom, Edge::Right),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct HudMargins {
+    pub vertical: i32,
+    pub horizontal: i32,
+}
+
+impl HudMargins {
+    pub(super) fn uniform(margin: i32) -> Self {
+        Self {
+            vertical: margin,
+            horizontal: margin,
+        }
+    }
+
+    pub(super) fn dragged(self, position: HudPosition, dx: f64, dy: f64) -> Self {
+        let (vertical, horizontal) = position.edges();
+        let dy = if vertical == Edge::Top { dy } else { -dy };
+        let dx = if horizontal == Edge::Left { dx } else { -dx };
+        Self {
+            vertical: (self.vertical + dy.round() as i32).max(0),
+            horizontal: (self.horizontal + dx.round() as i32).max(0),
+        }
+    }
+}
+
+pub(super) fn place_hud(hud: &impl LayerShell, position: HudPosition, margins: HudMargins) {
+    let (vertical, horizontal) = position.edges();
+    hud.set_anchor(vertical, true);
+    hud.set_anchor(horizontal, true);
+    hud.set_margin(vertical, margins.vertical);
+    hud.set_margin(horizontal, margins.horizontal);
+}
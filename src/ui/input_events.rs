@@ -0,0 +1,99 @@
+use std::cell::Cell;
+use std::ffi::c_long;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::fd::AsRawFd;
+use std::rc::Rc;
+
+use gtk::glib::{self, ControlFlow, IOCondition, SourceId};
+
+pub(super) const INPUT_DIR: &str = "/dev/input";
+const INPUT_EVENT_SIZE: usize = 2 * size_of::<c_long>() + 8;
+const EV_KEY: u16 = 0x01;
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct KeyEvent {
+    pub code: u16,
+    pub value: i32,
+}
+
+pub(super) struct InputWatch {
+    sources: Vec<(SourceId, Rc<Cell<bool>>)>,
+}
+
+impl Drop for InputWatch {
+    fn drop(&mut self) {
+        for (source, active) in self.sources.drain(..) {
+            if active.get() {
+                source.remove();
+            }
+        }
+    }
+}
+
+pub(super) fn watch_key_events(on_event: impl Fn(KeyEvent) + 'static) -> Option<InputWatch> {
+    let devices = open_input_devices();
+    if devices.is_empty() {
+        return None;
+    }
+
+    let on_event = Rc::new(on_event);
+    let sources = devices
+        .into_iter()
+        .map(|mut device| {
+            let active = Rc::new(Cell::new(true));
+            let source_active = active.clone();
+            let on_event = on_event.clone();
+            let source = glib::unix_fd_add_local(
+                device.as_raw_fd(),
+                IOCondition::IN | IOCondition::HUP | IOCondition::ERR,
+                move |_, _| {
+                    let mut buffer = [0u8; INPUT_EVENT_SIZE * 16];
+                    let read = match device.read(&mut buffer) {
+                        Ok(read) if read > 0 => read,
+                        _ => {
+                            source_active.set(false);
+                            return ControlFlow::Break;
+                        }
+                    };
+                    buffer[..read]
+                        .chunks_exact(INPUT_EVENT_SIZE)
+                        .filter_map(parse_key_event)
+                        .for_each(|event| on_event(event));
+                    ControlFlow::Continue
+                },
+            );
+            (source, active)
+        })
+        .collect();
+
+    Some(InputWatch { sources })
+}
+
+fn open_input_devices() -> Vec<File> {
+    let Ok(entries) = fs::read_dir(INPUT_DIR) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .filter_map(|entry| File::open(entry.path()).ok())
+        .collect()
+}
+
+fn parse_key_event(event: &[u8]) -> Option<KeyEvent> {
+    let offset = INPUT_EVENT_SIZE - 8;
+    let kind = u16::from_ne_bytes([event[offset], event[offset + 1]]);
+    if kind != EV_KEY {
+        return None;
+    }
+    Some(KeyEvent {
+        code: u16::from_ne_bytes([event[offset + 2], event[offset + 3]]),
+        value: i32::from_ne_bytes([
+            event[offset + 4],
+            event[offset + 5],
+            event[offset + 6],
+            event[offset + 7],
+        ]),
+    })
+}
@@ -1,14 +1,30 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use adw::prelude::*;
+use gtk::gdk;
 use gtk::{
     Align, Box as GtkBox, Button, Image, Label, ListBox, Orientation, SelectionMode, Switch,
     ToggleButton,
 };
 
-use crate::capture::{self, CaptureTarget, RecordingSession};
+use crate::capture::{
+    self, CaptureError, CaptureTarget, Container, OutputInfo, QualityPreset, RecordingOptions,
+    RecordingSession,
+};
+use crate::config::config;
+use crate::deps::{DependencyReport, check_dependencies, install_hint_for};
+use crate::history::{CaptureKind, log_capture};
+use crate::ui::countdown_overlay::show_countdown_overlay;
+use crate::ui::freeze_overlay::select_region_on_frozen_frame;
+use crate::ui::gallery::show_recent_captures;
+use crate::ui::keybind_hints::show_keybind_hints;
+use crate::ui::preferences::DialogPreferences;
 use crate::ui::recording_hud::show_recording_hud;
+use crate::ui::recording_overlays::RecordingOverlays;
+use crate::ui::shortcuts_window::attach_shortcuts_window;
+use crate::ui::window_picker::show_window_picker;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CaptureMode {
@@ -17,10 +33,13 @@ pub enum CaptureMode {
     Selection,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct InteractiveDialogResult {
     pub mode: CaptureMode,
     pub show_pointer: bool,
+    pub repeat_region: bool,
+    pub screen_target: CaptureTarget,
+    pub freeze: bool,
     pub delay_seconds: u32,
 }
 
@@ -30,9 +49,13 @@ pub fn build_interactive_dialog(
 ) -> adw::ApplicationWindow {
     let selected_mode = Rc::new(RefCell::new(CaptureMode::Selection));
     let show_pointer = Rc::new(RefCell::new(false));
+    let repeat_region = Rc::new(RefCell::new(false));
+    let screen_target = Rc::new(RefCell::new(CaptureTarget::Fullscreen));
+    let freeze = Rc::new(RefCell::new(false));
     let delay_seconds = Rc::new(RefCell::new(0_u32));
     let is_record_mode = Rc::new(RefCell::new(false));
     let recording_session: Rc<RefCell<Option<RecordingSession>>> = Rc::new(RefCell::new(None));
+    let preferences = Rc::new(RefCell::new(DialogPreferences::load()));
 
     let window = adw::ApplicationWindow::builder()
         .application(app)
@@ -48,10 +71,42 @@ pub fn build_interactive_dialog(
     let take_screenshot_button = Button::with_label("Take Screenshot");
     take_screenshot_button.add_css_class("suggested-action");
 
+    let menu = gtk::gio::Menu::new();
+    let capture_section = gtk::gio::Menu::new();
+    capture_section.append(Some("Recent Captures"), Some("win.recent-captures"));
+    menu.append_section(None, &capture_section);
+    let app_section = gtk::gio::Menu::new();
+    app_section.append(Some("Preferences"), Some("app.preferences"));
+    app_section.append(Some("Keyboard Shortcuts"), Some("win.show-help-overlay"));
+    app_section.append(Some("Compositor Keybinds"), Some("win.keybind-hints"));
+    app_section.append(Some("About NCaptura"), Some("app.about"));
+    app_section.append(Some("Quit"), Some("app.quit"));
+    menu.append_section(None, &app_section);
     let menu_button = gtk::MenuButton::builder()
         .icon_name("open-menu-symbolic")
+        .menu_model(&menu)
         .build();
 
+    let keybind_hints_action = gtk::gio::SimpleAction::new("keybind-hints", None);
+    {
+        let app = app.clone();
+        let window = window.clone();
+        keybind_hints_action.connect_activate(move |_, _| {
+            show_keybind_hints(&app, &window);
+        });
+    }
+    window.add_action(&keybind_hints_action);
+
+    let recent_captures_action = gtk::gio::SimpleAction::new("recent-captures", None);
+    {
+        let app = app.clone();
+        let window = window.clone();
+        recent_captures_action.connect_activate(move |_, _| {
+            show_recent_captures(&app, &window);
+        });
+    }
+    window.add_action(&recent_captures_action);
+
     header_bar.pack_start(&take_screenshot_button);
     header_bar.pack_end(&menu_button);
 
@@ -112,6 +167,53 @@ pub fn build_interactive_dialog(
     pointer_row.add_suffix(&pointer_switch);
     options_list.append(&pointer_row);
 
+    let repeat_region_row = adw::ActionRow::builder()
+        .title("Repeat Last Region")
+        .build();
+    let repeat_region_switch = Switch::new();
+    repeat_region_switch.set_valign(Align::Center);
+    repeat_region_row.add_suffix(&repeat_region_switch);
+    options_list.append(&repeat_region_row);
+
+    let outputs = capture::list_outputs().unwrap_or_default();
+    let screenshot_output_choices = output_choices(&outputs, true);
+    let recording_output_choices = output_choices(&outputs, false);
+    let output_targets: Rc<RefCell<Vec<CaptureTarget>>> = Rc::new(RefCell::new(
+        screenshot_output_choices
+            .iter()
+            .map(|(_, target)| target.clone())
+            .collect(),
+    ));
+    let output_row = adw::ComboRow::builder()
+        .title("Monitor")
+        .model(&output_choices_model(&screenshot_output_choices))
+        .build();
+    output_row.set_visible(false);
+    options_list.append(&output_row);
+    let has_multiple_outputs = outputs.len() > 1;
+
+    let workspaces = capture::list_workspaces().unwrap_or_default();
+    let mut workspace_labels = vec!["Current Workspace".to_string()];
+    workspace_labels.extend(workspaces.iter().map(|workspace| workspace.label()));
+    let workspace_labels: Vec<&str> = workspace_labels.iter().map(String::as_str).collect();
+    let workspace_row = adw::ComboRow::builder()
+        .title("Workspace")
+        .subtitle("Switch to the workspace briefly and capture its monitor")
+        .model(&gtk::StringList::new(&workspace_labels))
+        .build();
+    workspace_row.set_visible(false);
+    options_list.append(&workspace_row);
+    let has_multiple_workspaces = workspaces.len() > 1;
+
+    let freeze_row = adw::ActionRow::builder()
+        .title("Freeze Screen")
+        .subtitle("Capture the screen as it was when the delay started")
+        .build();
+    let freeze_switch = Switch::new();
+    freeze_switch.set_valign(Align::Center);
+    freeze_row.add_suffix(&freeze_switch);
+    options_list.append(&freeze_row);
+
     let audio_row = adw::ActionRow::builder().title("Record Audio").build();
     let audio_switch = Switch::new();
     audio_switch.set_valign(Align::Center);
@@ -119,6 +221,75 @@ pub fn build_interactive_dialog(
     audio_row.set_visible(false);
     options_list.append(&audio_row);
 
+    let cursor_row = adw::ActionRow::builder()
+        .title("Record Cursor")
+        .subtitle("Include the mouse cursor in the recording")
+        .build();
+    let cursor_switch = Switch::new();
+    cursor_switch.set_valign(Align::Center);
+    cursor_row.add_suffix(&cursor_switch);
+    cursor_row.set_visible(false);
+    options_list.append(&cursor_row);
+
+    let highlight_clicks_row = adw::ActionRow::builder()
+        .title("Highlight Clicks")
+        .subtitle("Draw a ripple on every mouse click in the recording")
+        .build();
+    highlight_clicks_row.set_tooltip_text(Some(
+        "Reads mouse buttons from /dev/input, which requires membership in the input group",
+    ));
+    let highlight_clicks_switch = Switch::new();
+    highlight_clicks_switch.set_valign(Align::Center);
+    highlight_clicks_row.add_suffix(&highlight_clicks_switch);
+    highlight_clicks_row.set_visible(false);
+    options_list.append(&highlight_clicks_row);
+
+    let show_keys_row = adw::ActionRow::builder()
+        .title("Show Keystrokes")
+        .subtitle("Display pressed keys at the bottom of the screen")
+        .build();
+    show_keys_row.set_tooltip_text(Some(
+        "Hidden while a window from key_display_blocklist in config.json is focused",
+    ));
+    let show_keys_switch = Switch::new();
+    show_keys_switch.set_valign(Align::Center);
+    show_keys_row.add_suffix(&show_keys_switch);
+    show_keys_row.set_visible(false);
+    options_list.append(&show_keys_row);
+
+    let container_labels: Vec<&str> = Container::ALL
+        .iter()
+        .map(|container| container.label())
+        .collect();
+    let container_row = adw::ComboRow::builder()
+        .title("Format")
+        .model(&gtk::StringList::new(&container_labels))
+        .build();
+    container_row.set_visible(false);
+    options_list.append(&container_row);
+
+    let quality_labels: Vec<&str> = std::iter::once("Encoder Default")
+        .chain(QualityPreset::ALL.iter().map(|quality| quality.label()))
+        .collect();
+    let quality_row = adw::ComboRow::builder()
+        .title("Quality")
+        .model(&gtk::StringList::new(&quality_labels))
+        .build();
+    quality_row.set_visible(false);
+    options_list.append(&quality_row);
+
+    let time_limit_row = adw::ActionRow::builder()
+        .title("Time Limit in Minutes")
+        .subtitle("Stop recording automatically; 0 means no limit")
+        .build();
+    let time_limit_spin = gtk::SpinButton::with_range(0.0, 600.0, 1.0);
+    time_limit_spin.set_valign(Align::Center);
+    time_limit_spin.set_numeric(true);
+    time_limit_spin.set_snap_to_ticks(true);
+    time_limit_row.add_suffix(&time_limit_spin);
+    time_limit_row.set_visible(false);
+    options_list.append(&time_limit_row);
+
     let delay_row = adw::ActionRow::builder().title("Delay in Seconds").build();
     let delay_spin = gtk::SpinButton::with_range(0.0, 99.0, 1.0);
     delay_spin.set_valign(Align::Center);
@@ -131,62 +302,290 @@ pub fn build_interactive_dialog(
     content.append(&capture_section);
     content.append(&options_list);
 
+    let status_label = Label::new(None);
+    status_label.add_css_class("dim-label");
+    status_label.set_wrap(true);
+    status_label.set_visible(false);
+    content.append(&status_label);
+
     root.append(&header_bar);
+    if let Some(banner) = build_missing_tools_banner(&check_dependencies()) {
+        root.append(&banner);
+    }
     root.append(&content);
     window.set_content(Some(&root));
 
     {
         let selected_mode = selected_mode.clone();
+        let preferences = preferences.clone();
+        let output_row = output_row.clone();
+        let workspace_row = workspace_row.clone();
         screen_button.connect_toggled(move |button| {
+            output_row.set_visible(has_multiple_outputs && button.is_active());
+            workspace_row.set_visible(has_multiple_workspaces && button.is_active());
             if button.is_active() {
                 *selected_mode.borrow_mut() = CaptureMode::Screen;
+                preferences
+                    .borrow_mut()
+                    .update(|preferences| preferences.mode = CaptureMode::Screen);
             }
         });
     }
 
     {
         let selected_mode = selected_mode.clone();
+        let preferences = preferences.clone();
         window_button.connect_toggled(move |button| {
             if button.is_active() {
                 *selected_mode.borrow_mut() = CaptureMode::Window;
+                preferences
+                    .borrow_mut()
+                    .update(|preferences| preferences.mode = CaptureMode::Window);
             }
         });
     }
 
     {
         let selected_mode = selected_mode.clone();
+        let preferences = preferences.clone();
+        let repeat_region_row = repeat_region_row.clone();
         selection_button.connect_toggled(move |button| {
+            repeat_region_row.set_sensitive(button.is_active());
             if button.is_active() {
                 *selected_mode.borrow_mut() = CaptureMode::Selection;
+                preferences
+                    .borrow_mut()
+                    .update(|preferences| preferences.mode = CaptureMode::Selection);
             }
         });
     }
 
     {
         let show_pointer = show_pointer.clone();
+        let preferences = preferences.clone();
         pointer_switch.connect_active_notify(move |switch| {
             *show_pointer.borrow_mut() = switch.is_active();
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.show_pointer = switch.is_active());
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        audio_switch.connect_active_notify(move |switch| {
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.record_audio = switch.is_active());
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        cursor_switch.connect_active_notify(move |switch| {
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.record_cursor = switch.is_active());
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        highlight_clicks_switch.connect_active_notify(move |switch| {
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.highlight_clicks = switch.is_active());
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        show_keys_switch.connect_active_notify(move |switch| {
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.show_keys = switch.is_active());
+        });
+    }
+
+    {
+        let repeat_region = repeat_region.clone();
+        repeat_region_switch.connect_active_notify(move |switch| {
+            *repeat_region.borrow_mut() = switch.is_active();
+        });
+    }
+
+    let update_screen_target: Rc<dyn Fn()> = {
+        let screen_target = screen_target.clone();
+        let freeze_row = freeze_row.clone();
+        let output_row = output_row.clone();
+        let output_targets = output_targets.clone();
+        let workspace_row = workspace_row.clone();
+        Rc::new(move || {
+            let workspace = (workspace_row.selected() as usize)
+                .checked_sub(1)
+                .and_then(|index| workspaces.get(index));
+            let target = match (workspace, output_row.selected()) {
+                (Some(workspace), _) => CaptureTarget::Workspace(workspace.id),
+                (None, index) => output_targets
+                    .borrow()
+                    .get(index as usize)
+                    .cloned()
+                    .unwrap_or(CaptureTarget::Fullscreen),
+            };
+            output_row.set_sensitive(workspace.is_none());
+            freeze_row.set_sensitive(target == CaptureTarget::Fullscreen);
+            *screen_target.borrow_mut() = target;
+        })
+    };
+
+    {
+        let update_screen_target = update_screen_target.clone();
+        output_row.connect_selected_notify(move |_| update_screen_target());
+    }
+
+    {
+        let update_screen_target = update_screen_target.clone();
+        workspace_row.connect_selected_notify(move |_| update_screen_target());
+    }
+
+    {
+        let freeze = freeze.clone();
+        freeze_switch.connect_active_notify(move |switch| {
+            *freeze.borrow_mut() = switch.is_active();
         });
     }
 
     {
         let delay_seconds = delay_seconds.clone();
+        let preferences = preferences.clone();
         delay_spin.connect_value_changed(move |spin| {
-            *delay_seconds.borrow_mut() = spin.value_as_int() as u32;
+            let seconds = spin.value_as_int() as u32;
+            *delay_seconds.borrow_mut() = seconds;
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.delay_seconds = seconds);
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        time_limit_spin.connect_value_changed(move |spin| {
+            let minutes = spin.value_as_int() as u32;
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.time_limit_minutes = minutes);
         });
     }
 
+    {
+        let preferences = preferences.clone();
+        container_row.connect_selected_notify(move |row| {
+            let container = Container::ALL
+                .get(row.selected() as usize)
+                .copied()
+                .unwrap_or_default();
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.container = container);
+        });
+    }
+
+    {
+        let preferences = preferences.clone();
+        quality_row.connect_selected_notify(move |row| {
+            let quality = selected_quality(row);
+            preferences
+                .borrow_mut()
+                .update(|preferences| preferences.quality = quality);
+        });
+    }
+
+    {
+        let initial = preferences.borrow().clone();
+        match initial.mode {
+            CaptureMode::Screen => screen_button.set_active(true),
+            CaptureMode::Window => window_button.set_active(true),
+            CaptureMode::Selection => selection_button.set_active(true),
+        }
+        pointer_switch.set_active(initial.show_pointer);
+        audio_switch.set_active(initial.record_audio);
+        cursor_switch.set_active(initial.record_cursor);
+        highlight_clicks_switch.set_active(initial.highlight_clicks);
+        show_keys_switch.set_active(initial.show_keys);
+        delay_spin.set_value(f64::from(initial.delay_seconds));
+        time_limit_spin.set_value(f64::from(initial.time_limit_minutes));
+        if let Some(index) = Container::ALL
+            .iter()
+            .position(|container| *container == initial.container)
+        {
+            container_row.set_selected(index as u32);
+        }
+        if let Some(index) = initial.quality.and_then(|quality| {
+            QualityPreset::ALL
+                .iter()
+                .position(|preset| *preset == quality)
+        }) {
+            quality_row.set_selected(index as u32 + 1);
+        }
+    }
+
     {
         let is_record_mode = is_record_mode.clone();
         let recording_session = recording_session.clone();
         let take_screenshot_button = take_screenshot_button.clone();
         let pointer_row = pointer_row.clone();
+        let repeat_region_row = repeat_region_row.clone();
+        let freeze_row = freeze_row.clone();
         let audio_row = audio_row.clone();
+        let cursor_row = cursor_row.clone();
+        let highlight_clicks_row = highlight_clicks_row.clone();
+        let show_keys_row = show_keys_row.clone();
+        let time_limit_row = time_limit_row.clone();
+        let container_row = container_row.clone();
+        let quality_row = quality_row.clone();
+        let window_button = window_button.clone();
+        let screen_target = screen_target.clone();
+        let output_row = output_row.clone();
+        let output_targets = output_targets.clone();
+        let update_screen_target = update_screen_target.clone();
         mode_stack.connect_visible_child_name_notify(move |stack| {
             let recording_mode = stack.visible_child_name().as_deref() == Some("recording");
+            if *is_record_mode.borrow() != recording_mode {
+                let choices = if recording_mode {
+                    &recording_output_choices
+                } else {
+                    &screenshot_output_choices
+                };
+                let current = screen_target.borrow().clone();
+                let selected = choices
+                    .iter()
+                    .position(|(_, target)| *target == current)
+                    .unwrap_or(0);
+                *output_targets.borrow_mut() =
+                    choices.iter().map(|(_, target)| target.clone()).collect();
+                output_row.set_model(Some(&output_choices_model(choices)));
+                output_row.set_selected(selected as u32);
+                update_screen_target();
+            }
             *is_record_mode.borrow_mut() = recording_mode;
             pointer_row.set_sensitive(!recording_mode);
+            repeat_region_row.set_visible(!recording_mode);
+            freeze_row.set_subtitle(if recording_mode {
+                "Select the recording region on a still frame"
+            } else {
+                "Capture the screen as it was when the delay started"
+            });
             audio_row.set_visible(recording_mode);
+            cursor_row.set_visible(recording_mode);
+            highlight_clicks_row.set_visible(recording_mode);
+            show_keys_row.set_visible(recording_mode);
+            time_limit_row.set_visible(recording_mode);
+            container_row.set_visible(recording_mode);
+            quality_row.set_visible(recording_mode);
+            window_button.set_tooltip_text(recording_mode.then_some(
+                "Records the window's area as it is when recording starts; \
+                 the region does not follow the window if it moves",
+            ));
             if recording_mode {
                 if recording_session.borrow().is_some() {
                     take_screenshot_button.set_label("Stop Recording");
@@ -203,9 +602,18 @@ pub fn build_interactive_dialog(
         let app = app.clone();
         let selected_mode = selected_mode.clone();
         let show_pointer = show_pointer.clone();
+        let repeat_region = repeat_region.clone();
+        let screen_target = screen_target.clone();
+        let freeze = freeze.clone();
         let delay_seconds = delay_seconds.clone();
         let is_record_mode = is_record_mode.clone();
         let audio_switch = audio_switch.clone();
+        let cursor_switch = cursor_switch.clone();
+        let highlight_clicks_switch = highlight_clicks_switch.clone();
+        let show_keys_switch = show_keys_switch.clone();
+        let time_limit_spin = time_limit_spin.clone();
+        let container_row = container_row.clone();
+        let quality_row = quality_row.clone();
         let recording_session = recording_session.clone();
         let take_screenshot_button_handle = take_screenshot_button.clone();
         let take_screenshot_button = take_screenshot_button.clone();
@@ -218,25 +626,155 @@ pub fn build_interactive_dialog(
                 }
 
                 let target = match *selected_mode.borrow() {
-                    CaptureMode::Screen => CaptureTarget::Fullscreen,
+                    CaptureMode::Screen => screen_target.borrow().clone(),
                     CaptureMode::Window => CaptureTarget::Region,
                     CaptureMode::Selection => CaptureTarget::Region,
                 };
 
-                match capture::start_recording(target, audio_switch.is_active()) {
-                    Ok(session) => {
-                        *recording_session.borrow_mut() = Some(session);
-                        take_screenshot_button.set_label("Stop Recording");
+                let time_limit_minutes = time_limit_spin.value_as_int().max(0) as u64;
+                let options = RecordingOptions {
+                    audio: audio_switch.is_active(),
+                    show_cursor: cursor_switch.is_active(),
+                    max_duration: (time_limit_minutes > 0)
+                        .then(|| Duration::from_secs(time_limit_minutes * 60)),
+                    container: Container::ALL
+                        .get(container_row.selected() as usize)
+                        .copied()
+                        .unwrap_or_default(),
+                    video_codec: None,
+                    quality: selected_quality(&quality_row),
+                    region_geometry: None,
+                    normalize_audio: config().normalize_audio,
+                };
+                let freeze_selection = *freeze.borrow() && target == CaptureTarget::Region;
+                let overlays = RecordingOverlays {
+                    highlight_clicks: highlight_clicks_switch.is_active(),
+                    show_keys: show_keys_switch.is_active(),
+                };
+
+                let begin_recording = {
+                    let app = app.clone();
+                    let window_handle = window_handle.clone();
+                    let mode_stack = mode_stack.clone();
+                    let take_screenshot_button = take_screenshot_button.clone();
+                    let recording_session = recording_session.clone();
+                    let status_label = status_label.clone();
+                    move |options: RecordingOptions| {
+                        take_screenshot_button.set_sensitive(false);
+                        status_label.set_text("Starting recording…");
+                        status_label.set_visible(true);
+                        gtk::glib::spawn_future_local(async move {
+                            let target_label = target.slug();
+                            let result = gtk::gio::spawn_blocking(move || {
+                                capture::start_recording_with_webcam(
+                                    target,
+                                    &options,
+                                    config().webcam.clone(),
+                                )
+                            })
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(CaptureError::Failed("工作线程异常退出".to_string()))
+                            });
+                            take_screenshot_button.set_sensitive(true);
+
+                            match result {
+                                Ok(session) => {
+                                    log_capture(
+                                        CaptureKind::Recording,
+                                        target_label,
+                                        Ok(session.output_path()),
+                                    );
+                                    status_label.set_visible(false);
+                                    *recording_session.borrow_mut() = Some(session);
+                                    take_screenshot_button.set_label("Stop Recording");
+                                    window_handle.set_visible(false);
+                                    show_recording_hud(
+                                        &app,
+                                        &window_handle,
+                                        &mode_stack,
+                                        &take_screenshot_button,
+                                        &recording_session,
+                                        overlays,
+                                    );
+                                }
+                                Err(CaptureError::Cancelled) => {
+                                    status_label.set_visible(false);
+                                    window_handle.set_visible(true);
+                                }
+                                Err(err) => {
+                                    log_capture(
+                                        CaptureKind::Recording,
+                                        target_label,
+                                        Err(err.to_string()),
+                                    );
+                                    eprintln!("开始录屏失败: {err}");
+                                    status_label.set_text(&format!("Recording failed: {err}"));
+                                    window_handle.set_visible(true);
+                                }
+                            }
+                        });
+                    }
+                };
+
+                if *selected_mode.borrow() == CaptureMode::Window {
+                    let delay_seconds = *delay_seconds.borrow();
+                    let countdown_app = app.clone();
+                    pick_recording_window(&app, &window_handle, move |geometry| {
+                        let options = RecordingOptions {
+                            region_geometry: Some(geometry),
+                            ..options
+                        };
+                        if delay_seconds > 0 {
+                            show_countdown_overlay(&countdown_app, delay_seconds, move || {
+                                begin_recording(options)
+                            });
+                        } else {
+                            begin_recording(options);
+                        }
+                    });
+                    return;
+                }
+
+                let prepare_recording = {
+                    let app = app.clone();
+                    let window_handle = window_handle.clone();
+                    move || {
+                        if !freeze_selection {
+                            begin_recording(options);
+                            return;
+                        }
+
                         window_handle.set_visible(false);
-                        show_recording_hud(
-                            &app,
-                            &window_handle,
-                            &mode_stack,
-                            &take_screenshot_button,
-                            &recording_session,
+                        gtk::glib::timeout_add_local_once(
+                            config().hide_window_settle(),
+                            move || {
+                                select_region_on_frozen_frame(
+                                    &app,
+                                    move |geometry| match geometry {
+                                        Ok(geometry) => begin_recording(RecordingOptions {
+                                            region_geometry: Some(geometry),
+                                            ..options
+                                        }),
+                                        Err(err) => {
+                                            if !capture::is_cancelled(&err) {
+                                                eprintln!("开始录屏失败: {err:#}");
+                                            }
+                                            window_handle.set_visible(true);
+                                        }
+                                    },
+                                );
+                            },
                         );
                     }
-                    Err(err) => eprintln!("开始录屏失败: {err}"),
+                };
+
+                let delay_seconds = *delay_seconds.borrow();
+                if delay_seconds > 0 {
+                    window_handle.set_visible(false);
+                    show_countdown_overlay(&app, delay_seconds, prepare_recording);
+                } else {
+                    prepare_recording();
                 }
                 return;
             }
@@ -244,27 +782,206 @@ pub fn build_interactive_dialog(
             let result = InteractiveDialogResult {
                 mode: *selected_mode.borrow(),
                 show_pointer: *show_pointer.borrow(),
+                repeat_region: *repeat_region.borrow(),
+                screen_target: screen_target.borrow().clone(),
+                freeze: *freeze.borrow(),
                 delay_seconds: *delay_seconds.borrow(),
             };
-            window_handle.destroy();
+            if config().hide_window_before_capture {
+                window_handle.destroy();
+            }
             on_take(result);
         });
     }
 
     {
         let recording_session = recording_session.clone();
+        let app = app.clone();
         window.connect_close_request(move |_| {
             if let Some(session) = recording_session.borrow_mut().take() {
-                let _ = capture::stop_recording(session);
+                let guard = app.hold();
+                gtk::glib::spawn_future_local(async move {
+                    let result = gtk::gio::spawn_blocking(move || capture::stop_recording(session))
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(CaptureError::Failed("工作线程异常退出".to_string()))
+                        });
+                    match result {
+                        Ok(path) => eprintln!("录屏已保存: {}", path.display()),
+                        Err(err) => eprintln!("停止录屏失败: {err}"),
+                    }
+                    drop(guard);
+                });
             }
             gtk::glib::Propagation::Proceed
         });
     }
 
+    attach_shortcuts_window(&window);
+
+    let key_controller = gtk::EventControllerKey::new();
+    {
+        let window = window.clone();
+        key_controller.connect_key_pressed(move |_, key, _, state| {
+            if state.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK) {
+                return gtk::glib::Propagation::Proceed;
+            }
+
+            match key {
+                gdk::Key::s => selection_button.set_active(true),
+                gdk::Key::w => window_button.set_active(true),
+                gdk::Key::f => screen_button.set_active(true),
+                gdk::Key::Return | gdk::Key::KP_Enter => {
+                    if take_screenshot_button.is_sensitive() {
+                        take_screenshot_button.emit_clicked();
+                    }
+                }
+                gdk::Key::Escape => window.close(),
+                _ => return gtk::glib::Propagation::Proceed,
+            }
+            gtk::glib::Propagation::Stop
+        });
+    }
+    window.add_controller(key_controller);
+
     window.present();
     window
 }
 
+fn pick_recording_window(
+    app: &adw::Application,
+    dialog: &adw::ApplicationWindow,
+    on_picked: impl FnOnce(String) + 'static,
+) {
+    let app = app.clone();
+    let dialog = dialog.clone();
+    gtk::glib::spawn_future_local(async move {
+        let listing = gtk::gio::spawn_blocking(|| {
+            capture::list_capturable_windows()
+                .map(|windows| (windows, capture::list_workspaces().unwrap_or_default()))
+        })
+        .await
+        .unwrap_or_else(|_| Err(CaptureError::Failed("工作线程异常退出".to_string())));
+        let (windows, workspaces) = match listing {
+            Ok(listing) => listing,
+            Err(err) => {
+                eprintln!("读取窗口列表失败: {err}");
+                return;
+            }
+        };
+        if windows.is_empty() {
+            eprintln!("没有可供选择的窗口");
+            return;
+        }
+
+        let on_picked = RefCell::new(Some(on_picked));
+        show_window_picker(
+            &app,
+            windows,
+            &workspaces,
+            true,
+            app.hold(),
+            move |window_id, include_shadow, _guard| {
+                let Some(on_picked) = on_picked.borrow_mut().take() else {
+                    return;
+                };
+                let padding = if include_shadow {
+                    config().window_padding
+                } else {
+                    0
+                };
+                let dialog = dialog.clone();
+                dialog.set_visible(false);
+                gtk::glib::spawn_future_local(async move {
+                    let geometry = gtk::gio::spawn_blocking(move || {
+                        capture::window_geometry(window_id)
+                            .map(|geometry| geometry.padded(padding).to_string())
+                    })
+                    .await
+                    .unwrap_or_else(|_| Err(CaptureError::Failed("工作线程异常退出".to_string())));
+                    match geometry {
+                        Ok(geometry) => on_picked(geometry),
+                        Err(err) => {
+                            eprintln!("开始录屏失败: {err}");
+                            dialog.set_visible(true);
+                        }
+                    }
+                });
+            },
+        );
+    });
+}
+
+fn output_choices(outputs: &[OutputInfo], include_all: bool) -> Vec<(String, CaptureTarget)> {
+    let mut choices = vec![("Focused Monitor".to_string(), CaptureTarget::Fullscreen)];
+    if include_all {
+        choices.push(("All Monitors".to_string(), CaptureTarget::AllOutputs));
+    }
+    choices.extend(outputs.iter().map(|output| {
+        (
+            format!("{} ({} {})", output.name, output.make, output.model),
+            CaptureTarget::Output(output.name.clone()),
+        )
+    }));
+    choices
+}
+
+fn output_choices_model(choices: &[(String, CaptureTarget)]) -> gtk::StringList {
+    let labels: Vec<&str> = choices.iter().map(|(label, _)| label.as_str()).collect();
+    gtk::StringList::new(&labels)
+}
+
+fn selected_quality(row: &adw::ComboRow) -> Option<QualityPreset> {
+    (row.selected() as usize)
+        .checked_sub(1)
+        .and_then(|index| QualityPreset::ALL.get(index))
+        .copied()
+}
+
+fn build_missing_tools_banner(report: &DependencyReport) -> Option<GtkBox> {
+    let missing: Vec<_> = report.missing_essential().collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let banner = GtkBox::new(Orientation::Horizontal, 12);
+    banner.add_css_class("card");
+    banner.set_margin_top(12);
+    banner.set_margin_start(24);
+    banner.set_margin_end(24);
+
+    let icon = Image::from_icon_name("dialog-warning-symbolic");
+    icon.set_valign(Align::Start);
+    icon.set_margin_top(12);
+    icon.set_margin_start(12);
+
+    let text = GtkBox::new(Orientation::Vertical, 4);
+    text.set_margin_top(12);
+    text.set_margin_bottom(12);
+    text.set_margin_end(12);
+
+    let title = Label::new(Some("Some capture tools are missing"));
+    title.add_css_class("heading");
+    title.set_halign(Align::Start);
+    text.append(&title);
+
+    for tool in missing {
+        let hint = install_hint_for(tool.name)
+            .unwrap_or_else(|| "install it with your package manager".to_string());
+        let line = Label::new(Some(&format!("{}: {hint}", tool.name)));
+        line.add_css_class("caption");
+        line.set_halign(Align::Start);
+        line.set_wrap(true);
+        line.set_xalign(0.0);
+        line.set_selectable(true);
+        text.append(&line);
+    }
+
+    banner.append(&icon);
+    banner.append(&text);
+    Some(banner)
+}
+
 fn build_mode_button(icon_name: &str, label_text: &str) -> ToggleButton {
     let button = ToggleButton::new();
 
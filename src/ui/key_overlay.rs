@@ -0,0 +1,299 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use gtk::glib::{self, ControlFlow, SourceId};
+use gtk::{Align, Box as GtkBox, Label, Orientation};
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+use crate::capture::{self, WindowInfo};
+use crate::config::config;
+use crate::ui::click_overlay::pass_input_through;
+use crate::ui::input_events::{INPUT_DIR, InputWatch, watch_key_events};
+use crate::ui::style::install_app_css;
+
+const KEY_OVERLAY_MARGIN: i32 = 48;
+const MAX_KEYSTROKES: usize = 5;
+const KEYSTROKE_VISIBLE: Duration = Duration::from_millis(1500);
+const KEYSTROKE_FADE: Duration = Duration::from_millis(500);
+const KEYSTROKE_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+const FOCUS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const KEY_PRESSED: i32 = 1;
+const KEY_RELEASED: i32 = 0;
+const BTN_MISC: u16 = 0x100;
+
+const MODIFIERS: &[(u16, &str)] = &[
+    (29, "Ctrl"),
+    (97, "Ctrl"),
+    (56, "Alt"),
+    (100, "Alt"),
+    (125, "Super"),
+    (126, "Super"),
+    (42, "Shift"),
+    (54, "Shift"),
+];
+
+struct Keystroke {
+    label: Label,
+    shown_at: Instant,
+}
+
+pub(super) struct KeyOverlay {
+    window: adw::ApplicationWindow,
+    _input_watch: InputWatch,
+    frame_source: Option<SourceId>,
+    focus_source: Option<SourceId>,
+}
+
+impl Drop for KeyOverlay {
+    fn drop(&mut self) {
+        if let Some(source) = self.frame_source.take() {
+            source.remove();
+        }
+        if let Some(source) = self.focus_source.take() {
+            source.remove();
+        }
+        self.window.destroy();
+    }
+}
+
+pub(super) fn show_key_overlay(app: &adw::Application) -> Option<KeyOverlay> {
+    if !gtk4_layer_shell::is_supported() {
+        eprintln!("当前合成器不支持 layer-shell，无法显示按键");
+        return None;
+    }
+
+    install_app_css();
+
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Keystrokes")
+        .build();
+    window.set_decorated(false);
+    window.add_css_class("key-overlay");
+    window.init_layer_shell();
+    window.set_layer(Layer::Overlay);
+    window.set_anchor(Edge::Bottom, true);
+    window.set_margin(Edge::Bottom, KEY_OVERLAY_MARGIN);
+    window.set_keyboard_mode(KeyboardMode::None);
+    window.set_namespace(Some("ncaptura-key-overlay"));
+    pass_input_through(&window);
+
+    let row = GtkBox::new(Orientation::Horizontal, 8);
+    row.set_halign(Align::Center);
+    window.set_content(Some(&row));
+
+    let keystrokes: Rc<RefCell<Vec<Keystroke>>> = Rc::new(RefCell::new(Vec::new()));
+    let held_modifiers: Rc<RefCell<Vec<u16>>> = Rc::new(RefCell::new(Vec::new()));
+    let suppressed = Rc::new(Cell::new(focused_window_is_blocked()));
+
+    let input_watch = {
+        let row = row.clone();
+        let keystrokes = keystrokes.clone();
+        let suppressed = suppressed.clone();
+        watch_key_events(move |event| {
+            if event.code >= BTN_MISC {
+                return;
+            }
+            if modifier_name(event.code).is_some() {
+                let mut held = held_modifiers.borrow_mut();
+                held.retain(|code| *code != event.code);
+                if event.value != KEY_RELEASED {
+                    held.push(event.code);
+                }
+                return;
+            }
+            if event.value != KEY_PRESSED || suppressed.get() {
+                return;
+            }
+
+            let mut names: Vec<&str> = Vec::new();
+            for name in held_modifiers
+                .borrow()
+                .iter()
+                .filter_map(|code| modifier_name(*code))
+            {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            names.push(key_name(event.code));
+            push_keystroke(&row, &keystrokes, &names.join("+"));
+        })
+    };
+    let Some(input_watch) = input_watch else {
+        eprintln!("无法读取 {INPUT_DIR} 下的输入设备，显示按键需要当前用户属于 input 组");
+        window.destroy();
+        return None;
+    };
+
+    let frame_source = {
+        let row = row.clone();
+        let keystrokes = keystrokes.clone();
+        glib::timeout_add_local(KEYSTROKE_FRAME_INTERVAL, move || {
+            keystrokes.borrow_mut().retain(|keystroke| {
+                let age = keystroke.shown_at.elapsed();
+                if age >= KEYSTROKE_VISIBLE + KEYSTROKE_FADE {
+                    row.remove(&keystroke.label);
+                    return false;
+                }
+                let fade = age.saturating_sub(KEYSTROKE_VISIBLE).as_secs_f64()
+                    / KEYSTROKE_FADE.as_secs_f64();
+                keystroke.label.set_opacity(1.0 - fade);
+                true
+            });
+            ControlFlow::Continue
+        })
+    };
+
+    let focus_source = {
+        let row = row.clone();
+        let keystrokes = keystrokes.clone();
+        glib::timeout_add_local(FOCUS_CHECK_INTERVAL, move || {
+            let blocked = focused_window_is_blocked();
+            if blocked && !suppressed.get() {
+                for keystroke in keystrokes.borrow_mut().drain(..) {
+                    row.remove(&keystroke.label);
+                }
+            }
+            suppressed.set(blocked);
+            ControlFlow::Continue
+        })
+    };
+
+    window.present();
+    Some(KeyOverlay {
+        window,
+        _input_watch: input_watch,
+        frame_source: Some(frame_source),
+        focus_source: Some(focus_source),
+    })
+}
+
+fn push_keystroke(row: &GtkBox, keystrokes: &RefCell<Vec<Keystroke>>, text: &str) {
+    let mut keystrokes = keystrokes.borrow_mut();
+    if keystrokes.len() >= MAX_KEYSTROKES {
+        let oldest = keystrokes.remove(0);
+        row.remove(&oldest.label);
+    }
+
+    let label = Label::new(Some(text));
+    label.add_css_class("keystroke");
+    row.append(&label);
+    keystrokes.push(Keystroke {
+        label,
+        shown_at: Instant::now(),
+    });
+}
+
+fn focused_window_is_blocked() -> bool {
+    let blocklist = &config().key_display_blocklist;
+    if blocklist.is_empty() {
+        return false;
+    }
+    let Ok(windows) = capture::list_windows() else {
+        return true;
+    };
+    windows
+        .iter()
+        .find(|window| window.is_focused)
+        .is_some_and(|window| window_matches(window, blocklist))
+}
+
+fn window_matches(window: &WindowInfo, blocklist: &[String]) -> bool {
+    let app_id = window.app_id.to_lowercase();
+    let title = window.title.to_lowercase();
+    blocklist.iter().any(|entry| {
+        let entry = entry.to_lowercase();
+        app_id.contains(&entry) || title.contains(&entry)
+    })
+}
+
+fn modifier_name(code: u16) -> Option<&'static str> {
+    MODIFIERS
+        .iter()
+        .find(|(modifier, _)| *modifier == code)
+        .map(|(_, name)| *name)
+}
+
+fn key_name(code: u16) -> &'static str {
+    match code {
+        1 => "Esc",
+        2 => "1",
+        3 => "2",
+        4 => "3",
+        5 => "4",
+        6 => "5",
+        7 => "6",
+        8 => "7",
+        9 => "8",
+        10 => "9",
+        11 => "0",
+        12 => "-",
+        13 => "=",
+        14 => "⌫",
+        15 => "Tab",
+        16 => "Q",
+        17 => "W",
+        18 => "E",
+        19 => "R",
+        20 => "T",
+        21 => "Y",
+        22 => "U",
+        23 => "I",
+        24 => "O",
+        25 => "P",
+        26 => "[",
+        27 => "]",
+        28 => "⏎",
+        30 => "A",
+        31 => "S",
+        32 => "D",
+        33 => "F",
+        34 => "G",
+        35 => "H",
+        36 => "J",
+        37 => "K",
+        38 => "L",
+        39 => ";",
+        40 => "'",
+        41 => "`",
+        43 => "\\",
+        44 => "Z",
+        45 => "X",
+        46 => "C",
+        47 => "V",
+        48 => "B",
+        49 => "N",
+        50 => "M",
+        51 => ",",
+        52 => ".",
+        53 => "/",
+        57 => "Space",
+        58 => "CapsLock",
+        59 => "F1",
+        60 => "F2",
+        61 => "F3",
+        62 => "F4",
+        63 => "F5",
+        64 => "F6",
+        65 => "F7",
+        66 => "F8",
+        67 => "F9",
+        68 => "F10",
+        87 => "F11",
+        88 => "F12",
+        102 => "Home",
+        103 => "↑",
+        104 => "PgUp",
+        105 => "←",
+        106 => "→",
+        107 => "End",
+        108 => "↓",
+        109 => "PgDn",
+        110 => "Ins",
+        111 => "Del",
+        _ => "?",
+    }
+}
@@ -0,0 +1,95 @@
+use adw::prelude::*;
+use gtk::{Align, Box as GtkBox, Button, Image, Label, ListBox, Orientation, SelectionMode};
+
+use crate::keybinds::{RECOMMENDED_KEYBINDS, detect_niri_keybinds};
+
+pub(super) fn show_keybind_hints(app: &adw::Application, parent: &adw::ApplicationWindow) {
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .transient_for(parent)
+        .modal(true)
+        .title("Keybind Hints")
+        .default_width(560)
+        .resizable(false)
+        .build();
+
+    let header = adw::HeaderBar::new();
+    let copy_all_button = Button::with_label("Copy All");
+    header.pack_start(&copy_all_button);
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_top(16);
+    content.set_margin_bottom(16);
+    content.set_margin_start(16);
+    content.set_margin_end(16);
+
+    let status = detect_niri_keybinds();
+    let summary_text = match &status {
+        Some(status) => format!(
+            "{} of {} recommended binds found in {}",
+            status.configured.iter().filter(|configured| **configured).count(),
+            RECOMMENDED_KEYBINDS.len(),
+            status.config_path.display()
+        ),
+        None => "niri config not found; add these binds to the binds { } section of your compositor config".to_string(),
+    };
+    let summary = Label::new(Some(&summary_text));
+    summary.set_wrap(true);
+    summary.set_xalign(0.0);
+    summary.add_css_class("dim-label");
+    content.append(&summary);
+
+    let list = ListBox::new();
+    list.set_selection_mode(SelectionMode::None);
+    list.add_css_class("boxed-list");
+
+    for (index, keybind) in RECOMMENDED_KEYBINDS.iter().enumerate() {
+        let snippet = keybind.niri_snippet();
+        let row = adw::ActionRow::builder()
+            .title(keybind.description)
+            .subtitle(&snippet)
+            .build();
+        row.add_css_class("monospace");
+
+        if let Some(status) = &status {
+            let configured = status.configured.get(index).copied().unwrap_or(false);
+            let icon = Image::from_icon_name(if configured {
+                "emblem-ok-symbolic"
+            } else {
+                "dialog-warning-symbolic"
+            });
+            icon.set_tooltip_text(Some(if configured {
+                "Configured"
+            } else {
+                "Not found in niri config"
+            }));
+            row.add_suffix(&icon);
+        }
+
+        let copy_button = Button::from_icon_name("edit-copy-symbolic");
+        copy_button.set_valign(Align::Center);
+        copy_button.set_tooltip_text(Some("Copy Snippet"));
+        copy_button.add_css_class("flat");
+        copy_button.connect_clicked(move |button| {
+            button.clipboard().set_text(&snippet);
+        });
+        row.add_suffix(&copy_button);
+
+        list.append(&row);
+    }
+    content.append(&list);
+
+    copy_all_button.connect_clicked(|button| {
+        let snippets = RECOMMENDED_KEYBINDS
+            .iter()
+            .map(|keybind| format!("    {}\n", keybind.niri_snippet()))
+            .collect::<String>();
+        button.clipboard().set_text(&snippets);
+    });
+
+    let root = GtkBox::new(Orientation::Vertical, 0);
+    root.append(&header);
+    root.append(&content);
+    window.set_content(Some(&root));
+    window.present();
+}
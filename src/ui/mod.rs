@@ -1,10 +1,36 @@
+mod annotate;
+mod app_actions;
 mod cli_recording_hud;
+mod click_overlay;
+mod countdown_overlay;
+mod freeze_overlay;
+mod gallery;
+mod hud;
+mod hud_placement;
+mod input_events;
 mod interactive_dialog;
+mod key_overlay;
+mod keybind_hints;
+mod pin_window;
+mod preferences;
 mod recording_hud;
+mod recording_overlays;
 mod save_dialog;
+mod share_window;
+mod shortcuts_window;
+mod sleep_watch;
+mod style;
 mod window_picker;
 
+pub use app_actions::install_app_actions;
 pub use cli_recording_hud::run_cli_recording_hud;
+pub use countdown_overlay::show_countdown_overlay;
+pub use freeze_overlay::{load_frozen_frame, show_frozen_countdown};
+pub use hud_placement::HudPosition;
 pub use interactive_dialog::{CaptureMode, InteractiveDialogResult, build_interactive_dialog};
+pub use pin_window::run_pin_window;
+pub use recording_overlays::RecordingOverlays;
 pub use save_dialog::build_save_dialog;
+pub use share_window::run_share_window;
+pub use style::install_app_css;
 pub use window_picker::show_window_picker;
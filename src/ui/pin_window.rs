@@ -0,0 +1,78 @@
+use adw::prelude::*;
+use gtk::gdk;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib::Propagation;
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+const PIN_MARGIN: i32 = 48;
+
+pub fn run_pin_window(pixbuf: Pixbuf) {
+    let app = adw::Application::builder()
+        .application_id("io.ncaptura.app.pin")
+        .flags(gtk::gio::ApplicationFlags::NON_UNIQUE)
+        .build();
+
+    app.connect_activate(move |app| {
+        show_pinned_image(app, &pixbuf);
+    });
+    let _ = app.run_with_args(&["ncaptura-pin"]);
+}
+
+pub fn show_pinned_image(app: &adw::Application, pixbuf: &Pixbuf) {
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Pinned Screenshot")
+        .resizable(false)
+        .build();
+    window.set_decorated(false);
+
+    let picture = gtk::Picture::for_pixbuf(pixbuf);
+    picture.set_can_shrink(false);
+    picture.set_size_request(pixbuf.width(), pixbuf.height());
+
+    if gtk4_layer_shell::is_supported() {
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_anchor(Edge::Top, true);
+        window.set_anchor(Edge::Left, true);
+        window.set_margin(Edge::Top, PIN_MARGIN);
+        window.set_margin(Edge::Left, PIN_MARGIN);
+        window.set_keyboard_mode(KeyboardMode::OnDemand);
+        window.set_namespace(Some("ncaptura-pin"));
+        attach_layer_drag(&window, &picture);
+        window.set_content(Some(&picture));
+    } else {
+        let handle = gtk::WindowHandle::new();
+        handle.set_child(Some(&picture));
+        window.set_content(Some(&handle));
+    }
+
+    let key_controller = gtk::EventControllerKey::new();
+    {
+        let window = window.clone();
+        key_controller.connect_key_pressed(move |_, key, _, _| {
+            if key == gdk::Key::Escape {
+                window.close();
+                return Propagation::Stop;
+            }
+            Propagation::Proceed
+        });
+    }
+    window.add_controller(key_controller);
+
+    window.present();
+}
+
+fn attach_layer_drag(window: &adw::ApplicationWindow, picture: &gtk::Picture) {
+    let drag = gtk::GestureDrag::new();
+    {
+        let window = window.clone();
+        drag.connect_drag_update(move |_, offset_x, offset_y| {
+            let left = window.margin(Edge::Left) + offset_x.round() as i32;
+            let top = window.margin(Edge::Top) + offset_y.round() as i32;
+            window.set_margin(Edge::Left, left.max(0));
+            window.set_margin(Edge::Top, top.max(0));
+        });
+    }
+    picture.add_controller(drag);
+}
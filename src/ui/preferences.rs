@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::capture::{Container, QualityPreset, cli_state_dir};
+use crate::ui::hud_placement::{HudMargins, HudPosition};
+use crate::ui::interactive_dialog::CaptureMode;
+
+const PREFERENCES_FILE: &str = "preferences.json";
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct DialogPreferences {
+    pub mode: CaptureMode,
+    pub show_pointer: bool,
+    pub record_audio: bool,
+    pub record_cursor: bool,
+    pub highlight_clicks: bool,
+    pub show_keys: bool,
+    pub delay_seconds: u32,
+    pub time_limit_minutes: u32,
+    pub container: Container,
+    pub quality: Option<QualityPreset>,
+    pub hud_margins: HashMap<HudPosition, SavedHudMargins>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct SavedHudMargins {
+    pub base_margin: i32,
+    pub margins: HudMargins,
+}
+
+impl Default for DialogPreferences {
+    fn default() -> Self {
+        Self {
+            mode: CaptureMode::Selection,
+            show_pointer: false,
+            record_audio: false,
+            record_cursor: true,
+            highlight_clicks: false,
+            show_keys: false,
+            delay_seconds: 0,
+            time_limit_minutes: 0,
+            container: Container::default(),
+            quality: None,
+            hud_margins: HashMap::new(),
+        }
+    }
+}
+
+impl DialogPreferences {
+    pub fn load() -> Self {
+        let defaults = Self::default();
+        let Some(value) = preferences_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        else {
+            return defaults;
+        };
+
+        let u32_field = |key: &str, fallback: u32| {
+            value
+                .get(key)
+                .and_then(Value::as_u64)
+                .and_then(|number| u32::try_from(number).ok())
+                .unwrap_or(fallback)
+        };
+        let bool_field =
+            |key: &str, fallback: bool| value.get(key).and_then(Value::as_bool).unwrap_or(fallback);
+
+        Self {
+            mode: value
+                .get("mode")
+                .and_then(Value::as_str)
+                .and_then(mode_from_name)
+                .unwrap_or(defaults.mode),
+            show_pointer: bool_field("show_pointer", defaults.show_pointer),
+            record_audio: bool_field("record_audio", defaults.record_audio),
+            record_cursor: bool_field("record_cursor", defaults.record_cursor),
+            highlight_clicks: bool_field("highlight_clicks", defaults.highlight_clicks),
+            show_keys: bool_field("show_keys", defaults.show_keys),
+            delay_seconds: u32_field("delay_seconds", defaults.delay_seconds),
+            time_limit_minutes: u32_field("time_limit_minutes", defaults.time_limit_minutes),
+            container: value
+                .get("container")
+                .and_then(Value::as_str)
+                .and_then(Container::from_name)
+                .unwrap_or(defaults.container),
+            quality: value
+                .get("quality")
+                .and_then(Value::as_str)
+                .and_then(QualityPreset::from_name),
+            hud_margins: value
+                .get("hud_margins")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, saved)| {
+                    let position = HudPosition::from_name(name)?;
+                    let margin = |key: &str| {
+                        saved
+                            .get(key)
+                            .and_then(Value::as_u64)
+                            .and_then(|number| i32::try_from(number).ok())
+                    };
+                    let saved = SavedHudMargins {
+                        base_margin: margin("base_margin")?,
+                        margins: HudMargins {
+                            vertical: margin("vertical")?,
+                            horizontal: margin("horizontal")?,
+                        },
+                    };
+                    Some((position, saved))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn hud_margins(&self, position: HudPosition, base_margin: i32) -> Option<HudMargins> {
+        self.hud_margins
+            .get(&position)
+            .filter(|saved| saved.base_margin == base_margin)
+            .map(|saved| saved.margins)
+    }
+
+    pub fn update(&mut self, change: impl FnOnce(&mut Self)) {
+        let previous = self.clone();
+        change(self);
+        if *self == previous {
+            return;
+        }
+
+        if let Err(err) = self.save() {
+            eprintln!("保存偏好设置失败: {err}");
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = preferences_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("无法创建状态目录: {}", dir.display()))?;
+        }
+
+        let data = json!({
+            "mode": mode_name(self.mode),
+            "show_pointer": self.show_pointer,
+            "record_audio": self.record_audio,
+            "record_cursor": self.record_cursor,
+            "highlight_clicks": self.highlight_clicks,
+            "show_keys": self.show_keys,
+            "delay_seconds": self.delay_seconds,
+            "time_limit_minutes": self.time_limit_minutes,
+            "container": self.container.extension(),
+            "quality": self.quality.map(QualityPreset::name),
+            "hud_margins": self
+                .hud_margins
+                .iter()
+                .map(|(position, saved)| {
+                    let saved = json!({
+                        "base_margin": saved.base_margin,
+                        "vertical": saved.margins.vertical,
+                        "horizontal": saved.margins.horizontal,
+                    });
+                    (position.name().to_string(), saved)
+                })
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        fs::write(&path, data.to_string())
+            .with_context(|| format!("无法写入偏好设置: {}", path.display()))
+    }
+}
+
+fn preferences_path() -> Result<PathBuf> {
+    Ok(cli_state_dir()?.join(PREFERENCES_FILE))
+}
+
+fn mode_name(mode: CaptureMode) -> &'static str {
+    match mode {
+        CaptureMode::Screen => "screen",
+        CaptureMode::Window => "window",
+        CaptureMode::Selection => "selection",
+    }
+}
+
+fn mode_from_name(name: &str) -> Option<CaptureMode> {
+    match name {
+        "screen" => Some(CaptureMode::Screen),
+        "window" => Some(CaptureMode::Window),
+        "selection" => Some(CaptureMode::Selection),
+        _ => None,
+    }
+}
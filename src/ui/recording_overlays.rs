@@ -0,0 +1,25 @@
+use crate::ui::click_overlay::{ClickOverlay, show_click_overlay};
+use crate::ui::key_overlay::{KeyOverlay, show_key_overlay};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordingOverlays {
+    pub highlight_clicks: bool,
+    pub show_keys: bool,
+}
+
+pub(super) struct ActiveOverlays {
+    _clicks: Option<ClickOverlay>,
+    _keys: Option<KeyOverlay>,
+}
+
+impl RecordingOverlays {
+    pub(super) fn show(self, app: &adw::Application) -> ActiveOverlays {
+        ActiveOverlays {
+            _clicks: self
+                .highlight_clicks
+                .then(|| show_click_overlay(app))
+                .flatten(),
+            _keys: self.show_keys.then(|| show_key_overlay(app)).flatten(),
+        }
+    }
+}
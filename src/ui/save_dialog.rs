@@ -1,19 +1,36 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::process;
 use std::rc::Rc;
 
 use adw::prelude::*;
 use gtk::gdk;
 use gtk::gdk::prelude::GdkCairoContextExt;
-use gtk::gdk_pixbuf::Pixbuf;
+use gtk::gdk_pixbuf::{InterpType, Pixbuf};
+
+use crate::capture::{copy_text_to_clipboard, set_clipboard_image};
+use crate::config::config;
+use crate::imaging::{Encoding, save_image};
+use crate::launch::open_with_default_app;
+use crate::notify::notify;
+use crate::ui::annotate::{show_annotation_editor, view_transform};
+use crate::ui::pin_window::show_pinned_image;
+use crate::upload::upload_image;
+
+const REDACTION_BLOCK_SIZE: i32 = 12;
+
+type Selection = (f64, f64, f64, f64);
 
 pub fn build_save_dialog(
     app: &adw::Application,
     screenshot: &Pixbuf,
-    initial_folder: &PathBuf,
+    initial_folder: &Path,
     initial_filename: &str,
 ) -> adw::ApplicationWindow {
-    let selected_folder = Rc::new(RefCell::new(initial_folder.clone()));
+    let selected_folder = Rc::new(RefCell::new(initial_folder.to_path_buf()));
+    let screenshot = Rc::new(RefCell::new(screenshot.clone()));
 
     let window = adw::ApplicationWindow::builder()
         .application(app)
@@ -38,20 +55,61 @@ pub fn build_save_dialog(
     {
         let screenshot = screenshot.clone();
         copy_button.connect_clicked(move |_| {
-            if let Some(display) = gdk::Display::default() {
-                let clipboard = display.clipboard();
-                let texture = gdk::Texture::for_pixbuf(&screenshot);
-                clipboard.set_texture(&texture);
+            if let Err(err) = set_clipboard_image(&screenshot.borrow()) {
+                eprintln!("复制截图失败: {err}");
             }
         });
     }
     header.pack_end(&copy_button);
 
+    let copy_path_button = gtk::Button::from_icon_name("edit-copy-symbolic");
+    copy_path_button.set_tooltip_text(Some("Copy Path"));
+    header.pack_end(&copy_path_button);
+
+    let pin_button = gtk::Button::from_icon_name("view-pin-symbolic");
+    pin_button.set_tooltip_text(Some("Pin to Screen"));
+    {
+        let app = app.clone();
+        let screenshot = screenshot.clone();
+        pin_button.connect_clicked(move |_| {
+            show_pinned_image(&app, &screenshot.borrow());
+        });
+    }
+    header.pack_start(&pin_button);
+
+    let upload_button = gtk::Button::with_label("Upload");
+    upload_button.set_tooltip_text(Some("Upload and copy the link to the clipboard"));
+    upload_button.set_visible(config().upload_endpoint.is_some());
+    header.pack_end(&upload_button);
+
     let save_button = gtk::Button::with_label("Save");
     save_button.add_css_class("suggested-action");
     window.set_default_widget(Some(&save_button));
     header.pack_end(&save_button);
 
+    let open_button = gtk::Button::from_icon_name("document-open-symbolic");
+    open_button.set_tooltip_text(Some("Save and Open"));
+    header.pack_end(&open_button);
+
+    let open_folder_button = gtk::Button::from_icon_name("folder-open-symbolic");
+    open_folder_button.set_tooltip_text(Some("Open Folder"));
+    header.pack_end(&open_folder_button);
+
+    let edit_button = gtk::Button::with_label("Edit");
+    header.pack_start(&edit_button);
+
+    let redact_button = gtk::ToggleButton::with_label("Redact");
+    redact_button.set_tooltip_text(Some("Drag over the preview to pixelate a region"));
+    header.pack_start(&redact_button);
+
+    let undo_redaction_button = gtk::Button::from_icon_name("edit-undo-symbolic");
+    undo_redaction_button.set_tooltip_text(Some("Undo Last Redaction"));
+    undo_redaction_button.set_sensitive(false);
+    header.pack_start(&undo_redaction_button);
+
+    let redaction_selection: Rc<Cell<Option<Selection>>> = Rc::new(Cell::new(None));
+    let redaction_history: Rc<RefCell<Vec<Pixbuf>>> = Rc::new(RefCell::new(Vec::new()));
+
     let root = gtk::Box::new(gtk::Orientation::Vertical, 0);
     root.append(&header);
 
@@ -72,20 +130,13 @@ pub fn build_save_dialog(
     preview_area.set_vexpand(true);
     {
         let screenshot = screenshot.clone();
+        let redaction_selection = redaction_selection.clone();
         preview_area.set_draw_func(move |_, cr, width, height| {
-            let source_width = screenshot.width() as f64;
-            let source_height = screenshot.height() as f64;
-            if source_width <= 0.0 || source_height <= 0.0 {
+            let screenshot = screenshot.borrow();
+            let Some((scale, offset_x, offset_y)) = view_transform(&screenshot, width, height)
+            else {
                 return;
-            }
-
-            let target_width = width as f64;
-            let target_height = height as f64;
-            let scale = f64::min(target_width / source_width, target_height / source_height);
-            let draw_width = source_width * scale;
-            let draw_height = source_height * scale;
-            let offset_x = (target_width - draw_width) / 2.0;
-            let offset_y = (target_height - draw_height) / 2.0;
+            };
 
             cr.save().ok();
             cr.translate(offset_x, offset_y);
@@ -93,10 +144,98 @@ pub fn build_save_dialog(
             cr.set_source_pixbuf(&screenshot, 0.0, 0.0);
             let _ = cr.paint();
             cr.restore().ok();
+
+            if let Some((x, y, w, h)) = redaction_selection.get() {
+                cr.set_source_rgba(0.0, 0.0, 0.0, 0.35);
+                cr.rectangle(x, y, w, h);
+                let _ = cr.fill_preserve();
+                cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+                cr.set_line_width(1.0);
+                let _ = cr.stroke();
+            }
         });
     }
     content.append(&preview_area);
 
+    let redaction_drag = gtk::GestureDrag::new();
+    {
+        let redact_button = redact_button.clone();
+        let redaction_selection = redaction_selection.clone();
+        redaction_drag.connect_drag_begin(move |_, x, y| {
+            if redact_button.is_active() {
+                redaction_selection.set(Some((x, y, 0.0, 0.0)));
+            }
+        });
+    }
+    {
+        let redact_button = redact_button.clone();
+        let redaction_selection = redaction_selection.clone();
+        let preview_area = preview_area.clone();
+        redaction_drag.connect_drag_update(move |gesture, offset_x, offset_y| {
+            if !redact_button.is_active() {
+                return;
+            }
+            if let Some((start_x, start_y)) = gesture.start_point() {
+                let x = start_x.min(start_x + offset_x);
+                let y = start_y.min(start_y + offset_y);
+                redaction_selection.set(Some((x, y, offset_x.abs(), offset_y.abs())));
+                preview_area.queue_draw();
+            }
+        });
+    }
+    {
+        let screenshot = screenshot.clone();
+        let redaction_selection = redaction_selection.clone();
+        let redaction_history = redaction_history.clone();
+        let undo_redaction_button = undo_redaction_button.clone();
+        let preview_area = preview_area.clone();
+        redaction_drag.connect_drag_end(move |_, _, _| {
+            let Some((x, y, w, h)) = redaction_selection.take() else {
+                return;
+            };
+            preview_area.queue_draw();
+
+            let current = screenshot.borrow().clone();
+            let Some((scale, offset_x, offset_y)) =
+                view_transform(&current, preview_area.width(), preview_area.height())
+            else {
+                return;
+            };
+
+            let left = (((x - offset_x) / scale).floor() as i32).clamp(0, current.width());
+            let top = (((y - offset_y) / scale).floor() as i32).clamp(0, current.height());
+            let right = (((x + w - offset_x) / scale).ceil() as i32).clamp(0, current.width());
+            let bottom = (((y + h - offset_y) / scale).ceil() as i32).clamp(0, current.height());
+            if right - left < 1 || bottom - top < 1 {
+                return;
+            }
+
+            let Some(redacted) = pixelate_region(&current, left, top, right - left, bottom - top)
+            else {
+                eprintln!("打码失败");
+                return;
+            };
+            redaction_history.borrow_mut().push(current);
+            *screenshot.borrow_mut() = redacted;
+            undo_redaction_button.set_sensitive(true);
+        });
+    }
+    preview_area.add_controller(redaction_drag);
+
+    {
+        let screenshot = screenshot.clone();
+        let redaction_history = redaction_history.clone();
+        let preview_area = preview_area.clone();
+        undo_redaction_button.connect_clicked(move |button| {
+            let mut history = redaction_history.borrow_mut();
+            if let Some(previous) = history.pop() {
+                *screenshot.borrow_mut() = previous;
+                preview_area.queue_draw();
+            }
+            button.set_sensitive(!history.is_empty());
+        });
+    }
+
     let form_grid = gtk::Grid::new();
     form_grid.set_halign(gtk::Align::Center);
     form_grid.set_row_spacing(6);
@@ -113,6 +252,15 @@ pub fn build_save_dialog(
     let selected_char_count = selected_filename_chars(initial_filename);
     name_entry.select_region(0, selected_char_count);
 
+    let name_error_label = gtk::Label::new(None);
+    name_error_label.add_css_class("error");
+    name_error_label.set_halign(gtk::Align::Start);
+    name_error_label.set_visible(false);
+    {
+        let name_error_label = name_error_label.clone();
+        name_entry.connect_changed(move |_| name_error_label.set_visible(false));
+    }
+
     let folder_label = gtk::Label::new(Some("Folder:"));
     folder_label.set_halign(gtk::Align::End);
 
@@ -135,13 +283,12 @@ pub fn build_save_dialog(
             let folder_button = folder_button.clone();
             let selected_folder = selected_folder.clone();
             chooser.connect_response(move |chooser, response| {
-                if response == gtk::ResponseType::Accept {
-                    if let Some(file) = chooser.file() {
-                        if let Some(path) = file.path() {
-                            *selected_folder.borrow_mut() = path.clone();
-                            folder_button.set_label(&path.to_string_lossy());
-                        }
-                    }
+                if response == gtk::ResponseType::Accept
+                    && let Some(file) = chooser.file()
+                    && let Some(path) = file.path()
+                {
+                    *selected_folder.borrow_mut() = path.clone();
+                    folder_button.set_label(&path.to_string_lossy());
                 }
             });
             chooser.show();
@@ -150,8 +297,95 @@ pub fn build_save_dialog(
 
     {
         let window = window.clone();
+        let screenshot = screenshot.clone();
+        let name_entry = name_entry.clone();
+        let name_error_label = name_error_label.clone();
+        let selected_folder = selected_folder.clone();
         save_button.connect_clicked(move |_| {
-            window.close();
+            let Some(path) =
+                validated_save_path(&selected_folder.borrow(), &name_entry, &name_error_label)
+            else {
+                return;
+            };
+            let screenshot = screenshot.clone();
+            let dialog_window = window.clone();
+            confirm_overwrite(&window, path, move |path| {
+                if save_screenshot(&screenshot.borrow(), &path) {
+                    dialog_window.close();
+                }
+            });
+        });
+    }
+
+    {
+        let window = window.clone();
+        let screenshot = screenshot.clone();
+        let name_entry = name_entry.clone();
+        let name_error_label = name_error_label.clone();
+        let selected_folder = selected_folder.clone();
+        open_button.connect_clicked(move |_| {
+            let Some(path) =
+                validated_save_path(&selected_folder.borrow(), &name_entry, &name_error_label)
+            else {
+                return;
+            };
+            let screenshot = screenshot.clone();
+            let dialog_window = window.clone();
+            confirm_overwrite(&window, path, move |path| {
+                if !save_screenshot(&screenshot.borrow(), &path) {
+                    return;
+                }
+                if let Err(err) = open_with_default_app(&path) {
+                    eprintln!("无法打开截图: {err}");
+                }
+                dialog_window.close();
+            });
+        });
+    }
+
+    {
+        let name_entry = name_entry.clone();
+        let name_error_label = name_error_label.clone();
+        let selected_folder = selected_folder.clone();
+        copy_path_button.connect_clicked(move |_| {
+            let Some(path) =
+                validated_save_path(&selected_folder.borrow(), &name_entry, &name_error_label)
+            else {
+                return;
+            };
+            if let Err(err) = copy_text_to_clipboard(&path.to_string_lossy()) {
+                eprintln!("复制路径失败: {err}");
+            }
+        });
+    }
+
+    {
+        let selected_folder = selected_folder.clone();
+        open_folder_button.connect_clicked(move |_| {
+            if let Err(err) = open_with_default_app(&selected_folder.borrow()) {
+                eprintln!("无法打开目录: {err}");
+            }
+        });
+    }
+
+    {
+        let app = app.clone();
+        let window = window.clone();
+        let screenshot = screenshot.clone();
+        let redaction_history = redaction_history.clone();
+        let undo_redaction_button = undo_redaction_button.clone();
+        let preview_area = preview_area.clone();
+        edit_button.connect_clicked(move |_| {
+            let screenshot_handle = screenshot.clone();
+            let redaction_history = redaction_history.clone();
+            let undo_redaction_button = undo_redaction_button.clone();
+            let preview_area = preview_area.clone();
+            show_annotation_editor(&app, &window, &screenshot.borrow(), move |edited| {
+                *screenshot_handle.borrow_mut() = edited;
+                redaction_history.borrow_mut().clear();
+                undo_redaction_button.set_sensitive(false);
+                preview_area.queue_draw();
+            });
         });
     }
 
@@ -159,8 +393,64 @@ pub fn build_save_dialog(
     form_grid.attach(&name_entry, 1, 0, 1, 1);
     form_grid.attach(&folder_label, 0, 1, 1, 1);
     form_grid.attach(&folder_button, 1, 1, 1, 1);
+    form_grid.attach(&name_error_label, 1, 2, 1, 1);
+
+    let status_label = gtk::Label::new(None);
+    status_label.add_css_class("dim-label");
+    status_label.set_selectable(true);
+    status_label.set_wrap(true);
+    status_label.set_visible(false);
+
+    {
+        let screenshot = screenshot.clone();
+        let status_label = status_label.clone();
+        upload_button.connect_clicked(move |button| {
+            let Some(endpoint) = config().upload_endpoint.clone() else {
+                return;
+            };
+
+            let upload_path =
+                env::temp_dir().join(format!("ncaptura-upload-{}.png", process::id()));
+            if let Err(err) = save_image(&screenshot.borrow(), &upload_path, Encoding::Png) {
+                status_label.set_text(&format!("Upload failed: {err:#}"));
+                status_label.set_visible(true);
+                return;
+            }
+
+            button.set_sensitive(false);
+            status_label.set_text("Uploading…");
+            status_label.set_visible(true);
+
+            let button = button.clone();
+            let status_label = status_label.clone();
+            gtk::glib::spawn_future_local(async move {
+                let task_path = upload_path.clone();
+                let result = gtk::gio::spawn_blocking(move || {
+                    upload_image(&task_path, &endpoint, &config().upload_headers)
+                })
+                .await;
+                let _ = fs::remove_file(&upload_path);
+                button.set_sensitive(true);
+
+                match result {
+                    Ok(Ok(url)) => {
+                        if let Some(display) = gdk::Display::default() {
+                            display.clipboard().set_text(&url);
+                        }
+                        status_label.set_text(&format!("Uploaded, link copied: {url}"));
+                    }
+                    Ok(Err(err)) => {
+                        eprintln!("{err}");
+                        status_label.set_text(&format!("Upload failed: {err}"));
+                    }
+                    Err(_) => status_label.set_text("Upload failed: worker thread panicked"),
+                }
+            });
+        });
+    }
 
     content.append(&form_grid);
+    content.append(&status_label);
     root.append(&content);
     window.set_content(Some(&root));
 
@@ -182,6 +472,106 @@ pub fn build_save_dialog(
     window
 }
 
+fn save_screenshot(screenshot: &Pixbuf, path: &Path) -> bool {
+    let encoding = Encoding::from_extension(path).unwrap_or(Encoding::Png);
+    match save_image(screenshot, path, encoding) {
+        Ok(()) => {
+            notify("截图已保存", &path.display().to_string(), Some(path));
+            true
+        }
+        Err(err) => {
+            eprintln!("保存截图失败: {err:#}");
+            false
+        }
+    }
+}
+
+fn validated_save_path(
+    folder: &Path,
+    name_entry: &gtk::Entry,
+    error_label: &gtk::Label,
+) -> Option<PathBuf> {
+    match sanitize_filename(&name_entry.text()) {
+        Ok(filename) => {
+            if filename != name_entry.text().as_str() {
+                name_entry.set_text(&filename);
+            }
+            error_label.set_visible(false);
+            Some(folder.join(filename))
+        }
+        Err(message) => {
+            error_label.set_text(message);
+            error_label.set_visible(true);
+            name_entry.grab_focus();
+            None
+        }
+    }
+}
+
+fn sanitize_filename(raw: &str) -> Result<String, &'static str> {
+    let filename = raw.trim().replace('/', "-");
+    if filename.is_empty() {
+        return Err("Filename cannot be empty");
+    }
+    if filename == "." || filename == ".." {
+        return Err("Filename is not valid");
+    }
+
+    if Encoding::from_extension(Path::new(&filename)).is_some() {
+        Ok(filename)
+    } else {
+        Ok(format!("{filename}.png"))
+    }
+}
+
+fn confirm_overwrite(
+    window: &adw::ApplicationWindow,
+    path: PathBuf,
+    on_confirm: impl Fn(PathBuf) + 'static,
+) {
+    if !path.exists() {
+        on_confirm(path);
+        return;
+    }
+
+    let filename = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let dialog = gtk::MessageDialog::builder()
+        .transient_for(window)
+        .modal(true)
+        .message_type(gtk::MessageType::Warning)
+        .text(format!("Replace “{filename}”?"))
+        .secondary_text("A file with this name already exists in the selected folder.")
+        .build();
+    dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+    let replace_button = dialog.add_button("Replace", gtk::ResponseType::Accept);
+    replace_button.add_css_class("destructive-action");
+    dialog.set_default_response(gtk::ResponseType::Cancel);
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+        if response == gtk::ResponseType::Accept {
+            on_confirm(path.clone());
+        }
+    });
+    dialog.present();
+}
+
+fn pixelate_region(source: &Pixbuf, x: i32, y: i32, width: i32, height: i32) -> Option<Pixbuf> {
+    let redacted = source.copy()?;
+    let region = source.new_subpixbuf(x, y, width, height);
+    let reduced = region.scale_simple(
+        (width / REDACTION_BLOCK_SIZE).max(1),
+        (height / REDACTION_BLOCK_SIZE).max(1),
+        InterpType::Bilinear,
+    )?;
+    let mosaic = reduced.scale_simple(width, height, InterpType::Nearest)?;
+    mosaic.copy_area(0, 0, width, height, &redacted, x, y);
+    Some(redacted)
+}
+
 fn selected_filename_chars(filename: &str) -> i32 {
     let stem = Path::new(filename)
         .file_stem()
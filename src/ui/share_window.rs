@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use adw::prelude::*;
+use gtk::{Align, Box as GtkBox, Label, Orientation};
+
+pub fn run_share_window(qr_path: PathBuf, caption: String, timeout: Option<Duration>) {
+    let app = adw::Application::builder()
+        .application_id("io.ncaptura.app.share")
+        .build();
+
+    app.connect_activate(move |app| {
+        build_share_window(app, &qr_path, &caption, timeout);
+    });
+    let _ = app.run_with_args(&["ncaptura-share"]);
+}
+
+fn build_share_window(
+    app: &adw::Application,
+    qr_path: &Path,
+    caption: &str,
+    timeout: Option<Duration>,
+) {
+    let window = adw::ApplicationWindow::builder()
+        .application(app)
+        .title("Share via QR Code")
+        .default_width(420)
+        .default_height(520)
+        .build();
+
+    let header = adw::HeaderBar::new();
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+
+    let picture = gtk::Picture::for_filename(qr_path);
+    picture.set_can_shrink(true);
+    picture.set_hexpand(true);
+    picture.set_vexpand(true);
+
+    let caption_label = Label::new(Some(caption));
+    caption_label.set_selectable(true);
+    caption_label.set_wrap(true);
+    caption_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+    caption_label.set_halign(Align::Center);
+
+    let countdown_label = Label::new(None);
+    countdown_label.add_css_class("dim-label");
+    countdown_label.set_halign(Align::Center);
+
+    content.append(&picture);
+    content.append(&caption_label);
+    content.append(&countdown_label);
+
+    let root = GtkBox::new(Orientation::Vertical, 0);
+    root.append(&header);
+    root.append(&content);
+    window.set_content(Some(&root));
+
+    if let Some(timeout) = timeout {
+        let remaining = Rc::new(Cell::new(timeout.as_secs()));
+        countdown_label.set_text(&expiry_text(remaining.get()));
+
+        let window = window.clone();
+        gtk::glib::timeout_add_local(Duration::from_secs(1), move || {
+            let left = remaining.get().saturating_sub(1);
+            remaining.set(left);
+            if left == 0 {
+                window.close();
+                return gtk::glib::ControlFlow::Break;
+            }
+
+            countdown_label.set_text(&expiry_text(left));
+            gtk::glib::ControlFlow::Continue
+        });
+    } else {
+        countdown_label.set_visible(false);
+    }
+
+    let key_controller = gtk::EventControllerKey::new();
+    {
+        let window = window.clone();
+        key_controller.connect_key_pressed(move |_, key, _, _| {
+            if key == gtk::gdk::Key::Escape {
+                window.close();
+                return gtk::glib::Propagation::Stop;
+            }
+
+            gtk::glib::Propagation::Proceed
+        });
+    }
+    window.add_controller(key_controller);
+
+    window.present();
+}
+
+fn expiry_text(seconds: u64) -> String {
+    format!("Link expires in {:02}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,85 @@
+use adw::prelude::*;
+
+const SHORTCUTS_UI: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<interface>
+  <object class="GtkShortcutsWindow" id="shortcuts">
+    <property name="modal">1</property>
+    <child>
+      <object class="GtkShortcutsSection">
+        <property name="section-name">capture</property>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">Capture Mode</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Selection</property>
+                <property name="accelerator">s</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Window</property>
+                <property name="accelerator">w</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Screen</property>
+                <property name="accelerator">f</property>
+              </object>
+            </child>
+          </object>
+        </child>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">Capture</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Take screenshot or start/stop recording</property>
+                <property name="accelerator">Return</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Close the dialog</property>
+                <property name="accelerator">Escape</property>
+              </object>
+            </child>
+          </object>
+        </child>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">General</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Keyboard shortcuts</property>
+                <property name="accelerator">&lt;Control&gt;question</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Preferences</property>
+                <property name="accelerator">&lt;Control&gt;comma</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Quit</property>
+                <property name="accelerator">&lt;Control&gt;q</property>
+              </object>
+            </child>
+          </object>
+        </child>
+      </object>
+    </child>
+  </object>
+</interface>
+"#;
+
+pub(super) fn attach_shortcuts_window(window: &adw::ApplicationWindow) {
+    let builder = gtk::Builder::from_string(SHORTCUTS_UI);
+    let Some(shortcuts) = builder.object::<gtk::ShortcutsWindow>("shortcuts") else {
+        return;
+    };
+    window.set_help_overlay(Some(&shortcuts));
+}
@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::os::fd::OwnedFd;
+use std::rc::Rc;
+
+use gtk::gio;
+use gtk::gio::prelude::*;
+use gtk::glib;
+
+const LOGIND_BUS_NAME: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+
+pub(super) struct SleepWatch {
+    _subscription: gio::SignalSubscription,
+}
+
+pub(super) fn watch_system_sleep(on_change: impl Fn(bool) + 'static) -> Option<SleepWatch> {
+    let connection = match gio::bus_get_sync(gio::BusType::System, None::<&gio::Cancellable>) {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("无法连接系统 D-Bus，挂起时不会自动暂停录屏: {err}");
+            return None;
+        }
+    };
+
+    let inhibitor = Rc::new(RefCell::new(take_delay_inhibitor(&connection)));
+    let subscription = connection.subscribe_to_signal(
+        Some(LOGIND_BUS_NAME),
+        Some(LOGIND_MANAGER),
+        Some("PrepareForSleep"),
+        Some(LOGIND_PATH),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |signal| {
+            let Some((sleeping,)) = signal.parameters.get::<(bool,)>() else {
+                return;
+            };
+
+            on_change(sleeping);
+            if sleeping {
+                inhibitor.borrow_mut().take();
+            } else {
+                *inhibitor.borrow_mut() = take_delay_inhibitor(signal.connection);
+            }
+        },
+    );
+
+    Some(SleepWatch {
+        _subscription: subscription,
+    })
+}
+
+fn take_delay_inhibitor(connection: &gio::DBusConnection) -> Option<OwnedFd> {
+    let (_, fd_list) = connection
+        .call_with_unix_fd_list_sync(
+            Some(LOGIND_BUS_NAME),
+            LOGIND_PATH,
+            LOGIND_MANAGER,
+            "Inhibit",
+            Some(
+                &(
+                    "sleep",
+                    "NCaptura",
+                    "Pause recording before suspend",
+                    "delay",
+                )
+                    .to_variant(),
+            ),
+            Some(glib::VariantTy::new("(h)").ok()?),
+            gio::DBusCallFlags::NONE,
+            -1,
+            None::<&gio::UnixFDList>,
+            None::<&gio::Cancellable>,
+        )
+        .inspect_err(|err| eprintln!("无法获取挂起延迟锁: {err}"))
+        .ok()?;
+
+    fd_list?.get(0).ok()
+}
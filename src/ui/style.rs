@@ -0,0 +1,112 @@
+use std::cell::Cell;
+
+use gtk::CssProvider;
+
+use crate::config::{DEFAULT_INDICATOR_COLOR, config};
+
+const APP_CSS: &str = "
+window.countdown-overlay {
+    background: rgba(30, 30, 30, 0.88);
+    border-radius: 24px;
+}
+
+window.countdown-overlay label.countdown-label {
+    color: white;
+    font-size: 56px;
+    font-weight: 700;
+}
+
+window.click-overlay {
+    background: transparent;
+}
+
+window.key-overlay {
+    background: transparent;
+}
+
+window.key-overlay label.keystroke {
+    background: rgba(30, 30, 30, 0.88);
+    color: white;
+    border-radius: 10px;
+    padding: 8px 14px;
+    font-size: 22px;
+    font-weight: 700;
+}
+
+window.recording-hud {
+    background: rgba(30, 30, 30, 0.88);
+    border-radius: 14px;
+}
+
+window.recording-hud label.recording-indicator {
+    color: @recording_indicator_color;
+    font-size: 10px;
+    font-weight: 700;
+}
+
+window.recording-hud label.recording-indicator.paused {
+    color: #f4b400;
+}
+
+window.recording-hud image.audio-warning {
+    color: #f4b400;
+}
+
+window.recording-hud button.stop-record-btn {
+    min-width: 34px;
+    min-height: 34px;
+    border-radius: 999px;
+    background: #d32f2f;
+    color: white;
+}
+
+window.recording-hud button.pause-record-btn {
+    min-width: 34px;
+    min-height: 34px;
+    border-radius: 999px;
+}
+
+window.recording-hud button.collapse-hud-btn {
+    min-width: 24px;
+    min-height: 24px;
+    padding: 0;
+}
+
+window.recording-hud.collapsed label.recording-indicator {
+    font-size: 16px;
+}
+";
+
+thread_local! {
+    static CSS_INSTALLED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn install_app_css() {
+    if CSS_INSTALLED.get() {
+        return;
+    }
+    let Some(display) = gtk::gdk::Display::default() else {
+        return;
+    };
+
+    let provider = CssProvider::new();
+    provider.load_from_data(&format!(
+        "@define-color recording_indicator_color {};\n{APP_CSS}",
+        indicator_color()
+    ));
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+    CSS_INSTALLED.set(true);
+}
+
+fn indicator_color() -> String {
+    let color = &config().hud_indicator_color;
+    if gtk::gdk::RGBA::parse(color.as_str()).is_ok() {
+        return color.clone();
+    }
+    eprintln!("无效的录制指示灯颜色: {color}，使用默认颜色");
+    DEFAULT_INDICATOR_COLOR.to_string()
+}
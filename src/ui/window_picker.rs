@@ -1,15 +1,26 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use adw::prelude::*;
+use gtk::gdk;
+use gtk::glib::markup_escape_text;
 
-use crate::capture::WindowInfo;
+use crate::capture::{WindowInfo, WorkspaceInfo};
+
+const APP_ICON_SIZE: i32 = 32;
+const FALLBACK_APP_ICON: &str = "application-x-executable";
+const PICKER_HINT: &str = "选择要截图的窗口（数字键 1-9 快速选择，输入文字筛选）";
+const RECORDING_PICKER_HINT: &str =
+    "选择要录制的窗口（数字键 1-9 快速选择，输入文字筛选），录制区域在开始时固定";
 
 pub fn show_window_picker(
     app: &adw::Application,
     windows: Vec<WindowInfo>,
+    workspaces: &[WorkspaceInfo],
+    for_recording: bool,
     guard: gtk::gio::ApplicationHoldGuard,
-    on_capture: impl Fn(u64, gtk::gio::ApplicationHoldGuard) + 'static,
+    on_capture: impl Fn(u64, bool, gtk::gio::ApplicationHoldGuard) + 'static,
 ) {
     let picker = adw::ApplicationWindow::builder()
         .application(app)
@@ -25,18 +36,40 @@ pub fn show_window_picker(
     root.set_margin_start(16);
     root.set_margin_end(16);
 
-    let hint = gtk::Label::new(Some("选择要截图的窗口"));
+    let hint = gtk::Label::new(Some(if for_recording {
+        RECORDING_PICKER_HINT
+    } else {
+        PICKER_HINT
+    }));
     hint.set_halign(gtk::Align::Start);
     root.append(&hint);
 
+    let search_entry = gtk::SearchEntry::new();
+    search_entry.set_placeholder_text(Some("按标题或应用筛选"));
+    search_entry.set_key_capture_widget(Some(&picker));
+    root.append(&search_entry);
+
     let list = gtk::ListBox::new();
     list.set_selection_mode(gtk::SelectionMode::Single);
     list.add_css_class("boxed-list");
     list.set_vexpand(true);
 
+    let workspace_labels: HashMap<u64, String> = workspaces
+        .iter()
+        .map(|workspace| (workspace.id, workspace.label()))
+        .collect();
+    let workspace_label = move |window: &WindowInfo| {
+        workspace_labels
+            .get(&window.workspace_id)
+            .cloned()
+            .unwrap_or_else(|| format!("workspace {}", window.workspace_id))
+    };
+
+    let icon_theme = gtk::IconTheme::for_display(&WidgetExt::display(&picker));
+    let mut row_labels = Vec::with_capacity(windows.len());
     for window in &windows {
         let row = gtk::ListBoxRow::new();
-        let row_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
         row_box.set_margin_top(8);
         row_box.set_margin_bottom(8);
         row_box.set_margin_start(8);
@@ -46,25 +79,43 @@ pub fn show_window_picker(
         title.set_halign(gtk::Align::Start);
         title.set_wrap(true);
 
-        let subtitle = gtk::Label::new(Some(&format!(
-            "{}  |  workspace {}  |  id {}",
-            window.app_id, window.workspace_id, window.id
-        )));
+        let subtitle = gtk::Label::new(None);
+        subtitle.set_markup(&subtitle_markup(window, &workspace_label(window), ""));
         subtitle.set_halign(gtk::Align::Start);
         subtitle.add_css_class("dim-label");
 
-        row_box.append(&title);
-        row_box.append(&subtitle);
+        let icon = gtk::Image::from_paintable(Some(&app_icon(
+            &icon_theme,
+            &window.app_id,
+            picker.scale_factor(),
+        )));
+        icon.set_pixel_size(APP_ICON_SIZE);
+        icon.set_valign(gtk::Align::Center);
+
+        let text_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        text_box.append(&title);
+        text_box.append(&subtitle);
+        row_box.append(&icon);
+        row_box.append(&text_box);
         row.set_child(Some(&row_box));
         list.append(&row);
+        row_labels.push((title, subtitle));
     }
 
     root.append(&list);
 
     let action_row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-    action_row.set_halign(gtk::Align::End);
+    let include_shadow = gtk::CheckButton::with_label("Include Shadow");
+    include_shadow.set_tooltip_text(Some("Capture extra margins around the window"));
+    include_shadow.set_hexpand(true);
+    include_shadow.set_halign(gtk::Align::Start);
+    action_row.append(&include_shadow);
     let cancel = gtk::Button::with_label("Cancel");
-    let capture_btn = gtk::Button::with_label("Take Screenshot");
+    let capture_btn = gtk::Button::with_label(if for_recording {
+        "Start Recording"
+    } else {
+        "Take Screenshot"
+    });
     capture_btn.add_css_class("suggested-action");
     action_row.append(&cancel);
     action_row.append(&capture_btn);
@@ -73,37 +124,48 @@ pub fn show_window_picker(
     picker.set_content(Some(&root));
 
     let windows = Rc::new(windows);
+    let filter = Rc::new(RefCell::new(String::new()));
     let selected_index = Rc::new(RefCell::new(Some(0usize)));
     list.select_row(list.row_at_index(0).as_ref());
 
+    {
+        let windows = windows.clone();
+        let filter = filter.clone();
+        list.set_filter_func(move |row| {
+            usize::try_from(row.index())
+                .ok()
+                .and_then(|index| windows.get(index))
+                .is_some_and(|window| window_matches(window, &filter.borrow()))
+        });
+    }
+
     {
         let selected_index = selected_index.clone();
+        let capture_btn = capture_btn.clone();
         list.connect_selected_rows_changed(move |listbox| {
             let row = listbox.selected_row();
             *selected_index.borrow_mut() = row.map(|r| r.index() as usize);
+            capture_btn.set_sensitive(selected_index.borrow().is_some());
         });
     }
 
     let guard_cell = Rc::new(RefCell::new(Some(guard)));
 
-    {
+    let cancel_picker = {
         let picker = picker.clone();
         let guard_cell = guard_cell.clone();
-        cancel.connect_clicked(move |_| {
+        Rc::new(move || {
             picker.destroy();
             let _ = guard_cell.borrow_mut().take();
-        });
-    }
+        })
+    };
 
-    {
+    let capture_window: Rc<dyn Fn(usize)> = {
         let picker = picker.clone();
         let windows = windows.clone();
-        let selected_index = selected_index.clone();
         let guard_cell = guard_cell.clone();
-        capture_btn.connect_clicked(move |_| {
-            let Some(idx) = *selected_index.borrow() else {
-                return;
-            };
+        let include_shadow = include_shadow.clone();
+        Rc::new(move |idx| {
             let Some(info) = windows.get(idx) else {
                 return;
             };
@@ -111,10 +173,142 @@ pub fn show_window_picker(
                 return;
             };
 
+            let include_shadow = include_shadow.is_active();
             picker.destroy();
-            on_capture(info.id, guard);
+            on_capture(info.id, include_shadow, guard);
+        })
+    };
+
+    {
+        let list = list.clone();
+        let windows = windows.clone();
+        let filter = filter.clone();
+        search_entry.connect_search_changed(move |entry| {
+            *filter.borrow_mut() = entry.text().to_lowercase();
+            let query = filter.borrow();
+            list.invalidate_filter();
+            for ((title, subtitle), window) in row_labels.iter().zip(windows.iter()) {
+                title.set_markup(&highlight_markup(&window.title, &query));
+                subtitle.set_markup(&subtitle_markup(window, &workspace_label(window), &query));
+            }
+            list.select_row(visible_rows(&list).first());
+        });
+    }
+
+    {
+        let cancel_picker = cancel_picker.clone();
+        cancel.connect_clicked(move |_| cancel_picker());
+    }
+
+    {
+        let selected_index = selected_index.clone();
+        let capture_window = capture_window.clone();
+        capture_btn.connect_clicked(move |_| {
+            let Some(idx) = *selected_index.borrow() else {
+                return;
+            };
+            capture_window(idx);
+        });
+    }
+
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    {
+        let list = list.clone();
+        key_controller.connect_key_pressed(move |_, key, _, modifiers| {
+            match key {
+                gdk::Key::Escape => {
+                    cancel_picker();
+                    return gtk::glib::Propagation::Stop;
+                }
+                gdk::Key::Return | gdk::Key::KP_Enter => {
+                    let selected = *selected_index.borrow();
+                    if let Some(idx) = selected {
+                        capture_window(idx);
+                    }
+                    return gtk::glib::Propagation::Stop;
+                }
+                _ => {}
+            }
+
+            if modifiers.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK) {
+                return gtk::glib::Propagation::Proceed;
+            }
+            let Some(digit @ 1..=9) = key.to_unicode().and_then(|ch| ch.to_digit(10)) else {
+                return gtk::glib::Propagation::Proceed;
+            };
+
+            if let Some(row) = visible_rows(&list).get(digit as usize - 1) {
+                capture_window(row.index() as usize);
+            }
+            gtk::glib::Propagation::Stop
         });
     }
+    picker.add_controller(key_controller);
 
     picker.present();
 }
+
+fn visible_rows(list: &gtk::ListBox) -> Vec<gtk::ListBoxRow> {
+    (0..)
+        .map_while(|index| list.row_at_index(index))
+        .filter(|row| row.is_child_visible())
+        .collect()
+}
+
+fn app_icon(theme: &gtk::IconTheme, app_id: &str, scale: i32) -> gtk::IconPaintable {
+    let lowercase = app_id.to_lowercase();
+    let last_segment = lowercase.rsplit('.').next().unwrap_or_default().to_string();
+    let mut candidates = vec![app_id.to_string(), lowercase, last_segment];
+    candidates.retain(|name| !name.is_empty());
+    candidates.dedup();
+
+    let (icon_name, fallbacks) = match candidates.split_first() {
+        Some((first, rest)) => (first.as_str(), rest),
+        None => (FALLBACK_APP_ICON, &[][..]),
+    };
+    let mut fallbacks: Vec<&str> = fallbacks.iter().map(String::as_str).collect();
+    fallbacks.push(FALLBACK_APP_ICON);
+
+    theme.lookup_icon(
+        icon_name,
+        &fallbacks,
+        APP_ICON_SIZE,
+        scale,
+        gtk::TextDirection::None,
+        gtk::IconLookupFlags::empty(),
+    )
+}
+
+fn window_matches(window: &WindowInfo, query: &str) -> bool {
+    query.is_empty()
+        || window.title.to_lowercase().contains(query)
+        || window.app_id.to_lowercase().contains(query)
+}
+
+fn subtitle_markup(window: &WindowInfo, workspace_label: &str, query: &str) -> String {
+    format!(
+        "{}{}",
+        highlight_markup(&window.app_id, query),
+        markup_escape_text(&format!("  |  {workspace_label}  |  id {}", window.id))
+    )
+}
+
+fn highlight_markup(text: &str, query: &str) -> String {
+    let lowercase = text.to_lowercase();
+    let matched = (!query.is_empty() && lowercase.len() == text.len())
+        .then(|| lowercase.find(query))
+        .flatten()
+        .map(|start| (start, start + query.len()))
+        .filter(|(start, end)| text.is_char_boundary(*start) && text.is_char_boundary(*end));
+
+    match matched {
+        Some((start, end)) => format!(
+            "{}<b>{}</b>{}",
+            markup_escape_text(&text[..start]),
+            markup_escape_text(&text[start..end]),
+            markup_escape_text(&text[end..])
+        ),
+        None => markup_escape_text(text).to_string(),
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::capture::image_mime_type;
+use crate::config::config;
+use crate::deps::missing_tool_message;
+
+const CONNECT_TIMEOUT_SECS: &str = "10";
+const MAX_TIME_SECS: &str = "60";
+
+pub(crate) fn upload_image(
+    path: &Path,
+    endpoint: &str,
+    headers: &[(String, String)],
+) -> Result<String> {
+    let settings = config();
+    let mime_type = image_mime_type(path)?;
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail-with-body"])
+        .args(["--connect-timeout", CONNECT_TIMEOUT_SECS])
+        .args(["--max-time", MAX_TIME_SECS])
+        .arg("--form")
+        .arg(format!(
+            "{}=@{};type={mime_type}",
+            settings.upload_field,
+            path.display()
+        ));
+    for (name, value) in headers {
+        command.arg("--header").arg(format!("{name}: {value}"));
+    }
+    command.arg(endpoint);
+
+    let output = command
+        .output()
+        .with_context(|| missing_tool_message("curl"))?;
+    let body = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("上传失败: {} {}", stderr.trim(), body.trim());
+    }
+
+    let response: Value = serde_json::from_str(&body)
+        .with_context(|| format!("无法解析上传响应: {}", body.trim()))?;
+    let Some(url) =
+        lookup_json_path(&response, &settings.upload_response_path).and_then(Value::as_str)
+    else {
+        bail!("上传响应中找不到链接字段 {}", settings.upload_response_path);
+    };
+
+    Ok(url.to_string())
+}
+
+fn lookup_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| match segment.parse::<usize>() {
+            Ok(index) if current.is_array() => current.get(index),
+            _ => current.get(segment),
+        })
+}